@@ -0,0 +1,70 @@
+import { instanceToPlain } from 'class-transformer'
+import { BaseBotAdapter } from './BotAdapter.js'
+import type { AdapterEventType, AdapterOptions } from './types.js'
+import type { RenApiData } from './msgTypes.js'
+import { NapcatRenMessage } from './OneBot/napcatMsgTypes.js'
+
+/**
+ * 远程连接的传输层，由宿主实现（例如通过 Tauri 后端维持的连接）
+ */
+export interface RemoteBotTransport {
+    connect(): Promise<void>
+    disconnect(): Promise<void>
+    // 调用 API 并返回 echo 匹配的完整响应
+    callApi(action: string, params: any, timeout?: number): Promise<any>
+}
+
+/**
+ * RemoteBotAdapter: 连接由宿主维持的适配器
+ * 宿主通过 dispatch 推送事件，API 调用转交给传输层
+ */
+export class RemoteBotAdapter extends BaseBotAdapter {
+    private transport: RemoteBotTransport
+
+    constructor(id: string, transport: RemoteBotTransport, opts?: AdapterOptions) {
+        super(id, opts)
+        this.transport = transport
+    }
+
+    async connect(): Promise<void> {
+        await this.transport.connect()
+        this.connected = true
+    }
+
+    async disconnect(): Promise<void> {
+        await this.transport.disconnect()
+        this.connected = false
+    }
+
+    // 事件由宿主通过 dispatch 推送，这里无需处理原始数据
+    async get() {
+        return { ok: true }
+    }
+
+    /**
+     * 推送宿主收到的事件，message / message_mine 的数据为 RenMessage 结构的普通对象
+     */
+    dispatch(event: AdapterEventType, payload?: any) {
+        if (event === 'connected') this.connected = true
+        if (event === 'disconnected') this.connected = false
+        if ((event === 'message' || event === 'message_mine') && payload) {
+            const msg = Object.assign(new NapcatRenMessage(), payload, { time: new Date(payload.time ?? Date.now()) })
+            this.emitEvent(event, msg)
+            return
+        }
+        this.emitEvent(event, payload)
+    }
+
+    public async callApiAsync(message: RenApiData): Promise<any> {
+        await this.callApiSync(message)
+        return
+    }
+
+    public callApiSync(message: RenApiData): any {
+        if (!this.connected) throw new Error(`RemoteBotAdapter: ${this.id} is not connected`)
+        const plain = instanceToPlain(message)
+        return this.transport.callApi(plain.action, plain.params ?? {}, this.options?.syncTimeout)
+    }
+}
+
+export default RemoteBotAdapter
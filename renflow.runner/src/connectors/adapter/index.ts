@@ -1,3 +1,4 @@
 export * from './types.js'
 export * from './BotAdapter.js'
+export * from './RemoteBotAdapter.js'
 export { event } from './decorators.js'
@@ -248,6 +248,8 @@ export * from './nodes/index.js'
 export * from './workflow/index.js'
 export * from './connectors/adapter/msgTypes.js'
 export { connectorManager } from './connectors/index.js'
+export { BaseBotAdapter } from './connectors/adapter/BotAdapter.js'
+export { RemoteBotAdapter, type RemoteBotTransport } from './connectors/adapter/RemoteBotAdapter.js'
 
 /**
  * 全局初始化入口。
@@ -131,7 +131,9 @@ export class NodeManager {
                 log: (...args) => console.log(...args),
                 error: (...args) => console.error(...args),
                 warn: (...args) => console.warn(...args)
-            }
+            },
+            http: context?.http,
+            notice: context?.notice
         }
 
         /* eslint-enable no-console */
@@ -1,10 +1,11 @@
 import { Logger } from '../../utils/logger.js'
 import { fillTextTemplate } from '../../utils/node.js'
 import { BaseNode } from '../BaseNode.js'
-import type { NodeMetadata, NodeContext, NodeExecutionResult } from '../types.js'
+import type { NodeMetadata, NodeContext, NodeExecutionResult, HttpTransport, HttpTransportRequest } from '../types.js'
 
 /**
- * 简单的 HTTP 请求节点，基于全局 fetch（Node >=18 / 浏览器）
+ * 简单的 HTTP 请求节点
+ * 宿主提供了 HTTP 传输层（context.http）时通过它发起请求，否则使用全局 fetch（Node >=18 / 浏览器）
  */
 export class HttpRequestNode extends BaseNode {
 
@@ -57,6 +58,55 @@ export class HttpRequestNode extends BaseNode {
         }
     }
 
+    private parseText(text: string, responseType: string): any {
+        if (responseType !== 'json') return text
+        try {
+            return text ? JSON.parse(text) : null
+        } catch (e) {
+            // 解析失败则回退为原始文本
+            return text
+        }
+    }
+
+    /**
+     * 通过宿主的传输层发起请求，重试与超时由传输层处理
+     */
+    private async requestWithTransport(
+        http: HttpTransport,
+        options: HttpTransportRequest,
+        responseType: string,
+        context: NodeContext
+    ): Promise<NodeExecutionResult> {
+        const start = Date.now()
+        if (context && context.logger && typeof context.logger.log === 'function') {
+            context.logger.log(`HttpRequestNode ${options.method} ${options.url}`)
+        }
+        try {
+            const res = await http.request(options)
+            const body = typeof res.body === 'string' ? this.parseText(res.body, responseType) : res.body
+            return {
+                success: true,
+                output: {
+                    status: res.status,
+                    headers: res.headers || {},
+                    body,
+                    duration: Date.now() - start
+                }
+            }
+        } catch (err: any) {
+            return {
+                success: false,
+                error: `请求失败: ${err?.message || String(err)}`,
+                output: {
+                    status: null,
+                    headers: {},
+                    body: null,
+                    duration: Date.now() - start
+                }
+            }
+        }
+    }
+
     async execute(
         input: any,
         params: Record<string, any>,
@@ -107,6 +157,26 @@ export class HttpRequestNode extends BaseNode {
         const retries = Math.max(0, Number(params.retries || 0))
         const responseType = params.responseType || 'json'
 
+        if (context?.http) {
+            const query: Record<string, any> = {}
+            for (const k of Object.keys(queryObj || {})) {
+                if (queryObj[k] !== undefined && queryObj[k] !== null) query[k] = queryObj[k]
+            }
+            return await this.requestWithTransport(context.http, {
+                method,
+                url: rawUrl,
+                headers,
+                query,
+                body: body === undefined ? undefined
+                    : typeof body === 'object' ? { type: 'json', content: body } : { type: 'text', content: String(body) },
+                timeout,
+                retries,
+                retryDelay: 200,
+                // 与 fetch 一致，读取为文本后再按 responseType 解析
+                responseType: 'text'
+            }, responseType, context)
+        }
+
         const start = Date.now()
         for (let attempt = 1; attempt <= retries + 1; attempt++) {
             // log attempt
@@ -140,18 +210,7 @@ export class HttpRequestNode extends BaseNode {
                 let parsed: any = null
                 try {
                     // 先读取为文本，避免多次消费 body 导致的 locked 错误
-                    const text = await res.text()
-
-                    if (responseType === 'json') {
-                        try {
-                            parsed = text ? JSON.parse(text) : null
-                        } catch (e) {
-                            // 解析失败则回退为原始文本
-                            parsed = text
-                        }
-                    } else {
-                        parsed = text
-                    }
+                    parsed = this.parseText(await res.text(), responseType)
                 } catch (ex) {
                     return {
                         success: false,
@@ -0,0 +1,104 @@
+import { BaseBotAdapter } from '../../connectors/index.js'
+import { RenMessage } from '../../connectors/adapter/msgTypes.js'
+import { BaseNode } from '../BaseNode.js'
+import type { NodeMetadata, NodeContext, NodeExecutionResult } from '../types.js'
+import { fillTextTemplate, getGlobal } from '../../utils/node.js'
+
+/**
+ * 发送系统通知节点
+ * 通知带有回复输入框，回复内容由触发本工作流的 Bot 发回原会话（notification_replied 触发器）
+ */
+export class SendNoticeNode extends BaseNode {
+    metadata: NodeMetadata = {
+        id: 'send-notice',
+        name: '发送系统通知',
+        description: '为触发消息发送可回复的系统通知',
+        category: 'bot',
+        icon: 'bell',
+        params: [
+            {
+                key: 'title',
+                label: '通知标题',
+                type: 'input',
+                placeholder: '输入通知标题',
+                required: true,
+                dynamic: true
+            },
+            {
+                key: 'body',
+                label: '通知内容',
+                type: 'textarea',
+                placeholder: '输入通知内容',
+                required: true,
+                dynamic: true
+            }
+        ],
+        outputSchema: [
+            {
+                key: 'title',
+                label: '通知标题',
+                type: 'string',
+                description: '发送的通知标题'
+            },
+            {
+                key: 'sent',
+                label: '发送状态',
+                type: 'boolean',
+                description: '通知是否成功发送'
+            }
+        ]
+    }
+
+    async execute(
+        input: any,
+        params: Record<string, any>,
+        context: NodeContext
+    ): Promise<NodeExecutionResult> {
+        if (!context.notice) {
+            return {
+                success: false,
+                error: '当前环境不支持发送系统通知'
+            }
+        }
+
+        const bot = getGlobal(context, 'bot') as BaseBotAdapter
+        if (!bot || !bot.id) {
+            return {
+                success: false,
+                error: 'bot 参数无效'
+            }
+        }
+
+        const message = getGlobal(context, 'trigger') as RenMessage
+        const id = message?.groupId || message?.userId
+        if(id == undefined) {
+            return {
+                success: false,
+                error: '消息体异常'
+            }
+        }
+
+        const title = fillTextTemplate(params.title, input, context)
+        const sent = await context.notice.send({
+            title,
+            body: fillTextTemplate(params.body, input, context),
+            tag: String(id),
+            type: message.messageType,
+            // 回复通知时由发出通知的 Bot 发送
+            bot: bot.id
+        })
+        if (!sent) {
+            return {
+                success: false,
+                error: '发送通知失败'
+            }
+        }
+        return {
+            success: true,
+            output: {
+                title,
+                sent: true
+            }
+        }
+    }
+}
@@ -6,6 +6,7 @@ import { IfElseNode } from './IfElseNode.js'
 import { HtmlRenderNode } from './HtmlRenderNode.js'
 import { HttpRequestNode } from './HttpRequestNode.js'
 import { SendMessageNode } from './SendMessageNode.js'
+import { SendNoticeNode } from './SendNoticeNode.js'
 import { CommandAnalNode } from './CommandAnalNode.js'
 import { MergeNode } from './MergeNode.js'
 
@@ -17,6 +18,7 @@ export const builtinNodes = [
     new NoteNode(),
     new SendTextNode(),
     new SendMessageNode(),
+    new SendNoticeNode(),
     new HtmlRenderNode(),
     new HttpRequestNode(),
     new CustomJSNode(),
@@ -30,6 +32,7 @@ export {
     NoteNode,
     SendTextNode,
     SendMessageNode,
+    SendNoticeNode,
     HtmlRenderNode,
     HttpRequestNode,
     CustomJSNode,
@@ -92,6 +92,60 @@ export interface NodeMetadata {
     settingsComponent?: string
 }
 
+/**
+ * HTTP 请求选项，字段与桌面端的 sys:httpRequest 一致
+ */
+export interface HttpTransportRequest {
+    method: string
+    url: string
+    headers?: Record<string, any>
+    /** 查询参数，数组值会生成多个同名参数 */
+    query?: Record<string, any>
+    body?: { type: 'text', content: string, contentType?: string } | { type: 'json', content: any }
+    /** 单次请求的超时时间（毫秒） */
+    timeout?: number
+    /** 连接失败、超时、429 和 5xx 时的重试次数 */
+    retries?: number
+    /** 首次重试前的等待时间（毫秒），之后每次翻倍 */
+    retryDelay?: number
+    responseType?: 'text' | 'json'
+}
+
+export interface HttpTransportResponse {
+    status: number
+    headers: Record<string, string>
+    body: any
+}
+
+/**
+ * HTTP 传输层，由宿主实现（例如桌面端由后端发起请求，不受 CORS 与请求头限制）
+ */
+export interface HttpTransport {
+    request(options: HttpTransportRequest): Promise<HttpTransportResponse>
+}
+
+/**
+ * 系统通知选项
+ */
+export interface NoticeTransportRequest {
+    title: string
+    body: string
+    /** 通知分类，与 type 一起决定点击、回复时的目标 */
+    tag: string
+    type: string
+    /** 发出通知的 Bot ID，回复通知时由它发送消息 */
+    bot?: string
+    icon?: string
+    image?: string
+}
+
+/**
+ * 系统通知传输层，由宿主实现（例如桌面端由后端发送系统通知）
+ */
+export interface NoticeTransport {
+    send(options: NoticeTransportRequest): Promise<boolean>
+}
+
 /**
  * 节点执行上下文
  */
@@ -108,6 +162,10 @@ export interface NodeContext {
         error: (...args: any[]) => void
         warn: (...args: any[]) => void
     }
+    /** 宿主提供的 HTTP 传输层，未提供时使用全局 fetch */
+    http?: HttpTransport
+    /** 宿主提供的系统通知传输层，未提供时无法发送通知 */
+    notice?: NoticeTransport
 }
 
 /**
@@ -63,6 +63,7 @@ export class WorkflowConverter {
             trigger,
             entryNode,
             nodes,
+            ...(vueFlowWorkflow.timeout ? { timeout: vueFlowWorkflow.timeout } : {}),
             createdAt: vueFlowWorkflow.createdAt,
             updatedAt: vueFlowWorkflow.updatedAt
         }
@@ -4,7 +4,7 @@
  */
 
 import type { WorkflowExecution, ExecutionNode } from './types.js'
-import type { NodeContext, NodeExecutionResult } from '../nodes/types.js'
+import type { HttpTransport, NodeContext, NodeExecutionResult } from '../nodes/types.js'
 import { NodeManager } from '../nodes/NodeManager.js'
 import { Logger } from '../utils/logger.js'
 
@@ -78,6 +78,10 @@ export interface ExecutionOptions {
     callback?: ExecutionCallback
     /** 初始全局变量（会被复制到 ExecutionContext.globalState），可在此传入 bot 等对象 */
     initialGlobals?: Record<string, any>
+    /** HTTP 请求节点使用的传输层 */
+    http?: HttpTransport
+    /** 发送通知节点使用的传输层 */
+    notice?: NoticeTransport
 }
 
 export class WorkflowEngine {
@@ -244,7 +248,9 @@ export class WorkflowEngine {
                 warn: (...args: any[]) => {
                     this.addLog(context, nodeId, 'warn', args.join(' '))
                 }
-            }
+            },
+            http: options.http,
+            notice: options.notice
         }
 
         let result: NodeExecutionResult
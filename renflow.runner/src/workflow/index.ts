@@ -10,6 +10,7 @@ import { RenMessage, WorkflowConverter, WorkflowEngine, WorkflowExecutionResult
 import { Logger } from '../utils/logger.js'
 import { WorkflowExecution } from './types.js'
 import { BaseBotAdapter } from '../connectors/index.js'
+import type { HttpTransport, NoticeTransport } from '../nodes/types.js'
 import { startsWithArray } from '../utils/util.js'
 
 export * from './types.js'
@@ -26,7 +27,7 @@ export * from './engine.js'
 export async function runWorkflow(
     executionData: WorkflowExecution,
     data: any,
-    configs?: { minDelay?: number; timeout?: number, bot?: BaseBotAdapter },
+    configs?: { minDelay?: number; timeout?: number, bot?: BaseBotAdapter, http?: HttpTransport, notice?: NoticeTransport },
     callbacks?: {
         onNodeStart?: (nodeId: string) => void | Promise<void>
         onNodeComplete?: (nodeId: string) => void | Promise<void>
@@ -55,10 +56,12 @@ export async function runWorkflow(
     const engine = new WorkflowEngine()
     await engine.execute(executionData, data, {
         minDelay: configs?.minDelay || 1000,
-        timeout: configs?.timeout || 60000,
+        timeout: configs?.timeout || executionData.timeout || 60000,
         initialGlobals: {
             ...(configs?.bot ? { bot: configs.bot } : {})
         },
+        http: configs?.http,
+        notice: configs?.notice,
         callback: {
             onNodeStart: async (nodeId: string) => {
                 callbacks?.onNodeStart && await callbacks.onNodeStart(nodeId)
@@ -86,7 +89,7 @@ export async function runWorkflow(
 export async function runWorkflowByTrigger(
     executionData: WorkflowExecution[],
     triggerData: any,
-    configs?: { minDelay?: number; timeout?: number, bot?: BaseBotAdapter },
+    configs?: { minDelay?: number; timeout?: number, bot?: BaseBotAdapter, http?: HttpTransport, notice?: NoticeTransport },
     callbacks?: {
         /**
          * 触发前检查，你可以通过返回 false 来阻止工作流执行
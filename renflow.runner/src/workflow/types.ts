@@ -61,6 +61,8 @@ export interface WorkflowExecution {
     entryNode: string | null
     /** 执行节点映射表 */
     nodes: Record<string, ExecutionNode>
+    /** 执行超时时间（毫秒），未设置时由运行方决定 */
+    timeout?: number
     /** 创建时间戳 */
     createdAt: number
     /** 更新时间戳 */
@@ -112,6 +114,7 @@ export interface VueFlowWorkflow {
     triggerLabel: string
     nodes: VueFlowNode[]
     edges: VueFlowEdge[]
+    timeout?: number
     createdAt: number
     updatedAt: number
 }
@@ -0,0 +1,6 @@
+fn main() {
+    if let Err(err) = app_lib::daemon::run(std::env::args().skip(1).collect()) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use log::error;
+use serde::Deserialize;
+use serde_json::Value;
+use tauri::{command, AppHandle, Emitter, State};
+
+use crate::connectors::{BotConfig, ConnectorInfo, ConnectorManager, EventSink};
+
+/// 连接器事件通过 AppHandle::emit 推送到所有窗口
+impl EventSink for AppHandle {
+    fn send(&self, event: &str, payload: Value) {
+        if let Err(err) = self.emit(event, payload) {
+            error!("发送 {} 事件失败: {}", event, err);
+        }
+    }
+}
+
+/// 创建并连接 Bot，事件通过 bot:* 推送
+#[command]
+pub async fn bot_connect(manager: State<'_, ConnectorManager>, data: BotConfig) -> Result<(), String> {
+    manager.connect(data).await
+}
+
+/// 断开并移除 Bot
+#[command]
+pub async fn bot_disconnect(manager: State<'_, ConnectorManager>, data: String) -> Result<(), String> {
+    manager.disconnect(&data).await
+}
+
+/// 获取所有 Bot 的连接状态
+#[command]
+pub fn bot_list(manager: State<'_, ConnectorManager>) -> Vec<ConnectorInfo> {
+    manager.list()
+}
+
+/// 获取后端支持的 Bot 类型
+#[command]
+pub fn bot_get_supported_types() -> Vec<String> {
+    ConnectorManager::supported_types()
+}
+
+#[derive(Deserialize)]
+pub struct BotCallApiPayload {
+    id: String,
+    action: String,
+    #[serde(default)]
+    params: Value,
+    /// 等待响应的超时时间（毫秒），默认使用 Bot 配置的 syncTimeout
+    timeout: Option<u64>,
+}
+
+/// 调用 Bot API，返回 echo 匹配的响应
+#[command]
+pub async fn bot_call_api(manager: State<'_, ConnectorManager>, data: BotCallApiPayload) -> Result<Value, String> {
+    let params = if data.params.is_null() { Value::Object(Default::default()) } else { data.params };
+    manager
+        .call_api(&data.id, &data.action, params, data.timeout.map(Duration::from_millis))
+        .await
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use log::error;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tauri::{command, AppHandle, Emitter, State};
+
+use crate::connectors::ConnectorManager;
+use crate::workflow::nodes::NodeExecutionResult;
+use crate::workflow::{
+    ExecutionCallback, ExecutionOptions, WorkflowEngine, WorkflowExecution, WorkflowExecutionResult,
+};
+
+/// 通过 AppHandle::emit 将节点执行状态推送到前端
+struct EmitCallback {
+    app: AppHandle,
+    workflow_id: String,
+}
+
+impl EmitCallback {
+    fn emit(&self, event: &str, payload: Value) {
+        if let Err(err) = self.app.emit(event, payload) {
+            error!("发送 {} 事件失败: {}", event, err);
+        }
+    }
+}
+
+impl ExecutionCallback for EmitCallback {
+    fn on_node_start(&self, node_id: &str, node_type: &str) {
+        self.emit("flow:nodeStart", json!({ "id": self.workflow_id, "nodeId": node_id, "nodeType": node_type }));
+    }
+
+    fn on_node_complete(&self, node_id: &str, result: &NodeExecutionResult) {
+        self.emit("flow:nodeComplete", json!({ "id": self.workflow_id, "nodeId": node_id, "result": result }));
+    }
+
+    fn on_node_error(&self, node_id: &str, error: &str) {
+        self.emit("flow:nodeError", json!({ "id": self.workflow_id, "nodeId": node_id, "error": error }));
+    }
+
+    fn on_workflow_complete(&self, result: &WorkflowExecutionResult) {
+        self.emit(
+            "flow:workflowComplete",
+            json!({ "id": self.workflow_id, "success": result.success, "error": result.error, "logs": result.logs }),
+        );
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlowRunPayload {
+    workflow: WorkflowExecution,
+    #[serde(default)]
+    data: Value,
+    min_delay: Option<u64>,
+    timeout: Option<u64>,
+    /// 触发工作流的 Bot（后端连接器 ID）
+    bot_id: Option<String>,
+}
+
+/// 在后端运行工作流，节点状态通过 flow:* 事件推送
+#[command]
+pub async fn flow_run(
+    app: AppHandle,
+    manager: State<'_, ConnectorManager>,
+    data: FlowRunPayload,
+) -> Result<WorkflowExecutionResult, String> {
+    let errors = data.workflow.validate();
+    if !errors.is_empty() {
+        return Err(format!("工作流执行数据验证失败: {}", json!(errors)));
+    }
+
+    let engine = WorkflowEngine::new();
+    let unsupported = engine.unsupported_node_types(&data.workflow);
+    if !unsupported.is_empty() {
+        return Err(format!("后端不支持的节点类型: {}", unsupported.join(", ")));
+    }
+
+    let options = ExecutionOptions {
+        min_delay: data.min_delay.unwrap_or(1000),
+        timeout: data.timeout.or(data.workflow.timeout).unwrap_or(60000),
+        callback: Some(Arc::new(EmitCallback {
+            app,
+            workflow_id: data.workflow.id.clone(),
+        })),
+        initial_globals: HashMap::new(),
+        bot: data.bot_id.as_deref().and_then(|id| manager.get(id)),
+    };
+    Ok(engine.execute(data.workflow, data.data, options).await)
+}
+
+/// 验证工作流执行数据，返回错误列表
+#[command]
+pub fn flow_validate(data: WorkflowExecution) -> Vec<String> {
+    data.validate()
+}
+
+/// 获取后端可执行的节点类型
+#[command]
+pub fn flow_get_supported_nodes() -> Vec<String> {
+    WorkflowEngine::new().node_manager().node_types()
+}
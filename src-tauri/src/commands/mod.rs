@@ -1,3 +1,6 @@
 pub mod sys;
 pub mod win;
 pub mod opt;
+pub mod flow;
+pub mod bot;
+pub mod wf;
@@ -1,17 +1,24 @@
-use std::{collections::HashMap, io::{self, Write}, fs::File, process::Command, sync::Arc, time::Duration};
-use crate::{PROXY_PORT};
+use std::{collections::{HashMap, HashSet}, io::Write, fs::File, process::Command, sync::Arc};
 
-use log::{debug, error, info};
-use reqwest::Client;
-use rfd::MessageLevel;
+use log::{debug, error, info, warn};
 use serde_json::Value;
 use tauri::{command, AppHandle, Emitter, Manager, State};
 use tauri_plugin_opener::OpenerExt;
-use futures_util::StreamExt;
 use user_notify::NotificationManager;
 use serde::{Deserialize};
 use std::path::PathBuf;
-use zip::write::FileOptions;
+use crate::workflow::WorkflowEngine;
+use crate::utils::asset_cache::{AssetCache, AssetCacheStats};
+use crate::utils::download::{DownloadInfo, DownloadManager, DownloadRequest};
+use crate::utils::http_client::{self, HttpClientConfig};
+use crate::utils::http_proxy::ProxyServer;
+use crate::utils::http_request::{HttpRequest, HttpRequests, HttpResponse};
+use crate::utils::notification::{reply_user_info, REPLY_CATEGORY};
+use crate::utils::process::{parse_allowlist, ProcessOutput, ProcessRequest, ProcessRunner};
+use crate::utils::redirect::{self, RedirectChain, RedirectOptions};
+use crate::workflow_store::WorkflowStore;
+use crate::workspace::secret::{self, ExportMode};
+use crate::workspace::{write_package, ImportPreview, Manifest, MergeStrategy, WorkspacePackage, BOTS_FILE, MANIFEST_FILE};
 
 #[command]
 pub async fn sys_front_loaded(
@@ -80,33 +87,25 @@ pub fn sys_get_release() -> Option<SystemInfo> {
     }
 }
 
+/// 获取重定向后的最终地址
 #[command]
 pub async fn sys_get_final_redirect_url(data: String) -> Result<String, String> {
-    let client = Client::builder()
-        .redirect(reqwest::redirect::Policy::custom(|attempt| {
-            if attempt.previous().len() >= 100 {
-                attempt.stop()
-            } else {
-                attempt.follow()
-            }
-        }))
-        .timeout(Duration::from_secs(10))
-        .build()
-        .map_err(|e| format!("Client build error: {}", e))?;
-
-    let res = client
-        .get(&data)
-        .send()
-        .await
-        .map_err(|e| format!("Request error: {}", e))?;
+    let chain = redirect::resolve(&RedirectOptions::new(data)).await?;
+    match chain.error {
+        Some(err) if chain.hops.is_empty() => Err(err),
+        _ => Ok(chain.final_url),
+    }
+}
 
-    let final_url = res.url().to_string();
-    Ok(final_url)
+/// 解析完整的重定向链，可选跟随 meta refresh 与 canonical 链接
+#[command]
+pub async fn sys_resolve_redirects(data: RedirectOptions) -> Result<RedirectChain, String> {
+    redirect::resolve(&data).await
 }
 
 #[command]
 pub async fn sys_get_html(data: String) -> Result<String, String> {
-    let client = reqwest::Client::new();
+    let client = http_client::client();
     let res = client.get(&data).send().await.map_err(|e| e.to_string())?;
 
     let content_type = res
@@ -124,7 +123,7 @@ pub async fn sys_get_html(data: String) -> Result<String, String> {
 
 #[command]
 pub async fn sys_get_api(data: String) -> Result<Value, String> {
-    let client = reqwest::Client::new();
+    let client = http_client::client();
     let res = client.get(&data).send().await.map_err(|e| e.to_string())?;
 
     let content_type = res
@@ -140,80 +139,73 @@ pub async fn sys_get_api(data: String) -> Result<Value, String> {
     }
 }
 
+/// 由后端发起的通用 HTTP 请求，不受 CORS 限制
 #[command]
-pub async fn sys_download(app_handle: AppHandle, downloadPath: String, fileName: String) -> Result<(), String> {
-    info!("下载文件：{:?}", downloadPath);
-
-    let folder = rfd::FileDialog::new()
-        .pick_folder();
+pub async fn sys_http_request(requests: State<'_, HttpRequests>, data: HttpRequest) -> Result<HttpResponse, String> {
+    debug!("HTTP 请求：{} {}", data.method, data.url);
+    requests.send(data).await
+}
 
-    let folder_path = match folder {
-        Some(folder) => folder,
-        None => {
-            info!("用户取消了选择文件夹");
-            app_handle.emit("sys:downloadCancel", "").unwrap();
-            return Ok(());
-        }
-    };
+/// 取消带有 requestId 的 HTTP 请求
+#[command]
+pub fn sys_cancel_http_request(requests: State<'_, HttpRequests>, data: String) -> bool {
+    requests.cancel(&data)
+}
 
-    let filepath = folder_path.join(fileName);
-    debug!("下载文件路径: {:?}", filepath);
-    // 检查文件是否存在
-    if filepath.exists() {
-        let result = rfd::MessageDialog::new()
-            .set_title("文件已存在")
-            .set_description("文件已存在，是否覆盖？")
-            .set_level(MessageLevel::Warning)
-            .set_buttons(rfd::MessageButtons::YesNo)
-            .show();
-        if result != rfd::MessageDialogResult::Yes {
-            info!("用户取消了下载");
-            app_handle.emit("sys:downloadCancel", "").unwrap();
-            return Ok(());
+/// 开始下载，ask 为 true 时先让用户选择保存目录，用户取消时返回 None
+#[command]
+pub async fn sys_download(
+    downloads: State<'_, Arc<DownloadManager>>,
+    data: DownloadRequest,
+    ask: Option<bool>,
+) -> Result<Option<DownloadInfo>, String> {
+    let mut request = data;
+    if ask.unwrap_or(false) {
+        match rfd::FileDialog::new().set_directory(downloads.dir()).pick_folder() {
+            Some(folder) => request.dir = Some(folder),
+            None => {
+                info!("用户取消了选择文件夹");
+                return Ok(None);
+            }
         }
     }
+    downloads.start(request).map(Some)
+}
 
-    let result = async {
-        let client = Client::new();
-        let response = client.get(downloadPath).send().await.map_err(|e| format!("请求失败: {}", e))?;
-
-        let total_size = response
-            .content_length()
-            .ok_or_else(|| {
-                io::Error::new(io::ErrorKind::Other, "无法获取文件大小")
-            })?;
-
-        let mut file = File::create(&filepath)?;
-        let mut stream = response.bytes_stream();
-
-        let mut downloaded: u64 = 0;
-        while let Some(item) = stream.next().await {
-            let chunk = item?;
-            file.write_all(&chunk)?;
-            downloaded += chunk.len() as u64;
-
-            let percent = downloaded as f64 / total_size as f64 * 100.0;
-            print!("\r已下载: {:.2}%", percent);
-            let mut payload: HashMap<&str, Value> = HashMap::new();
-            payload.insert("lengthComputable", true.into());
-            payload.insert("loaded", downloaded.into());
-            payload.insert("total", total_size.into());
-            app_handle.emit("sys:downloadBack", payload).unwrap();
-            io::stdout().flush()?;
-        }
+/// 暂停下载
+#[command]
+pub fn sys_pause_download(downloads: State<'_, Arc<DownloadManager>>, data: String) -> Result<(), String> {
+    downloads.pause(&data)
+}
 
-        Ok::<_, Box<dyn std::error::Error>>(())
-    }.await;
-    if let Err(e) = result {
-        error!("下载失败: {}", e);
-        app_handle.emit("sys:downloadError", e.to_string()).unwrap();
-        return Err(e.to_string());
-    }
+/// 继续暂停或失败的下载
+#[command]
+pub async fn sys_resume_download(downloads: State<'_, Arc<DownloadManager>>, data: String) -> Result<(), String> {
+    downloads.resume(&data)
+}
+
+/// 取消下载并删除已下载的部分
+#[command]
+pub fn sys_cancel_download(downloads: State<'_, Arc<DownloadManager>>, data: String) -> Result<(), String> {
+    downloads.cancel(&data)
+}
+
+/// 获取本次运行中的所有下载
+#[command]
+pub fn sys_list_downloads(downloads: State<'_, Arc<DownloadManager>>) -> Vec<DownloadInfo> {
+    downloads.list()
+}
 
-    println!("\r");
-    info!("下载完成: {:?}", filepath);
+/// 设置默认下载目录（.settings.dat 的 download_dir）
+#[command]
+pub fn sys_set_download_dir(app: AppHandle, downloads: State<'_, Arc<DownloadManager>>, data: String) -> Result<(), String> {
+    use tauri_plugin_store::StoreExt;
 
-    return Ok(())
+    let store = app.store(".settings.dat").map_err(|e| e.to_string())?;
+    store.set("download_dir", Value::String(data.clone()));
+    store.save().map_err(|e| e.to_string())?;
+    downloads.set_dir(PathBuf::from(data));
+    Ok(())
 }
 
 #[command]
@@ -232,13 +224,16 @@ pub async fn sys_send_notice(
             .body(data.get("body").unwrap().as_str().unwrap())
             .set_thread_id(data.get("tag").unwrap().as_str().unwrap())
             .set_xdg_category(user_notify::XdgNotificationCategory::ImReceived)
-            .set_category_id("cn.stapxs.qqweb.reply");
-        // 设置 payload
-        let mut user_info = HashMap::new();
-        user_info.insert(
-            "NotificationPayload".to_owned(),
-            data.get("tag").unwrap().as_str().unwrap().to_owned() +
-                "/" + data.get("type").unwrap().as_str().unwrap()
+            .set_category_id(REPLY_CATEGORY);
+        // 设置 payload，回复通知时由发出通知的 Bot 发送
+        let bot = data.get("bot").and_then(|v| v.as_str());
+        if bot.is_none() {
+            warn!("消息通知缺少 bot，回复此通知时将被忽略");
+        }
+        let user_info = reply_user_info(
+            data.get("tag").unwrap().as_str().unwrap(),
+            data.get("type").unwrap().as_str().unwrap(),
+            bot
         );
         notification = notification.set_user_info(user_info);
         // 获取图片，优先 image，没有为 icon；都是 url
@@ -253,7 +248,7 @@ pub async fn sys_send_notice(
         if !final_image.is_empty() {
             if final_image.starts_with("http://") || final_image.starts_with("https://") {
                 // 下载图片缓存
-                let client = Client::new();
+                let client = http_client::client();
                 let response = client.get(final_image).send().await.map_err(|e| format!("请求失败: {}", e))?;
                 if response.status().is_success() {
                     let bytes = response.bytes().await.map_err(|e| format!("读取响应失败: {}", e))?;
@@ -360,23 +355,49 @@ pub async fn sys_close_all_notice(
     return Ok("success".to_string());
 }
 
+/// 运行白名单中的程序，返回退出码与输出
 #[command]
-pub fn sys_run_command(data: String) -> HashMap<String, Value> {
-    let mut ret: HashMap<String, Value> = HashMap::new();
+pub async fn sys_run_process(runner: State<'_, ProcessRunner>, data: ProcessRequest) -> Result<ProcessOutput, String> {
+    runner.run(data).await
+}
 
-    match Command::new(data).output() {
-        Ok(output) => {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            ret.insert("success".to_string(), true.into());
-            ret.insert("message".to_string(), stdout.into());
-        },
-        Err(e) => {
-            ret.insert("success".to_string(), false.into());
-            ret.insert("message".to_string(), e.to_string().into());
+/// 获取允许运行的程序白名单
+#[command]
+pub fn sys_get_process_allowlist(runner: State<'_, ProcessRunner>) -> Vec<String> {
+    runner.allowlist()
+}
+
+/// 设置允许运行的程序白名单（.settings.dat 的 process_allowlist），新增的程序需要用户在系统对话框中确认
+#[command]
+pub async fn sys_set_process_allowlist(
+    app: AppHandle,
+    runner: State<'_, ProcessRunner>,
+    data: Vec<String>,
+) -> Result<(), String> {
+    use tauri_plugin_store::StoreExt;
+
+    let allowlist = parse_allowlist(&Value::from(data));
+    let current = runner.allowlist();
+    let added: Vec<&String> = allowlist.iter().filter(|entry| !current.contains(entry)).collect();
+    if !added.is_empty() {
+        let names: Vec<&str> = added.iter().map(|s| s.as_str()).collect();
+        let result = rfd::MessageDialog::new()
+            .set_title("允许运行程序")
+            .set_description(format!("工作流将可以运行以下程序：\n\n{}\n\n是否允许？", names.join("\n")))
+            .set_level(rfd::MessageLevel::Warning)
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show();
+        if result != rfd::MessageDialogResult::Yes {
+            return Err("用户拒绝了修改程序白名单".to_string());
         }
     }
 
-    return ret;
+    let store = app.store(".settings.dat").map_err(|e| e.to_string())?;
+    store.set("process_allowlist", Value::from(allowlist.clone()));
+    store.save().map_err(|e| e.to_string())?;
+    info!("程序白名单已更新：{:?}", allowlist);
+    runner.set_allowlist(allowlist);
+    Ok(())
 }
 
 #[command]
@@ -384,11 +405,109 @@ pub fn sys_open_in_browser(app_handle: tauri::AppHandle, data: String) {
     let _ = app_handle.opener().open_path(data, None::<&str>);
 }
 
+/// 设置中固定的代理端口（.settings.dat 的 proxy_port），未设置时自动选择
+pub fn proxy_port_setting<R: tauri::Runtime>(store: &tauri_plugin_store::Store<R>) -> Option<u16> {
+    store
+        .get("proxy_port")
+        .and_then(|v| v.as_u64().or_else(|| v.as_str()?.parse().ok()))
+        .and_then(|port| u16::try_from(port).ok())
+        .filter(|port| *port != 0)
+}
+
+/// 设置中的出站 HTTP 配置（上游代理、CA 证书、User-Agent 与超时）
+pub fn http_client_setting<R: tauri::Runtime>(store: &tauri_plugin_store::Store<R>) -> HttpClientConfig {
+    HttpClientConfig::from_settings(|key| {
+        store.get(key).and_then(|v| match v {
+            Value::String(s) => Some(s),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        })
+    })
+}
+
+fn proxy_info(proxy: &ProxyServer) -> Value {
+    serde_json::json!({ "port": proxy.port(), "token": proxy.token, "pageToken": proxy.page_token })
+}
+
+/// 代理端口变化时通知前端
+fn emit_proxy_port(app: &AppHandle, proxy: &ProxyServer, old_port: Option<u16>) {
+    if proxy.port() != old_port {
+        info!("代理端口已变更：{:?} -> {:?}", old_port, proxy.port());
+        let _ = app.emit("sys:proxyPort", proxy_info(proxy));
+    }
+}
+
+/// 获取本地代理的端口与访问令牌
 #[command]
-pub fn sys_run_proxy() -> u16 {
-    return PROXY_PORT.get().unwrap().clone();
+pub fn sys_run_proxy(proxy: State<'_, ProxyServer>) -> Value {
+    proxy_info(&proxy)
 }
 
+/// 启动本地代理，已在运行时返回当前端口
+#[command]
+pub async fn sys_start_proxy(app: AppHandle, proxy: State<'_, ProxyServer>) -> Result<u16, String> {
+    use tauri_plugin_store::StoreExt;
+
+    let store = app.store(".settings.dat").map_err(|e| e.to_string())?;
+    let old_port = proxy.port();
+    let port = proxy.start(proxy_port_setting(&store))?;
+    emit_proxy_port(&app, &proxy, old_port);
+    Ok(port)
+}
+
+/// 停止本地代理
+#[command]
+pub async fn sys_stop_proxy(app: AppHandle, proxy: State<'_, ProxyServer>) -> Result<(), String> {
+    let old_port = proxy.port();
+    if proxy.stop().await {
+        info!("代理服务器已停止");
+    }
+    emit_proxy_port(&app, &proxy, old_port);
+    Ok(())
+}
+
+/// 重启本地代理，会重新读取设置中的端口
+#[command]
+pub async fn sys_restart_proxy(app: AppHandle, proxy: State<'_, ProxyServer>) -> Result<u16, String> {
+    use tauri_plugin_store::StoreExt;
+
+    let store = app.store(".settings.dat").map_err(|e| e.to_string())?;
+    let old_port = proxy.port();
+    let port = proxy.restart(proxy_port_setting(&store)).await?;
+    info!("代理服务器已重启，端口：{}", port);
+    emit_proxy_port(&app, &proxy, old_port);
+    Ok(port)
+}
+
+/// 按当前设置重新创建出站 HTTP 客户端，本地代理服务需重启后生效
+#[command]
+pub fn sys_reload_http_client(app: AppHandle) -> Result<(), String> {
+    use tauri_plugin_store::StoreExt;
+
+    let store = app.store(".settings.dat").map_err(|e| e.to_string())?;
+    http_client::configure(http_client_setting(&store))
+}
+
+/// 获取资源缓存的命中统计
+#[command]
+pub fn sys_asset_cache_stats(cache: State<'_, Arc<AssetCache>>) -> AssetCacheStats {
+    cache.stats()
+}
+
+/// 清空资源缓存
+#[command]
+pub fn sys_clear_asset_cache(cache: State<'_, Arc<AssetCache>>) {
+    cache.clear();
+}
+
+/// 安全相关的设置，启动时会被读取，只能通过带确认对话框的专用命令或手动编辑设置文件修改
+const PROTECTED_SETTINGS: &[&str] = &[
+    "process_allowlist",
+    "proxy_allow_hosts",
+    "proxy_deny_hosts",
+    "proxy_insecure_hosts",
+];
+
 // 设置 Store 值
 #[command]
 pub async fn sys_set_store_value(
@@ -398,6 +517,11 @@ pub async fn sys_set_store_value(
 ) -> Result<(), String> {
     use tauri_plugin_store::StoreExt;
 
+    if PROTECTED_SETTINGS.contains(&key.as_str()) {
+        error!("拒绝通过 sys_set_store_value 修改受保护的设置：{}", key);
+        return Err(format!("设置 {} 不能通过此方法修改", key));
+    }
+
     let store = app.store(".settings.dat")
         .map_err(|e| format!("Failed to get store: {}", e))?;
 
@@ -412,7 +536,16 @@ pub async fn sys_set_store_value(
 pub struct ExportWorkflowFile { filename: String, content: String }
 
 #[derive(Deserialize)]
-pub struct ExportPayload { path: Option<String>, bots: Vec<serde_json::Value>, workflows: Vec<ExportWorkflowFile> }
+#[serde(rename_all = "camelCase")]
+pub struct ExportPayload {
+    path: Option<String>,
+    bots: Vec<serde_json::Value>,
+    workflows: Vec<ExportWorkflowFile>,
+    /// 导出方式：plain、strip（移除敏感信息）、encrypt（使用 passphrase 加密）
+    #[serde(default)]
+    mode: ExportMode,
+    passphrase: Option<String>,
+}
 
 #[command]
 pub async fn sys_export_workspace(data: ExportPayload) -> Result<(), String> {
@@ -432,20 +565,119 @@ pub async fn sys_export_workspace(data: ExportPayload) -> Result<(), String> {
             }
         }
     };
-    let file = File::create(&target).map_err(|e| e.to_string())?;
-    let mut zip = zip::ZipWriter::new(file);
-    let opts = FileOptions::default();
-    let bots_str = serde_json::to_string(&data.bots).map_err(|e| e.to_string())?;
-    zip.start_file("bots.config", opts).map_err(|e| e.to_string())?;
-    zip.write_all(bots_str.as_bytes()).map_err(|e| e.to_string())?;
-    for wf in data.workflows.iter() {
-        zip.start_file(&wf.filename, opts).map_err(|e| e.to_string())?;
-        zip.write_all(wf.content.as_bytes()).map_err(|e| e.to_string())?;
+    let mut bots = data.bots;
+    let mut workflows = data.workflows;
+    if data.mode == ExportMode::Strip {
+        bots.iter_mut().for_each(secret::strip_bot);
+        for wf in workflows.iter_mut() {
+            if let Ok(mut content) = serde_json::from_str::<Value>(&wf.content) {
+                secret::strip_workflow(&wf.filename, &mut content);
+                wf.content = content.to_string();
+            }
+        }
     }
-    zip.finish().map_err(|e| e.to_string())?;
+    let bots_str = serde_json::to_string(&bots).map_err(|e| e.to_string())?;
+    let mut files: Vec<(&str, &str)> = vec![(BOTS_FILE, bots_str.as_str())];
+    files.extend(workflows.iter().map(|wf| (wf.filename.as_str(), wf.content.as_str())));
+    let manifest = serde_json::to_string_pretty(&Manifest::build(&files)).map_err(|e| e.to_string())?;
+    files.insert(0, (MANIFEST_FILE, manifest.as_str()));
+    let mut package = write_package(&files)?;
+    if data.mode == ExportMode::Encrypt {
+        package = write_package(&secret::encrypt(&package, data.passphrase.as_deref().unwrap_or_default())?)?;
+    }
+    std::fs::write(&target, package).map_err(|e| e.to_string())?;
     Ok(())
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportPayload {
+    path: Option<String>,
+    strategy: Option<MergeStrategy>,
+    /// 前端可用的节点类型，与后端支持的节点类型一起用于检查未知节点
+    #[serde(default)]
+    known_node_types: Vec<String>,
+    /// 加密包的密码
+    passphrase: Option<String>,
+    /// 导出时被移除的敏感字段，SecretField.key -> 值
+    #[serde(default)]
+    secrets: HashMap<String, String>,
+}
+
+/// 读取 Store 中以 JSON 字符串保存的列表
+fn read_store_list(app: &AppHandle, file: &str, key: &str) -> Result<Vec<Value>, String> {
+    use tauri_plugin_store::StoreExt;
+
+    let store = app.store(file).map_err(|e| e.to_string())?;
+    match store.get(key) {
+        Some(Value::String(s)) if !s.is_empty() => serde_json::from_str(&s).map_err(|e| format!("{} 数据解析失败: {}", key, e)),
+        Some(Value::Array(list)) => Ok(list),
+        _ => Ok(Vec::new()),
+    }
+}
+
+fn write_store_list(app: &AppHandle, file: &str, key: &str, list: &[Value]) -> Result<(), String> {
+    use tauri_plugin_store::StoreExt;
+
+    let store = app.store(file).map_err(|e| e.to_string())?;
+    let value = serde_json::to_string(list).map_err(|e| e.to_string())?;
+    store.set(key, Value::String(value));
+    store.save().map_err(|e| e.to_string())
+}
+
+/// 导入工作区包：未指定 strategy 时只返回预览，指定后按冲突处理方式写入
+#[command]
+pub async fn sys_import_workspace(
+    app: AppHandle,
+    workflow_store: State<'_, WorkflowStore>,
+    data: ImportPayload,
+) -> Result<Option<ImportPreview>, String> {
+    let path: PathBuf = match data.path {
+        Some(ref p) if !p.is_empty() => PathBuf::from(p),
+        _ => {
+            let dialog = rfd::FileDialog::new().add_filter("RenFlow Package", &["rfw"]);
+            match dialog.pick_file() {
+                Some(p) => p,
+                None => return Ok(None),
+            }
+        }
+    };
+    if data.passphrase.is_none() && WorkspacePackage::is_encrypted(&path)? {
+        // 需要密码，由前端询问后再次调用
+        return Ok(Some(ImportPreview {
+            path: path.to_string_lossy().to_string(),
+            encrypted: true,
+            locked: true,
+            ..Default::default()
+        }));
+    }
+    let mut package = WorkspacePackage::open(&path, data.passphrase.as_deref())?;
+    let mut bots = read_store_list(&app, ".options.dat", "bots")?;
+    let mut workflows = workflow_store.all();
+
+    let mut preview = match data.strategy {
+        None => package.preview(&bots, &workflows),
+        Some(strategy) => {
+            package.fill_secrets(&data.secrets);
+            let result = package.merge(strategy, &mut bots, &mut workflows);
+            write_store_list(&app, ".options.dat", "bots", &bots)?;
+            for item in result.workflows.iter().filter(|item| matches!(item.action, Some(a) if a != "skipped")) {
+                let id = item.new_id.as_ref().unwrap_or(&item.id);
+                if let Some(workflow) = workflows.iter().find(|w| w.get("id").and_then(Value::as_str) == Some(id)) {
+                    workflow_store.save(workflow)?;
+                }
+            }
+            info!("已导入工作区包: {}", path.display());
+            result
+        }
+    };
+    let mut known: HashSet<String> = WorkflowEngine::new().node_manager().node_types().into_iter().collect();
+    known.extend(data.known_node_types);
+    preview.unknown_node_types = package.unknown_node_types(&known);
+    preview.path = path.to_string_lossy().to_string();
+    Ok(Some(preview))
+}
+
 /// 获取 Store 值
 #[command]
 pub async fn sys_get_store_value(
@@ -0,0 +1,52 @@
+use serde_json::Value;
+use tauri::{command, State};
+
+use crate::workflow_store::{Revision, RevisionDiff, WorkflowStore};
+
+/// 获取工作流列表（不包含节点与连线）
+#[command]
+pub fn wf_list(store: State<'_, WorkflowStore>) -> Vec<Value> {
+    store.list()
+}
+
+/// 获取完整的工作流数据
+#[command]
+pub fn wf_get(store: State<'_, WorkflowStore>, data: String) -> Result<Option<Value>, String> {
+    store.get(&data)
+}
+
+/// 保存工作流，message 为本次修改的说明（可选）
+#[command]
+pub fn wf_save(store: State<'_, WorkflowStore>, data: Value, message: Option<String>) -> Result<(), String> {
+    store.save_with_message(&data, message.filter(|m| !m.is_empty()))
+}
+
+/// 删除工作流
+#[command]
+pub fn wf_delete(store: State<'_, WorkflowStore>, data: String) -> Result<(), String> {
+    store.delete(&data)
+}
+
+/// 获取工作流的历史版本列表（从新到旧）
+#[command]
+pub fn wf_revisions(store: State<'_, WorkflowStore>, data: String) -> Result<Vec<Revision>, String> {
+    store.revisions(&data)
+}
+
+/// 获取某个历史版本的完整内容
+#[command]
+pub fn wf_get_revision(store: State<'_, WorkflowStore>, id: String, revision: u64) -> Result<Revision, String> {
+    store.get_revision(&id, revision)
+}
+
+/// 比较两个历史版本的节点与连线，未指定 to 时与当前版本比较
+#[command]
+pub fn wf_diff(store: State<'_, WorkflowStore>, id: String, from: u64, to: Option<u64>) -> Result<RevisionDiff, String> {
+    store.diff(&id, from, to)
+}
+
+/// 将历史版本恢复为当前版本，返回恢复后的工作流
+#[command]
+pub fn wf_restore(store: State<'_, WorkflowStore>, id: String, revision: u64) -> Result<Value, String> {
+    store.restore(&id, revision)
+}
@@ -0,0 +1,196 @@
+//! Bot 连接器
+//!
+//! 在后端维持与 OneBot 实现之间的连接（连接、重连、API 调用），
+//! 收到的事件通过 [`EventSink`] 转发出去，不依赖 webview 的存活。
+
+pub mod onebot;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 连接器事件的接收端（Tauri 中为 AppHandle）
+pub trait EventSink: Send + Sync {
+    fn send(&self, event: &str, payload: Value);
+}
+
+/// Bot 连接器
+#[async_trait]
+pub trait BotConnector: Send + Sync {
+    fn id(&self) -> &str;
+
+    /// 连接器类型（对应 bots 配置中的 type）
+    fn connector_type(&self) -> &'static str;
+
+    fn is_connected(&self) -> bool;
+
+    /// 建立连接，首次连接失败时返回错误（开启重连时会在后台继续尝试）
+    async fn connect(&self) -> Result<(), String>;
+
+    /// 首次连接失败后是否仍在后台重试，不重试的连接器会在失败时被移除
+    fn keeps_retrying(&self) -> bool {
+        true
+    }
+
+    async fn disconnect(&self);
+
+    /// 调用 OneBot API，返回完整的响应数据
+    async fn call_api(&self, action: &str, params: Value, timeout: Option<Duration>)
+        -> Result<Value, String>;
+}
+
+/// Bot 配置，与前端保存的 bots 结构一致
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BotConfig {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(rename = "type")]
+    pub bot_type: String,
+    pub address: String,
+    #[serde(default)]
+    pub token: Option<String>,
+    pub reconnect: Option<bool>,
+    pub max_retries: Option<u32>,
+    pub retry_interval: Option<u64>,
+    pub sync_timeout: Option<u64>,
+    /// HTTP 类型接收 POST 上报的本地地址，例如 `127.0.0.1:5701/onebot`
+    #[serde(default)]
+    pub listen: Option<String>,
+    /// 上报签名（X-Signature）使用的密钥
+    #[serde(default)]
+    pub secret: Option<String>,
+}
+
+/// 连接器状态
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectorInfo {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub connector_type: String,
+    pub connected: bool,
+}
+
+/// 已注册的连接器，服务端类型的连接器会把接入的连接注册到这里
+pub type ConnectorRegistry = Arc<Mutex<HashMap<String, Arc<dyn BotConnector>>>>;
+
+/// 连接器管理器，负责创建、保存和查找连接器
+pub struct ConnectorManager {
+    sink: Arc<dyn EventSink>,
+    connectors: ConnectorRegistry,
+}
+
+impl ConnectorManager {
+    pub fn new(sink: Arc<dyn EventSink>) -> Self {
+        Self { sink, connectors: Arc::new(Mutex::new(HashMap::new())) }
+    }
+
+    /// 支持的连接器类型
+    pub fn supported_types() -> Vec<String> {
+        vec![
+            onebot::ws::TYPE.to_string(),
+            onebot::reverse::TYPE.to_string(),
+            onebot::http::TYPE.to_string(),
+        ]
+    }
+
+    /// 创建连接器并连接
+    pub async fn connect(&self, config: BotConfig) -> Result<(), String> {
+        let connector: Arc<dyn BotConnector> = {
+            let mut connectors = self.connectors.lock().unwrap();
+            if connectors.contains_key(&config.id) {
+                return Err(format!("已存在适配器: {}", config.id));
+            }
+            let connector: Arc<dyn BotConnector> = match config.bot_type.as_str() {
+                onebot::ws::TYPE => Arc::new(onebot::ws::OneBotWsConnector::new(&config, self.sink.clone())),
+                onebot::reverse::TYPE => Arc::new(onebot::reverse::ReverseWsServer::new(
+                    &config,
+                    self.sink.clone(),
+                    self.connectors.clone(),
+                )?),
+                onebot::http::TYPE => Arc::new(onebot::http::OneBotHttpConnector::new(&config, self.sink.clone())?),
+                other => return Err(format!("未实现的 Bot 适配器类型: {}", other)),
+            };
+            connectors.insert(config.id.clone(), connector.clone());
+            connector
+        };
+        let result = connector.connect().await;
+        if result.is_err() && !connector.keeps_retrying() {
+            self.connectors.lock().unwrap().remove(&config.id);
+        }
+        result
+    }
+
+    /// 断开并移除连接器
+    pub async fn disconnect(&self, id: &str) -> Result<(), String> {
+        let connector = self.connectors.lock().unwrap().remove(id);
+        match connector {
+            Some(connector) => {
+                connector.disconnect().await;
+                Ok(())
+            }
+            None => Err(format!("未找到适配器: {}", id)),
+        }
+    }
+
+    /// 断开所有连接器
+    pub async fn disconnect_all(&self) {
+        let connectors: Vec<_> = self.connectors.lock().unwrap().drain().map(|(_, c)| c).collect();
+        for connector in connectors {
+            connector.disconnect().await;
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<Arc<dyn BotConnector>> {
+        self.connectors.lock().unwrap().get(id).cloned()
+    }
+
+    pub fn list(&self) -> Vec<ConnectorInfo> {
+        let mut list: Vec<ConnectorInfo> = self
+            .connectors
+            .lock()
+            .unwrap()
+            .values()
+            .map(|c| ConnectorInfo {
+                id: c.id().to_string(),
+                connector_type: c.connector_type().to_string(),
+                connected: c.is_connected(),
+            })
+            .collect();
+        list.sort_by(|a, b| a.id.cmp(&b.id));
+        list
+    }
+
+    /// 通过连接器 id 调用 API
+    pub async fn call_api(
+        &self,
+        id: &str,
+        action: &str,
+        params: Value,
+        timeout: Option<Duration>,
+    ) -> Result<Value, String> {
+        let connector = self.get(id).ok_or_else(|| format!("未找到适配器: {}", id))?;
+        connector.call_api(action, params, timeout).await
+    }
+}
+
+/// 连接器测试共用的工具
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::*;
+
+    /// 记录收到的所有事件
+    #[derive(Default)]
+    pub struct RecordSink(pub Mutex<Vec<(String, Value)>>);
+
+    impl EventSink for RecordSink {
+        fn send(&self, event: &str, payload: Value) {
+            self.0.lock().unwrap().push((event.to_string(), payload));
+        }
+    }
+}
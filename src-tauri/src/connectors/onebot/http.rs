@@ -0,0 +1,248 @@
+//! OneBot v11 HTTP 连接器
+//!
+//! API 通过 `POST ${address}/${action}` 调用；上报事件由本地 `listen` 地址接收，
+//! 配置了 secret 时校验 `X-Signature: sha1=<HMAC-SHA1>`。
+
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use log::{debug, info, warn};
+use reqwest::Client;
+use serde_json::{json, Value};
+use sha1::Sha1;
+use tokio::sync::oneshot;
+use warp::http::{HeaderMap, StatusCode};
+use warp::hyper::body::Bytes;
+use warp::path::FullPath;
+use warp::reply::Reply;
+use warp::Filter;
+
+use super::{bind_server, dispatch_event, parse_listen_address};
+use crate::connectors::{BotConfig, BotConnector, EventSink};
+
+pub const TYPE: &str = "onebot-http";
+
+struct Receiver {
+    id: String,
+    path: String,
+    secret: Option<String>,
+    sink: Arc<dyn EventSink>,
+}
+
+pub struct OneBotHttpConnector {
+    id: String,
+    address: String,
+    token: Option<String>,
+    sync_timeout: Duration,
+    client: Client,
+    sink: Arc<dyn EventSink>,
+    receiver: Option<(SocketAddr, Arc<Receiver>)>,
+    connected: AtomicBool,
+    local_addr: Mutex<Option<SocketAddr>>,
+    shutdown: Mutex<Option<oneshot::Sender<()>>>,
+}
+
+/// 校验上报数据的签名
+fn verify_signature(secret: &str, signature: &str, body: &[u8]) -> bool {
+    let Some(Ok(expected)) = signature.trim().strip_prefix("sha1=").map(hex::decode) else {
+        return false;
+    };
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret.as_bytes()).expect("HMAC 可以使用任意长度的密钥");
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+impl Receiver {
+    /// 处理一次 POST 上报
+    fn receive(&self, path: FullPath, headers: HeaderMap, body: Bytes) -> warp::reply::Response {
+        if !self.path.is_empty() && path.as_str().trim_end_matches('/') != self.path {
+            return StatusCode::NOT_FOUND.into_response();
+        }
+        if let Some(secret) = &self.secret {
+            match headers.get("x-signature").and_then(|v| v.to_str().ok()) {
+                None => return StatusCode::UNAUTHORIZED.into_response(),
+                Some(signature) if !verify_signature(secret, signature, &body) => {
+                    warn!("HTTP 上报 {} 签名校验失败", self.id);
+                    return StatusCode::FORBIDDEN.into_response();
+                }
+                _ => {}
+            }
+        }
+        let data: Value = match serde_json::from_slice(&body) {
+            Ok(data) => data,
+            Err(err) => {
+                self.sink.send("bot:error", json!({ "id": self.id, "error": err.to_string() }));
+                return StatusCode::BAD_REQUEST.into_response();
+            }
+        };
+        debug!("适配器 {} 收到上报: {}", self.id, data);
+        dispatch_event(self.sink.as_ref(), &self.id, data);
+        // 不使用快速操作，直接返回 204
+        StatusCode::NO_CONTENT.into_response()
+    }
+}
+
+impl OneBotHttpConnector {
+    pub fn new(config: &BotConfig, sink: Arc<dyn EventSink>) -> Result<Self, String> {
+        let receiver = match config.listen.as_deref().filter(|l| !l.is_empty()) {
+            Some(listen) => {
+                let (addr, path) = parse_listen_address(listen, "http", 5701)?;
+                Some((
+                    addr,
+                    Arc::new(Receiver {
+                        id: config.id.clone(),
+                        path,
+                        secret: config.secret.clone().filter(|s| !s.is_empty()),
+                        sink: sink.clone(),
+                    }),
+                ))
+            }
+            None => None,
+        };
+        Ok(Self {
+            id: config.id.clone(),
+            address: config.address.trim_end_matches('/').to_string(),
+            token: config.token.clone().filter(|t| !t.is_empty()),
+            sync_timeout: Duration::from_millis(config.sync_timeout.unwrap_or(5000)),
+            client: crate::utils::http_client::client(),
+            sink,
+            receiver,
+            connected: AtomicBool::new(false),
+            local_addr: Mutex::new(None),
+            shutdown: Mutex::new(None),
+        })
+    }
+
+    /// 上报接收服务实际监听的地址
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        *self.local_addr.lock().unwrap()
+    }
+
+    fn emit_state(&self, event: &str) {
+        self.sink.send(event, json!({ "id": self.id, "timestamp": chrono::Utc::now().timestamp_millis() }));
+    }
+}
+
+#[async_trait]
+impl BotConnector for OneBotHttpConnector {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn connector_type(&self) -> &'static str {
+        TYPE
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    fn keeps_retrying(&self) -> bool {
+        false
+    }
+
+    async fn connect(&self) -> Result<(), String> {
+        if self.is_connected() {
+            return Ok(());
+        }
+        if let Some((addr, receiver)) = &self.receiver {
+            let receiver = receiver.clone();
+            let route = warp::post()
+                .and(warp::path::full())
+                .and(warp::header::headers_cloned())
+                .and(warp::body::bytes())
+                .map(move |path: FullPath, headers: HeaderMap, body: Bytes| {
+                    receiver.receive(path, headers, body)
+                });
+            let (addr, tx) = bind_server(route, *addr).map_err(|e| format!("HTTP 上报服务无法绑定 {}: {}", addr, e))?;
+            *self.local_addr.lock().unwrap() = Some(addr);
+            *self.shutdown.lock().unwrap() = Some(tx);
+            info!("适配器 {} 的 HTTP 上报服务已启动，监听：{}", self.id, addr);
+        }
+        self.connected.store(true, Ordering::SeqCst);
+        self.emit_state("bot:connected");
+        Ok(())
+    }
+
+    async fn disconnect(&self) {
+        if let Some(tx) = self.shutdown.lock().unwrap().take() {
+            let _ = tx.send(());
+        }
+        if self.connected.swap(false, Ordering::SeqCst) {
+            self.emit_state("bot:disconnected");
+        }
+    }
+
+    async fn call_api(&self, action: &str, params: Value, timeout: Option<Duration>) -> Result<Value, String> {
+        let url = format!("{}/{}", self.address, action.trim_start_matches('/'));
+        let mut request = self
+            .client
+            .post(&url)
+            .timeout(timeout.unwrap_or(self.sync_timeout))
+            .json(&params);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request.send().await.map_err(|e| format!("调用 {} 失败: {}", action, e))?;
+        match response.status() {
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                return Err(format!("调用 {} 失败: 鉴权失败 ({})", action, response.status()))
+            }
+            reqwest::StatusCode::NOT_FOUND => return Err(format!("调用 {} 失败: 不存在的 API", action)),
+            status if !status.is_success() => return Err(format!("调用 {} 失败: {}", action, status)),
+            _ => {}
+        }
+        response.json::<Value>().await.map_err(|e| format!("解析 {} 的响应失败: {}", action, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::test_support::RecordSink;
+
+    fn sign(secret: &str, body: &str) -> String {
+        let mut mac = Hmac::<Sha1>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(body.as_bytes());
+        format!("sha1={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    #[tokio::test]
+    async fn receives_signed_events() {
+        let sink = Arc::new(RecordSink::default());
+        let config: BotConfig = serde_json::from_value(json!({
+            "id": "http", "type": TYPE, "address": "http://127.0.0.1:1",
+            "listen": "127.0.0.1:0/onebot", "secret": "secret"
+        }))
+        .unwrap();
+        let connector = OneBotHttpConnector::new(&config, sink.clone()).unwrap();
+        connector.connect().await.unwrap();
+        let url = format!("http://{}/onebot", connector.local_addr().unwrap());
+
+        let body = json!({ "post_type": "message", "message_type": "group", "self_id": 1, "group_id": 2, "user_id": 3, "time": 0 })
+            .to_string();
+        let client = Client::new();
+        let post = |signature: Option<String>| {
+            let mut request = client.post(&url).body(body.clone());
+            if let Some(signature) = signature {
+                request = request.header("X-Signature", signature);
+            }
+            request.send()
+        };
+
+        assert_eq!(post(None).await.unwrap().status(), reqwest::StatusCode::UNAUTHORIZED);
+        assert_eq!(post(Some(sign("wrong", &body))).await.unwrap().status(), reqwest::StatusCode::FORBIDDEN);
+        assert_eq!(post(Some(sign("secret", &body))).await.unwrap().status(), reqwest::StatusCode::NO_CONTENT);
+
+        let events = sink.0.lock().unwrap().clone();
+        let messages: Vec<_> = events.iter().filter(|(event, _)| event == "bot:message").collect();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].1["data"]["groupId"], 2);
+
+        connector.disconnect().await;
+    }
+}
@@ -0,0 +1,212 @@
+//! OneBot v11 协议相关的公共处理
+
+pub mod http;
+pub mod reverse;
+pub mod ws;
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat};
+use serde_json::{json, Map, Value};
+use tokio::sync::oneshot;
+use warp::http::HeaderMap;
+use warp::Filter;
+
+use super::EventSink;
+
+/// 等待 echo 响应的 API 调用
+#[derive(Default)]
+pub struct PendingCalls {
+    calls: Mutex<HashMap<String, oneshot::Sender<Result<Value, String>>>>,
+}
+
+impl PendingCalls {
+    /// 登记一个 echo，返回等待响应的接收端
+    pub fn register(&self, echo: &str) -> oneshot::Receiver<Result<Value, String>> {
+        let (tx, rx) = oneshot::channel();
+        self.calls.lock().unwrap().insert(echo.to_string(), tx);
+        rx
+    }
+
+    pub fn remove(&self, echo: &str) {
+        self.calls.lock().unwrap().remove(echo);
+    }
+
+    /// 如果数据是某个等待中调用的响应则完成它并返回 true
+    pub fn resolve(&self, data: &Value) -> bool {
+        let Some(echo) = data.get("echo").and_then(Value::as_str) else {
+            return false;
+        };
+        match self.calls.lock().unwrap().remove(echo) {
+            Some(tx) => {
+                let _ = tx.send(Ok(data.clone()));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 以指定原因拒绝所有等待中的调用
+    pub fn reject_all(&self, reason: &str) {
+        for (_, tx) in self.calls.lock().unwrap().drain() {
+            let _ = tx.send(Err(reason.to_string()));
+        }
+    }
+}
+
+/// 生成 API 调用的 echo
+pub fn new_echo() -> String {
+    format!(
+        "{:x}-{:06x}",
+        chrono::Utc::now().timestamp_millis(),
+        rand::random::<u32>() & 0xffffff
+    )
+}
+
+/// 发送 API 调用并等待 echo 匹配的响应
+///
+/// send 负责把序列化后的调用数据交给连接，连接不可用时返回 false
+pub async fn call_with_echo(
+    pending: &PendingCalls,
+    action: &str,
+    params: Value,
+    timeout: Duration,
+    send: impl FnOnce(String) -> bool,
+) -> Result<Value, String> {
+    let echo = new_echo();
+    let rx = pending.register(&echo);
+    let frame = json!({ "action": action, "params": params, "echo": echo });
+    if !send(frame.to_string()) {
+        pending.remove(&echo);
+        return Err("WebSocket not open".to_string());
+    }
+
+    match tokio::time::timeout(timeout, rx).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(_)) => Err("WebSocket closed".to_string()),
+        Err(_) => {
+            pending.remove(&echo);
+            Err(format!("等待 echo={} 的响应超时", echo))
+        }
+    }
+}
+
+/// 解析本地监听地址（如 `ws://0.0.0.0:6700/onebot/v11/ws`），返回地址和路径
+///
+/// 未写协议时按 scheme 处理，未写端口时使用 default_port
+pub fn parse_listen_address(address: &str, scheme: &str, default_port: u16) -> Result<(SocketAddr, String), String> {
+    let address = if address.contains("://") { address.to_string() } else { format!("{}://{}", scheme, address) };
+    let url = url::Url::parse(&address).map_err(|e| format!("无效的监听地址 {}: {}", address, e))?;
+    let host = match url.host_str() {
+        Some("localhost") | None => "127.0.0.1",
+        Some(host) => host.trim_start_matches('[').trim_end_matches(']'),
+    };
+    let ip = host.parse().map_err(|_| format!("监听地址必须是 IP: {}", host))?;
+    let port = url.port().unwrap_or(default_port);
+    Ok((SocketAddr::new(ip, port), url.path().trim_end_matches('/').to_string()))
+}
+
+/// 从 Authorization 头（Bearer / Token）或 access_token 参数中取出令牌
+pub fn request_token(headers: &HeaderMap, query: &str) -> Option<String> {
+    if let Some(value) = headers.get("authorization").and_then(|v| v.to_str().ok()) {
+        let value = value.trim();
+        let token = value
+            .strip_prefix("Bearer ")
+            .or_else(|| value.strip_prefix("Token "))
+            .unwrap_or(value);
+        return Some(token.trim().to_string());
+    }
+    url::form_urlencoded::parse(query.as_bytes())
+        .find(|(key, _)| key == "access_token")
+        .map(|(_, value)| value.into_owned())
+}
+
+/// 在后台启动 warp 服务，返回实际监听的地址和用于关闭服务的发送端
+pub fn bind_server<F>(route: F, addr: SocketAddr) -> Result<(SocketAddr, oneshot::Sender<()>), String>
+where
+    F: Filter + Clone + Send + Sync + 'static,
+    F::Extract: warp::Reply,
+{
+    let (tx, rx) = oneshot::channel::<()>();
+    let (addr, server) = warp::serve(route)
+        .try_bind_with_graceful_shutdown(addr, async {
+            rx.await.ok();
+        })
+        .map_err(|e| e.to_string())?;
+    tokio::spawn(server);
+    Ok((addr, tx))
+}
+
+fn number(value: Option<&Value>) -> Value {
+    match value {
+        Some(Value::Number(n)) => Value::Number(n.clone()),
+        Some(Value::String(s)) => s.trim().parse::<i64>().map(Value::from).unwrap_or(Value::Null),
+        _ => Value::Null,
+    }
+}
+
+/// 将 OneBot 消息事件转换为 RenMessage 结构（与 NapcatRenMessage 字段一致）
+pub fn format_message(data: &Value, is_mine: bool) -> Value {
+    let mut message = Map::new();
+    let mut put = |key: &str, value: Value| {
+        if !value.is_null() {
+            message.insert(key.to_string(), value);
+        }
+    };
+    put("messageId", data.get("message_id").cloned().unwrap_or(Value::Null));
+    put("messageSeqId", number(data.get("real_seq")));
+    put("messageType", data.get("message_type").cloned().unwrap_or(Value::Null));
+    put("selfId", number(data.get("self_id")));
+    put("targetId", number(data.get("target_id")));
+    put("groupId", number(data.get("group_id")));
+    put("groupName", data.get("group_name").cloned().unwrap_or(Value::Null));
+    put("userId", number(data.get("user_id")));
+    if let Some(sender) = data.get("sender") {
+        let mut dto = Map::new();
+        dto.insert("userId".to_string(), number(sender.get("user_id")));
+        for (from, to) in [("nickname", "nickName"), ("card", "cardName"), ("role", "role")] {
+            if let Some(value) = sender.get(from) {
+                dto.insert(to.to_string(), value.clone());
+            }
+        }
+        put("sender", Value::Object(dto));
+    }
+    put("rawMessage", data.get("raw_message").cloned().unwrap_or(Value::Null));
+    put("message", data.get("message").cloned().unwrap_or(Value::Null));
+    let time = data
+        .get("time")
+        .and_then(Value::as_i64)
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .map(|time| Value::String(time.to_rfc3339_opts(SecondsFormat::Millis, true)))
+        .unwrap_or(Value::Null);
+    put("time", time);
+    message.insert("isMine".to_string(), json!(is_mine));
+    Value::Object(message)
+}
+
+/// 分发 OneBot 上报事件
+///
+/// 原始数据通过 bot:event 转发，消息事件额外转换后通过 bot:message / bot:messageMine 转发
+pub fn dispatch_event(sink: &dyn EventSink, id: &str, data: Value) {
+    let post_type = data.get("post_type").and_then(Value::as_str).unwrap_or_default();
+    let msg_type = if post_type == "notice" {
+        data.get("sub_type")
+            .or_else(|| data.get("notice_type"))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+    } else {
+        post_type
+    };
+    let message = match msg_type {
+        "message" => Some(("bot:message", format_message(&data, false))),
+        "message_sent" => Some(("bot:messageMine", format_message(&data, true))),
+        _ => None,
+    };
+    sink.send("bot:event", json!({ "id": id, "data": data }));
+    if let Some((event, message)) = message {
+        sink.send(event, json!({ "id": id, "data": message }));
+    }
+}
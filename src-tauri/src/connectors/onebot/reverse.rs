@@ -0,0 +1,375 @@
+//! OneBot v11 反向 WebSocket 服务
+//!
+//! 由后端监听配置的地址（例如 `ws://0.0.0.0:6700/onebot/v11/ws`），OneBot 实现主动连入。
+//! 每个携带 X-Self-ID 的连接都会以 `<服务 id>:<self_id>` 注册为独立的连接器。
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use log::{debug, info, warn};
+use serde_json::{json, Value};
+use subtle::ConstantTimeEq;
+use tokio::sync::{mpsc, oneshot};
+use warp::http::{HeaderMap, StatusCode};
+use warp::path::FullPath;
+use warp::reply::Reply;
+use warp::ws::{Message, WebSocket, Ws};
+use warp::Filter;
+
+use super::{bind_server, call_with_echo, dispatch_event, parse_listen_address, request_token, PendingCalls};
+use crate::connectors::{BotConfig, BotConnector, ConnectorRegistry, EventSink};
+
+pub const TYPE: &str = "onebot-reverse";
+
+struct ServerInner {
+    id: String,
+    addr: SocketAddr,
+    path: String,
+    token: Option<String>,
+    sync_timeout: Duration,
+    sink: Arc<dyn EventSink>,
+    registry: ConnectorRegistry,
+    connections: Mutex<HashMap<String, Arc<ReverseWsConnection>>>,
+}
+
+/// 反向 WebSocket 服务，本身也作为连接器注册，便于统一启动和关闭
+pub struct ReverseWsServer {
+    inner: Arc<ServerInner>,
+    local_addr: Mutex<Option<SocketAddr>>,
+    shutdown: Mutex<Option<oneshot::Sender<()>>>,
+}
+
+/// 反向 WebSocket 服务中的一个 Bot 连接
+pub struct ReverseWsConnection {
+    id: String,
+    sink: Arc<dyn EventSink>,
+    sync_timeout: Duration,
+    connected: AtomicBool,
+    pending: PendingCalls,
+    sender: mpsc::UnboundedSender<Message>,
+}
+
+impl ReverseWsServer {
+    pub fn new(config: &BotConfig, sink: Arc<dyn EventSink>, registry: ConnectorRegistry) -> Result<Self, String> {
+        let (addr, path) = parse_listen_address(&config.address, "ws", 6700)?;
+        Ok(Self {
+            inner: Arc::new(ServerInner {
+                id: config.id.clone(),
+                addr,
+                path,
+                token: config.token.clone().filter(|t| !t.is_empty()),
+                sync_timeout: Duration::from_millis(config.sync_timeout.unwrap_or(5000)),
+                sink,
+                registry,
+                connections: Mutex::new(HashMap::new()),
+            }),
+            local_addr: Mutex::new(None),
+            shutdown: Mutex::new(None),
+        })
+    }
+
+    /// 实际监听的地址（端口为 0 时由系统分配）
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        *self.local_addr.lock().unwrap()
+    }
+
+    /// 当前已接入的 Bot（self_id）
+    pub fn self_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.inner.connections.lock().unwrap().keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+}
+
+impl ServerInner {
+    /// 检查连接请求，通过后升级为 WebSocket
+    fn accept(self: &Arc<Self>, path: FullPath, headers: HeaderMap, query: String, ws: Ws) -> warp::reply::Response {
+        if !self.path.is_empty() && path.as_str().trim_end_matches('/') != self.path {
+            return StatusCode::NOT_FOUND.into_response();
+        }
+        if let Some(token) = &self.token {
+            match request_token(&headers, &query) {
+                None => return StatusCode::UNAUTHORIZED.into_response(),
+                Some(given) if !bool::from(given.as_bytes().ct_eq(token.as_bytes())) => {
+                    warn!("反向 WebSocket {} 拒绝了令牌错误的连接", self.id);
+                    return StatusCode::FORBIDDEN.into_response();
+                }
+                _ => {}
+            }
+        }
+        let Some(self_id) = headers
+            .get("x-self-id")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+        else {
+            return warp::reply::with_status("缺少 X-Self-ID", StatusCode::BAD_REQUEST).into_response();
+        };
+
+        let server = self.clone();
+        ws.on_upgrade(move |socket| server.serve(self_id, socket)).into_response()
+    }
+
+    /// 处理一个 Bot 连接，连接关闭后注销
+    async fn serve(self: Arc<Self>, self_id: String, socket: WebSocket) {
+        let (mut write, mut read) = socket.split();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let connection = Arc::new(ReverseWsConnection {
+            id: format!("{}:{}", self.id, self_id),
+            sink: self.sink.clone(),
+            sync_timeout: self.sync_timeout,
+            connected: AtomicBool::new(true),
+            pending: PendingCalls::default(),
+            sender: tx,
+        });
+
+        // 同一个 self_id 重复连接时替换旧连接
+        let replaced = self.connections.lock().unwrap().insert(self_id.clone(), connection.clone());
+        if let Some(old) = replaced {
+            old.close();
+        }
+        self.registry
+            .lock()
+            .unwrap()
+            .insert(connection.id.clone(), connection.clone() as Arc<dyn BotConnector>);
+        info!("反向 WebSocket {} 已接入 Bot: {}", self.id, self_id);
+        connection.emit_state("bot:connected");
+
+        loop {
+            tokio::select! {
+                incoming = read.next() => match incoming {
+                    Some(Ok(message)) if message.is_text() || message.is_binary() => {
+                        connection.handle_text(&String::from_utf8_lossy(message.as_bytes()));
+                    }
+                    Some(Ok(message)) if message.is_close() => break,
+                    Some(Ok(_)) => {}
+                    Some(Err(err)) => {
+                        connection.sink.send("bot:error", json!({ "id": connection.id, "error": err.to_string() }));
+                        break;
+                    }
+                    None => break,
+                },
+                outgoing = rx.recv() => match outgoing {
+                    Some(message) => {
+                        let closing = message.is_close();
+                        if write.send(message).await.is_err() || closing {
+                            break;
+                        }
+                    }
+                    None => break,
+                },
+            }
+        }
+
+        {
+            let mut connections = self.connections.lock().unwrap();
+            if connections.get(&self_id).is_some_and(|c| Arc::ptr_eq(c, &connection)) {
+                connections.remove(&self_id);
+            }
+        }
+        {
+            let mut registry = self.registry.lock().unwrap();
+            let current = registry.get(&connection.id).map(|c| Arc::as_ptr(c) as *const ());
+            if current == Some(Arc::as_ptr(&connection) as *const ()) {
+                registry.remove(&connection.id);
+            }
+        }
+        if connection.connected.swap(false, Ordering::SeqCst) {
+            connection.pending.reject_all("WebSocket closed");
+            warn!("反向 WebSocket {} 的 Bot 已断开: {}", self.id, self_id);
+            connection.emit_state("bot:disconnected");
+        }
+    }
+}
+
+impl ReverseWsConnection {
+    fn emit_state(&self, event: &str) {
+        self.sink.send(event, json!({ "id": self.id, "timestamp": chrono::Utc::now().timestamp_millis() }));
+    }
+
+    fn handle_text(&self, text: &str) {
+        let data: Value = match serde_json::from_str(text) {
+            Ok(data) => data,
+            Err(err) => {
+                self.sink.send("bot:error", json!({ "id": self.id, "error": err.to_string() }));
+                return;
+            }
+        };
+        debug!("适配器 {} 收到数据: {}", self.id, text);
+        if self.pending.resolve(&data) {
+            return;
+        }
+        dispatch_event(self.sink.as_ref(), &self.id, data);
+    }
+
+    fn close(&self) {
+        let _ = self.sender.send(Message::close());
+    }
+}
+
+#[async_trait]
+impl BotConnector for ReverseWsConnection {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn connector_type(&self) -> &'static str {
+        TYPE
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    async fn connect(&self) -> Result<(), String> {
+        // 连接由 OneBot 实现发起
+        Ok(())
+    }
+
+    async fn disconnect(&self) {
+        self.close();
+    }
+
+    async fn call_api(&self, action: &str, params: Value, timeout: Option<Duration>) -> Result<Value, String> {
+        if !self.is_connected() {
+            return Err(format!("适配器未连接: {}", self.id));
+        }
+        let timeout = timeout.unwrap_or(self.sync_timeout);
+        call_with_echo(&self.pending, action, params, timeout, |frame| {
+            self.sender.send(Message::text(frame)).is_ok()
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl BotConnector for ReverseWsServer {
+    fn id(&self) -> &str {
+        &self.inner.id
+    }
+
+    fn connector_type(&self) -> &'static str {
+        TYPE
+    }
+
+    /// 服务正在监听即视为已连接
+    fn is_connected(&self) -> bool {
+        self.shutdown.lock().unwrap().is_some()
+    }
+
+    fn keeps_retrying(&self) -> bool {
+        false
+    }
+
+    async fn connect(&self) -> Result<(), String> {
+        if self.is_connected() {
+            return Ok(());
+        }
+        let inner = self.inner.clone();
+        let route = warp::path::full()
+            .and(warp::header::headers_cloned())
+            .and(warp::query::raw().or(warp::any().map(String::new)).unify())
+            .and(warp::ws())
+            .map(move |path: FullPath, headers: HeaderMap, query: String, ws: Ws| {
+                inner.accept(path, headers, query, ws)
+            });
+
+        let (addr, tx) = bind_server(route, self.inner.addr)
+            .map_err(|e| format!("反向 WebSocket 服务无法绑定 {}: {}", self.inner.addr, e))?;
+
+        *self.local_addr.lock().unwrap() = Some(addr);
+        *self.shutdown.lock().unwrap() = Some(tx);
+        info!("反向 WebSocket {} 已启动，监听：{}{}", self.inner.id, addr, self.inner.path);
+        Ok(())
+    }
+
+    async fn disconnect(&self) {
+        if let Some(tx) = self.shutdown.lock().unwrap().take() {
+            let _ = tx.send(());
+        }
+        let connections: Vec<_> = self.inner.connections.lock().unwrap().drain().map(|(_, c)| c).collect();
+        for connection in connections {
+            self.inner.registry.lock().unwrap().remove(&connection.id);
+            connection.close();
+        }
+        info!("反向 WebSocket {} 已关闭", self.inner.id);
+    }
+
+    /// 只有一个 Bot 接入时直接转交给它，否则需要使用具体连接的 id 调用
+    async fn call_api(&self, action: &str, params: Value, timeout: Option<Duration>) -> Result<Value, String> {
+        let connections: Vec<_> = self.inner.connections.lock().unwrap().values().cloned().collect();
+        match connections.as_slice() {
+            [] => Err(format!("反向 WebSocket {} 没有已接入的 Bot", self.inner.id)),
+            [connection] => connection.call_api(action, params, timeout).await,
+            _ => Err(format!(
+                "反向 WebSocket {} 接入了多个 Bot，请使用连接 id 调用: {}",
+                self.inner.id,
+                connections.iter().map(|c| c.id.as_str()).collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connectors::test_support::RecordSink;
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+    use tokio_tungstenite::tungstenite::Message as ClientMessage;
+
+    /// 模拟 OneBot 实现连入反向 WebSocket，并对 API 调用原样回复
+    async fn fake_onebot(addr: SocketAddr, self_id: &str, token: &str) -> Result<(), String> {
+        let mut request = format!("ws://{}/onebot/v11/ws", addr).into_client_request().unwrap();
+        request.headers_mut().insert("X-Self-ID", self_id.parse().unwrap());
+        request.headers_mut().insert("Authorization", format!("Bearer {}", token).parse().unwrap());
+        let (mut ws, _) = tokio_tungstenite::connect_async(request).await.map_err(|e| e.to_string())?;
+        let event = json!({ "post_type": "message", "message_type": "private", "self_id": self_id, "user_id": 10001, "time": 0, "raw_message": "hi" });
+        ws.send(ClientMessage::Text(event.to_string())).await.unwrap();
+        tokio::spawn(async move {
+            while let Some(Ok(ClientMessage::Text(text))) = ws.next().await {
+                let call: Value = serde_json::from_str(&text).unwrap();
+                let reply = json!({ "status": "ok", "retcode": 0, "data": call["params"], "echo": call["echo"] });
+                ws.send(ClientMessage::Text(reply.to_string())).await.unwrap();
+            }
+        });
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn accepts_multiple_bots_and_checks_token() {
+        let sink = Arc::new(RecordSink::default());
+        let registry = ConnectorRegistry::default();
+        let config: BotConfig = serde_json::from_value(json!({
+            "id": "rws", "type": TYPE, "address": "ws://127.0.0.1:0/onebot/v11/ws", "token": "secret"
+        }))
+        .unwrap();
+        let server = ReverseWsServer::new(&config, sink.clone(), registry.clone()).unwrap();
+        server.connect().await.unwrap();
+        let addr = server.local_addr().unwrap();
+
+        assert!(fake_onebot(addr, "1001", "wrong").await.is_err());
+        assert!(fake_onebot(addr, "1001", "secret2").await.is_err());
+        fake_onebot(addr, "1001", "secret").await.unwrap();
+        fake_onebot(addr, "1002", "secret").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(server.self_ids(), vec!["1001", "1002"]);
+        let connection = registry.lock().unwrap().get("rws:1002").cloned().unwrap();
+        let response = connection.call_api("echo", json!({ "a": 1 }), None).await.unwrap();
+        assert_eq!(response["data"]["a"], 1);
+        assert!(server.call_api("echo", json!({}), None).await.is_err());
+
+        let events = sink.0.lock().unwrap().clone();
+        assert!(events.iter().any(|(event, payload)| {
+            event == "bot:message" && payload["id"] == "rws:1001" && payload["data"]["selfId"] == 1001
+        }));
+
+        server.disconnect().await;
+        assert!(registry.lock().unwrap().is_empty());
+    }
+}
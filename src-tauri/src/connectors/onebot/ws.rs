@@ -0,0 +1,259 @@
+//! OneBot v11 正向 WebSocket 连接器
+//!
+//! 连接将使用 `${address}?access_token=${token}`，断开后按指数退避重连
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use log::{debug, info, warn};
+use serde_json::{json, Value};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+
+use super::{call_with_echo, dispatch_event, PendingCalls};
+use crate::connectors::{BotConfig, BotConnector, EventSink};
+
+pub const TYPE: &str = "napcat";
+
+/// 重连间隔上限
+const MAX_RETRY_DELAY: u64 = 30000;
+
+struct Inner {
+    id: String,
+    url: String,
+    reconnect: bool,
+    max_retries: u32,
+    retry_interval: u64,
+    sync_timeout: Duration,
+    sink: Arc<dyn EventSink>,
+    connected: AtomicBool,
+    stopped: AtomicBool,
+    pending: PendingCalls,
+    sender: Mutex<Option<mpsc::UnboundedSender<Message>>>,
+}
+
+pub struct OneBotWsConnector {
+    inner: Arc<Inner>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl OneBotWsConnector {
+    pub fn new(config: &BotConfig, sink: Arc<dyn EventSink>) -> Self {
+        let url = match url::Url::parse(&config.address) {
+            Ok(mut url) => {
+                if let Some(token) = config.token.as_deref().filter(|t| !t.is_empty()) {
+                    url.query_pairs_mut().append_pair("access_token", token);
+                }
+                url.to_string()
+            }
+            Err(_) => config.address.clone(),
+        };
+        Self {
+            inner: Arc::new(Inner {
+                id: config.id.clone(),
+                url,
+                reconnect: config.reconnect.unwrap_or(true),
+                max_retries: config.max_retries.unwrap_or(5),
+                retry_interval: config.retry_interval.unwrap_or(2000),
+                sync_timeout: Duration::from_millis(config.sync_timeout.unwrap_or(5000)),
+                sink,
+                connected: AtomicBool::new(false),
+                stopped: AtomicBool::new(false),
+                pending: PendingCalls::default(),
+                sender: Mutex::new(None),
+            }),
+            task: Mutex::new(None),
+        }
+    }
+}
+
+/// 计算第 attempt 次重连的等待时间（指数退避，附加最多 20% 的随机抖动）
+fn retry_delay(retry_interval: u64, attempt: u32) -> Duration {
+    let base = retry_interval.saturating_mul(1 << attempt.saturating_sub(1).min(16));
+    let capped = base.min(MAX_RETRY_DELAY);
+    let jitter = (capped as f64 * 0.2 * rand::random::<f64>()) as u64;
+    Duration::from_millis(capped + jitter)
+}
+
+impl Inner {
+    fn emit(&self, event: &str, payload: Value) {
+        self.sink.send(event, payload);
+    }
+
+    fn emit_state(&self, event: &str) {
+        self.emit(event, json!({ "id": self.id, "timestamp": chrono::Utc::now().timestamp_millis() }));
+    }
+
+    /// 连接循环，直到不再需要重连
+    async fn run(self: Arc<Self>, first: oneshot::Sender<Result<(), String>>) {
+        let mut first = Some(first);
+        let mut attempts = 0;
+        loop {
+            match tokio_tungstenite::connect_async(self.url.as_str()).await {
+                Ok((stream, _)) => {
+                    attempts = 0;
+                    self.connected.store(true, Ordering::SeqCst);
+                    info!("适配器已连接: {}", self.id);
+                    self.emit_state("bot:connected");
+                    if let Some(first) = first.take() {
+                        let _ = first.send(Ok(()));
+                    }
+
+                    self.serve(stream).await;
+
+                    if self.connected.swap(false, Ordering::SeqCst) {
+                        self.pending.reject_all("WebSocket closed");
+                        warn!("适配器已断开: {}", self.id);
+                        self.emit_state("bot:disconnected");
+                    }
+                }
+                Err(err) => {
+                    warn!("适配器连接失败: {} - {}", self.id, err);
+                    self.emit("bot:error", json!({ "id": self.id, "error": err.to_string() }));
+                    if let Some(first) = first.take() {
+                        let _ = first.send(Err(format!("适配器连接失败: {}", err)));
+                    }
+                }
+            }
+
+            if !self.reconnect || self.stopped.load(Ordering::SeqCst) {
+                break;
+            }
+            attempts += 1;
+            if attempts > self.max_retries {
+                warn!("适配器 {} 重连次数已达上限 ({})", self.id, self.max_retries);
+                break;
+            }
+            let delay = retry_delay(self.retry_interval, attempts);
+            debug!("适配器 {} 将在 {}ms 后第 {} 次重连", self.id, delay.as_millis(), attempts);
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// 处理一次连接的收发，连接关闭后返回
+    async fn serve<S>(&self, stream: tokio_tungstenite::WebSocketStream<S>)
+    where
+        S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+    {
+        let (mut write, mut read) = stream.split();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        *self.sender.lock().unwrap() = Some(tx);
+
+        loop {
+            tokio::select! {
+                incoming = read.next() => match incoming {
+                    Some(Ok(Message::Text(text))) => self.handle_text(&text),
+                    Some(Ok(Message::Binary(bytes))) => self.handle_text(&String::from_utf8_lossy(&bytes)),
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Ok(_)) => {}
+                    Some(Err(err)) => {
+                        self.emit("bot:error", json!({ "id": self.id, "error": err.to_string() }));
+                        break;
+                    }
+                },
+                outgoing = rx.recv() => match outgoing {
+                    Some(message) => {
+                        let closing = matches!(message, Message::Close(_));
+                        if let Err(err) = write.send(message).await {
+                            self.emit("bot:error", json!({ "id": self.id, "error": err.to_string() }));
+                            break;
+                        }
+                        if closing {
+                            break;
+                        }
+                    }
+                    None => break,
+                },
+            }
+        }
+
+        *self.sender.lock().unwrap() = None;
+    }
+
+    fn handle_text(&self, text: &str) {
+        let data: Value = match serde_json::from_str(text) {
+            Ok(data) => data,
+            Err(err) => {
+                self.emit("bot:error", json!({ "id": self.id, "error": err.to_string() }));
+                return;
+            }
+        };
+        debug!("适配器 {} 收到数据: {}", self.id, text);
+        // 优先处理带 echo 的 API 响应
+        if self.pending.resolve(&data) {
+            return;
+        }
+        dispatch_event(self.sink.as_ref(), &self.id, data);
+    }
+}
+
+#[async_trait]
+impl BotConnector for OneBotWsConnector {
+    fn id(&self) -> &str {
+        &self.inner.id
+    }
+
+    fn connector_type(&self) -> &'static str {
+        TYPE
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.connected.load(Ordering::SeqCst)
+    }
+
+    fn keeps_retrying(&self) -> bool {
+        self.inner.reconnect
+    }
+
+    async fn connect(&self) -> Result<(), String> {
+        {
+            let mut task = self.task.lock().unwrap();
+            // 重试用尽或不重连时连接任务会自行结束，此时需要重新启动
+            if task.as_ref().is_some_and(|handle| !handle.is_finished()) {
+                return Ok(());
+            }
+            task.take();
+        }
+        self.inner.stopped.store(false, Ordering::SeqCst);
+        let (tx, rx) = oneshot::channel();
+        let handle = tokio::spawn(self.inner.clone().run(tx));
+        *self.task.lock().unwrap() = Some(handle);
+        rx.await.unwrap_or_else(|_| Err("适配器连接任务已退出".to_string()))
+    }
+
+    async fn disconnect(&self) {
+        self.inner.stopped.store(true, Ordering::SeqCst);
+        let sender = self.inner.sender.lock().unwrap().take();
+        if let Some(sender) = sender {
+            let _ = sender.send(Message::Close(None));
+        }
+        let handle = self.task.lock().unwrap().take();
+        if let Some(mut handle) = handle {
+            // 等待关闭帧发出，超时则直接结束连接任务
+            if tokio::time::timeout(Duration::from_secs(1), &mut handle).await.is_err() {
+                handle.abort();
+            }
+        }
+        self.inner.pending.reject_all("Disconnected");
+        if self.inner.connected.swap(false, Ordering::SeqCst) {
+            self.inner.emit_state("bot:disconnected");
+        }
+    }
+
+    async fn call_api(&self, action: &str, params: Value, timeout: Option<Duration>) -> Result<Value, String> {
+        let sender = match self.inner.sender.lock().unwrap().clone() {
+            Some(sender) if self.is_connected() => sender,
+            _ => return Err(format!("适配器未连接: {}", self.inner.id)),
+        };
+
+        let timeout = timeout.unwrap_or(self.inner.sync_timeout);
+        call_with_echo(&self.inner.pending, action, params, timeout, |frame| {
+            sender.send(Message::Text(frame)).is_ok()
+        })
+        .await
+    }
+}
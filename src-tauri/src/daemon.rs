@@ -0,0 +1,255 @@
+//! 无界面运行模式（renflow-daemon）
+//!
+//! 读取 `sys_export_workspace` 导出的工作区包，连接其中的 Bot 并执行已启用的工作流，
+//! 直到收到 SIGTERM / Ctrl+C。不会创建任何窗体。
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use log::{error, info, warn};
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use crate::connectors::{BotConfig, ConnectorManager, EventSink};
+use crate::workflow::{check_trigger_config, ExecutionOptions, WorkflowEngine, WorkflowExecution};
+use crate::workspace::{WorkflowFile, WorkspacePackage};
+
+const USAGE: &str = "用法: renflow-daemon [--log-level err|info|debug|all] [--passphrase <密码>] <workspace.rfw | 工作区目录>
+
+加密的工作区包也可以通过环境变量 RENFLOW_PASSPHRASE 提供密码。
+Bot 的 token 未包含在工作区包中时，从环境变量 RENFLOW_TOKEN_<BOT ID> 读取；
+其他导出时被移除的敏感字段从 RENFLOW_SECRET_<字段标识> 读取（转为大写，非字母数字替换为 _）";
+
+/// 工作流未设置超时时间时使用的默认值（毫秒），与桌面端一致
+const DEFAULT_TIMEOUT: u64 = 60000;
+
+struct DaemonArgs {
+    workspace: PathBuf,
+    log_level: Option<String>,
+    passphrase: Option<String>,
+}
+
+/// 转换为环境变量名
+fn env_key(prefix: &str, key: &str) -> String {
+    let key: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    format!("{}{}", prefix, key)
+}
+
+fn parse_args(args: Vec<String>) -> Result<DaemonArgs, String> {
+    let mut workspace = None;
+    let mut log_level = std::env::var("RENFLOW_LOG_LEVEL").ok();
+    let mut passphrase = std::env::var("RENFLOW_PASSPHRASE").ok();
+    let mut iter = args.into_iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            "--log-level" => log_level = Some(iter.next().ok_or("--log-level 缺少参数")?),
+            _ if arg.starts_with("--log-level=") => log_level = Some(arg["--log-level=".len()..].to_string()),
+            "--passphrase" => passphrase = Some(iter.next().ok_or("--passphrase 缺少参数")?),
+            _ if arg.starts_with('-') => return Err(format!("未知的参数: {}\n\n{}", arg, USAGE)),
+            _ => workspace = Some(PathBuf::from(arg)),
+        }
+    }
+    Ok(DaemonArgs {
+        workspace: workspace.ok_or(USAGE)?,
+        log_level,
+        passphrase,
+    })
+}
+
+/// 将连接器事件转入主循环
+struct ChannelSink(mpsc::UnboundedSender<(String, Value)>);
+
+impl EventSink for ChannelSink {
+    fn send(&self, event: &str, payload: Value) {
+        let _ = self.0.send((event.to_string(), payload));
+    }
+}
+
+/// 读取 Bot 配置，token 为空时尝试从环境变量中获取
+fn load_bot(value: Value) -> Result<BotConfig, String> {
+    let mut config: BotConfig = serde_json::from_value(value).map_err(|e| format!("Bot 配置格式错误: {}", e))?;
+    if config.token.as_deref().unwrap_or_default().is_empty() {
+        config.token = std::env::var(env_key("RENFLOW_TOKEN_", &config.id)).ok();
+    }
+    Ok(config)
+}
+
+/// 工作流在导出时是否处于启用状态，`sources` 为包内的可编辑工作流数据
+fn is_enabled(file: &WorkflowFile, sources: &[WorkflowFile]) -> bool {
+    let id = file.content.get("id").and_then(Value::as_str);
+    let source = sources.iter().find(|s| s.content.get("id").and_then(Value::as_str) == id);
+    [Some(file), source]
+        .into_iter()
+        .flatten()
+        .all(|f| f.content.get("enabled").and_then(Value::as_bool) != Some(false))
+}
+
+/// 读取可以在后端执行的工作流，跳过未启用的
+fn load_workflows(engine: &WorkflowEngine, files: Vec<WorkflowFile>, sources: &[WorkflowFile]) -> Vec<WorkflowExecution> {
+    let mut workflows = Vec::new();
+    for file in files {
+        if !is_enabled(&file, sources) {
+            info!("跳过未启用的工作流: {}", file.filename);
+            continue;
+        }
+        let workflow: WorkflowExecution = match serde_json::from_value(file.content) {
+            Ok(workflow) => workflow,
+            Err(err) => {
+                warn!("跳过工作流 {}: 格式错误: {}", file.filename, err);
+                continue;
+            }
+        };
+        let errors = workflow.validate();
+        if !errors.is_empty() {
+            warn!("跳过工作流 {}: {}", workflow.name, errors.join("; "));
+            continue;
+        }
+        let unsupported = engine.unsupported_node_types(&workflow);
+        if !unsupported.is_empty() {
+            warn!("跳过工作流 {}: 不支持的节点类型 {}", workflow.name, unsupported.join(", "));
+            continue;
+        }
+        info!("已加载工作流: {} ({})，触发器: {}", workflow.name, workflow.id, workflow.trigger.name);
+        workflows.push(workflow);
+    }
+    workflows
+}
+
+/// 等待 SIGTERM 或 Ctrl+C
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut term) => {
+                tokio::select! {
+                    _ = term.recv() => {}
+                    _ = tokio::signal::ctrl_c() => {}
+                }
+            }
+            Err(err) => {
+                error!("无法监听 SIGTERM: {}", err);
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+/// 处理一条 Bot 消息，执行所有满足触发条件的工作流
+fn handle_message(
+    engine: &Arc<WorkflowEngine>,
+    manager: &ConnectorManager,
+    workflows: &[WorkflowExecution],
+    trigger_name: &str,
+    payload: Value,
+) {
+    let bot_id = payload.get("id").and_then(Value::as_str).unwrap_or_default();
+    let data = payload.get("data").cloned().unwrap_or_default();
+    for workflow in workflows.iter().filter(|w| w.trigger.name == trigger_name) {
+        match check_trigger_config(workflow.trigger.params.as_ref(), &data) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(err) => {
+                warn!("工作流 {} 的触发器验证失败: {}", workflow.name, err);
+                continue;
+            }
+        }
+        let engine = engine.clone();
+        let workflow = workflow.clone();
+        let data = data.clone();
+        let options = ExecutionOptions {
+            min_delay: 0,
+            timeout: workflow.timeout.unwrap_or(DEFAULT_TIMEOUT),
+            bot: manager.get(bot_id),
+            initial_globals: HashMap::new(),
+            callback: None,
+        };
+        tokio::spawn(async move {
+            engine.execute(workflow, data, options).await;
+        });
+    }
+}
+
+async fn serve(package: WorkspacePackage) {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let manager = ConnectorManager::new(Arc::new(ChannelSink(tx)));
+    let engine = Arc::new(WorkflowEngine::new());
+
+    let workflows = load_workflows(&engine, package.workflows, &package.sources);
+    if workflows.is_empty() {
+        warn!("工作区中没有可执行的工作流");
+    }
+
+    for bot in package.bots {
+        let config = match load_bot(bot) {
+            Ok(config) => config,
+            Err(err) => {
+                error!("{}", err);
+                continue;
+            }
+        };
+        let id = config.id.clone();
+        info!("正在连接 Bot: {} ({})", config.name, id);
+        if let Err(err) = manager.connect(config).await {
+            error!("Bot {} 连接失败: {}", id, err);
+        }
+    }
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            event = rx.recv() => {
+                let Some((event, payload)) = event else { break };
+                match event.as_str() {
+                    "bot:message" => handle_message(&engine, &manager, &workflows, "message", payload),
+                    "bot:messageMine" => handle_message(&engine, &manager, &workflows, "message_mine", payload),
+                    "bot:connected" => info!("Bot {} 已连接", payload["id"]),
+                    "bot:disconnected" => warn!("Bot {} 已断开", payload["id"]),
+                    "bot:error" => error!("Bot {} 出错: {}", payload["id"], payload["error"]),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    info!("正在停止 ……");
+    manager.disconnect_all().await;
+}
+
+/// renflow-daemon 入口
+pub fn run(args: Vec<String>) -> Result<(), String> {
+    let args = parse_args(args)?;
+    crate::init_logger(crate::log_level_filter(args.log_level.as_deref()));
+
+    info!("欢迎使用 Ren Flow Daemon, 当前版本: {}", env!("CARGO_PKG_VERSION"));
+    info!("正在读取工作区: {}", args.workspace.display());
+    let mut package = WorkspacePackage::open(&args.workspace, args.passphrase.as_deref())?;
+    // 导出时被移除的敏感字段从环境变量中读取
+    let secrets: HashMap<String, String> = package
+        .secret_fields()
+        .into_iter()
+        .filter_map(|field| {
+            let value = std::env::var(env_key("RENFLOW_SECRET_", &field.key)).ok();
+            if value.is_none() && field.target != "bot" {
+                warn!("未设置 {} 的 {}（{}）", field.name, field.field, env_key("RENFLOW_SECRET_", &field.key));
+            }
+            value.map(|value| (field.key, value))
+        })
+        .collect();
+    package.fill_secrets(&secrets);
+
+    let rt = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    rt.block_on(serve(package));
+    Ok(())
+}
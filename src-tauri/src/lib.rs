@@ -1,5 +1,10 @@
 mod commands;
 mod utils;
+pub mod connectors;
+pub mod daemon;
+pub mod workflow;
+pub mod workflow_store;
+pub mod workspace;
 
 use log::info;
 use log4rs::{
@@ -10,22 +15,16 @@ use log4rs::{
 
 use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
 use tauri_plugin_store::StoreBuilder;
-use once_cell::sync::OnceCell;
+use std::sync::Arc;
+use connectors::ConnectorManager;
+use utils::asset_cache::{AssetCache, AssetCacheConfig};
+use utils::download::DownloadManager;
 use utils::http_proxy::ProxyServer;
-
-pub static PROXY_PORT: OnceCell<u16> = OnceCell::new();
+use utils::proxy_policy::ProxyPolicy;
+use workflow_store::WorkflowStore;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let rt = tokio::runtime::Runtime::new().unwrap();
-
-    // 初始化本地代理服务器 ============
-    let proxy = rt.block_on(async {
-        let proxy = ProxyServer::new().await;
-        proxy
-    });
-    PROXY_PORT.set(proxy.port).unwrap();
-
     tauri::Builder::default()
         .plugin(tauri_plugin_store::Builder::default().build())
         .setup(|app| {
@@ -33,30 +32,7 @@ pub fn run() {
                 .build()
                 .map_err(|e| e.to_string())?;
             let log_level = store.get("log_level").unwrap_or_default();
-            let final_log_level = match log_level.as_str() {
-                Some("err") => log::LevelFilter::Error,
-                Some("debug") => log::LevelFilter::Debug,
-                Some("info") => log::LevelFilter::Info,
-                Some("all") => log::LevelFilter::Debug,
-                _ => log::LevelFilter::Info,
-            };
-            // 初始化 log4rs
-            let stdout = ConsoleAppender::builder()
-                .encoder(Box::new(utils::colored_encoder::ColoredPrefixEncoder))
-                .build();
-            let config = Config::builder()
-                .appender(Appender::builder().build("stdout", Box::new(stdout)))
-                .logger(Logger::builder().build("tao", log::LevelFilter::Info))
-                .logger(Logger::builder().build("tungstenite", log::LevelFilter::Info))
-                .logger(Logger::builder().build("tokio_tungstenite", log::LevelFilter::Info))
-                .logger(Logger::builder().build("hyper", log::LevelFilter::Info))
-                .logger(Logger::builder().build("hyper_util", log::LevelFilter::Info))
-                .logger(Logger::builder().build("reqwest", log::LevelFilter::Info))
-                .logger(Logger::builder().build("warp", log::LevelFilter::Info))
-                .build(Root::builder().appender("stdout").build(final_log_level))
-                .unwrap();
-
-            log4rs::init_config(config).unwrap();
+            init_logger(log_level_filter(log_level.as_str()));
 
             println!("");
             println!(" _____ _____ _____ _____ __ __ ");
@@ -66,10 +42,92 @@ pub fn run() {
             println!("=======================================================");
             println!("日志等级:{}", log_level);
 
-            if PROXY_PORT.get().is_some() {
-                info!("代理服务器已启动，端口：{}", PROXY_PORT.get().unwrap());
+            // 出站 HTTP 客户端 ============
+            if let Err(err) = utils::http_client::configure(commands::sys::http_client_setting(&store)) {
+                log::error!("出站 HTTP 配置无效，已使用默认配置: {}", err);
+            }
+
+            app.manage(utils::http_request::HttpRequests::new());
+
+            // 下载管理 ============
+            let download_dir = store
+                .get("download_dir")
+                .and_then(|v| v.as_str().filter(|s| !s.is_empty()).map(std::path::PathBuf::from))
+                .map_or_else(|| app.path().download_dir(), Ok)?;
+            let download_concurrency = store
+                .get("download_concurrency")
+                .and_then(|v| v.as_u64().or_else(|| v.as_str()?.parse().ok()))
+                .unwrap_or(3);
+            app.manage(Arc::new(DownloadManager::new(
+                Arc::new(app.handle().clone()),
+                download_dir,
+                download_concurrency as usize,
+            )));
+
+            // 外部程序 ============
+            let process_allowlist = store
+                .get("process_allowlist")
+                .map(|v| utils::process::parse_allowlist(&v))
+                .unwrap_or_default();
+            app.manage(utils::process::ProcessRunner::new(Arc::new(app.handle().clone()), process_allowlist));
+
+            // 系统通知 ============
+            let notifications = user_notify::get_notification_manager(app.config().identifier.clone(), None);
+            if let Err(err) = utils::notification::register(&notifications, Arc::new(app.handle().clone())) {
+                log::error!("注册系统通知失败: {}", err);
+            }
+            app.manage(notifications);
+
+            // 本地代理服务器 ============
+            let proxy = ProxyServer::new(Arc::new(AssetCache::new(AssetCacheConfig::default())));
+            let list = |key: &str| store.get(key).and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default();
+            *proxy.policy.write().unwrap() = ProxyPolicy::from_lists(
+                &list("proxy_allow_hosts"),
+                &list("proxy_deny_hosts"),
+                &list("proxy_insecure_hosts"),
+            );
+
+            // 资源缓存
+            let max_body_size = store
+                .get("proxy_max_body_size")
+                .and_then(|v| v.as_u64().or_else(|| v.as_str()?.parse().ok()));
+            if let Some(mb) = max_body_size {
+                // 设置中以 MB 为单位
+                proxy.assets.set_config(AssetCacheConfig { max_body_size: mb * 1024 * 1024, ..Default::default() });
+            }
+            if let Err(err) = proxy.assets.set_dir(app.path().app_cache_dir()?.join("assets")) {
+                log::error!("初始化资源缓存失败: {}", err);
             }
 
+            match tauri::async_runtime::block_on(async { proxy.start(commands::sys::proxy_port_setting(&store)) }) {
+                Ok(port) => info!("代理服务器已启动，端口：{}", port),
+                Err(err) => log::error!("代理服务器启动失败: {}", err),
+            }
+            app.manage(proxy.assets.clone());
+            app.manage(proxy);
+
+            // 工作流存储 ============
+            let max_revisions = store
+                .get("workflow_max_revisions")
+                .and_then(|v| v.as_u64().or_else(|| v.as_str()?.parse().ok()))
+                .map_or(workflow_store::DEFAULT_MAX_REVISIONS, |n| n as usize);
+            let workflow_store = WorkflowStore::new(app.path().app_data_dir()?.join("workflows"), max_revisions)?;
+            if let Some(legacy) = store.get(workflow_store::LEGACY_STORE_KEY) {
+                // 迁移旧版本保存在 .settings.dat 中的工作流
+                let data = legacy.as_str().map(str::to_string).unwrap_or_else(|| legacy.to_string());
+                match workflow_store.migrate_legacy(&data) {
+                    Ok(_) => {
+                        store.delete(workflow_store::LEGACY_STORE_KEY);
+                        store.save()?;
+                    }
+                    Err(err) => log::error!("迁移工作流失败: {}", err),
+                }
+            }
+            app.manage(workflow_store);
+
+            // Bot 连接器管理器 ============
+            app.manage(ConnectorManager::new(Arc::new(app.handle().clone())));
+
             // 创建主窗体 ============
             info!(
                 "欢迎使用 Ren Flow, 当前版本: {}",
@@ -113,19 +171,36 @@ pub fn run() {
             commands::sys::sys_get_platform,
             commands::sys::sys_get_release,
             commands::sys::sys_run_proxy,
+            commands::sys::sys_start_proxy,
+            commands::sys::sys_stop_proxy,
+            commands::sys::sys_restart_proxy,
+            commands::sys::sys_reload_http_client,
+            commands::sys::sys_asset_cache_stats,
+            commands::sys::sys_clear_asset_cache,
             commands::sys::sys_send_notice,
             commands::sys::sys_close_notice,
             commands::sys::sys_close_all_notice,
             commands::sys::sys_clear_notice,
             commands::sys::sys_open_in_browser,
-            commands::sys::sys_run_command,
+            commands::sys::sys_run_process,
+            commands::sys::sys_get_process_allowlist,
+            commands::sys::sys_set_process_allowlist,
             commands::sys::sys_get_final_redirect_url,
+            commands::sys::sys_resolve_redirects,
             commands::sys::sys_get_html,
             commands::sys::sys_get_api,
+            commands::sys::sys_http_request,
+            commands::sys::sys_cancel_http_request,
             commands::sys::sys_download,
+            commands::sys::sys_pause_download,
+            commands::sys::sys_resume_download,
+            commands::sys::sys_cancel_download,
+            commands::sys::sys_list_downloads,
+            commands::sys::sys_set_download_dir,
             commands::sys::sys_set_store_value,
             commands::sys::sys_get_store_value,
             commands::sys::sys_export_workspace,
+            commands::sys::sys_import_workspace,
             commands::win::win_create_window,
             commands::win::win_close_window,
             commands::win::win_show_window,
@@ -141,12 +216,59 @@ pub fn run() {
             commands::opt::opt_save_all,
             commands::opt::opt_get_all,
             commands::opt::opt_get,
-            commands::opt::opt_clear_all
+            commands::opt::opt_clear_all,
+            commands::flow::flow_run,
+            commands::flow::flow_validate,
+            commands::flow::flow_get_supported_nodes,
+            commands::bot::bot_connect,
+            commands::bot::bot_disconnect,
+            commands::bot::bot_list,
+            commands::bot::bot_get_supported_types,
+            commands::bot::bot_call_api,
+            commands::wf::wf_list,
+            commands::wf::wf_get,
+            commands::wf::wf_save,
+            commands::wf::wf_delete,
+            commands::wf::wf_revisions,
+            commands::wf::wf_get_revision,
+            commands::wf::wf_diff,
+            commands::wf::wf_restore
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
 
+/// 将设置中的日志等级转换为 LevelFilter
+fn log_level_filter(level: Option<&str>) -> log::LevelFilter {
+    match level {
+        Some("err") => log::LevelFilter::Error,
+        Some("debug") => log::LevelFilter::Debug,
+        Some("info") => log::LevelFilter::Info,
+        Some("all") => log::LevelFilter::Debug,
+        _ => log::LevelFilter::Info,
+    }
+}
+
+/// 初始化 log4rs，主程序与 renflow-daemon 共用
+fn init_logger(level: log::LevelFilter) {
+    let stdout = ConsoleAppender::builder()
+        .encoder(Box::new(utils::colored_encoder::ColoredPrefixEncoder))
+        .build();
+    let config = Config::builder()
+        .appender(Appender::builder().build("stdout", Box::new(stdout)))
+        .logger(Logger::builder().build("tao", log::LevelFilter::Info))
+        .logger(Logger::builder().build("tungstenite", log::LevelFilter::Info))
+        .logger(Logger::builder().build("tokio_tungstenite", log::LevelFilter::Info))
+        .logger(Logger::builder().build("hyper", log::LevelFilter::Info))
+        .logger(Logger::builder().build("hyper_util", log::LevelFilter::Info))
+        .logger(Logger::builder().build("reqwest", log::LevelFilter::Info))
+        .logger(Logger::builder().build("warp", log::LevelFilter::Info))
+        .build(Root::builder().appender("stdout").build(level))
+        .unwrap();
+
+    log4rs::init_config(config).unwrap();
+}
+
 /// 创建主窗体配置
 fn create_window(app: &mut tauri::App) -> tauri::Result<tauri::WebviewWindow> {
     let win_builder = WebviewWindowBuilder::new(app, "main", WebviewUrl::App("/".into()))
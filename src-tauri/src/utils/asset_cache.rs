@@ -0,0 +1,600 @@
+//! `/assets` 路由的磁盘缓存
+//!
+//! 以 URL 的 sha256 作为文件名，缓存内容保存在 `<key>.bin`，元数据保存在 `<key>.json`。
+//! 遵循 `Cache-Control` / `Expires`，过期后使用 `ETag` / `Last-Modified` 条件请求重新验证，
+//! 超出容量时淘汰最久未访问的条目。
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+use futures_util::StreamExt;
+use log::{debug, info, warn};
+use reqwest::header::{HeaderMap, CACHE_CONTROL, CONTENT_TYPE, ETAG, EXPIRES, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use warp::hyper::Body;
+use warp::reply::{Reply, Response};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+/// 缓存的最长有效期（秒），过大的 max-age 按此处理
+const MAX_AGE: i64 = 365 * 24 * 3600;
+
+#[derive(Clone, Copy)]
+pub struct AssetCacheConfig {
+    /// 单个资源的最大字节数，超出时拒绝代理
+    pub max_body_size: u64,
+    /// 缓存目录的最大字节数
+    pub max_cache_size: u64,
+}
+
+impl Default for AssetCacheConfig {
+    fn default() -> Self {
+        Self { max_body_size: 20 * 1024 * 1024, max_cache_size: 200 * 1024 * 1024 }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CacheEntry {
+    url: String,
+    content_type: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// 过期时间（毫秒），为 0 时每次都需要重新验证
+    expires_at: i64,
+    size: u64,
+    last_access: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub revalidated: u64,
+    pub entries: usize,
+    pub size: u64,
+    pub max_body_size: u64,
+    pub max_cache_size: u64,
+}
+
+pub struct AssetCache {
+    dir: RwLock<Option<PathBuf>>,
+    config: RwLock<AssetCacheConfig>,
+    entries: Mutex<HashMap<String, CacheEntry>>,
+    /// 每个条目最近一次开始的下载，同一 URL 并发下载时只保存最后开始的那个
+    fetching: Mutex<HashMap<String, u64>>,
+    next_fetch: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    revalidated: AtomicU64,
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn header(headers: &HeaderMap, name: reqwest::header::HeaderName) -> Option<String> {
+    headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string)
+}
+
+fn error_response(message: &'static str, status: warp::http::StatusCode) -> Response {
+    warp::reply::with_status(message, status).into_response()
+}
+
+/// 根据响应头计算缓存过期时间，返回 None 表示不应缓存
+fn expires_at(headers: &HeaderMap, has_validator: bool, now: i64) -> Option<i64> {
+    let cache_control = header(headers, CACHE_CONTROL).unwrap_or_default().to_ascii_lowercase();
+    let directives: Vec<&str> = cache_control.split(',').map(str::trim).collect();
+    if directives.contains(&"no-store") {
+        return None;
+    }
+    if directives.contains(&"no-cache") {
+        return has_validator.then_some(0);
+    }
+    let max_age = directives
+        .iter()
+        .find_map(|d| d.strip_prefix("max-age=")?.trim_matches('"').parse::<i64>().ok());
+    if let Some(max_age) = max_age {
+        return Some(now.saturating_add(max_age.clamp(0, MAX_AGE).saturating_mul(1000)));
+    }
+    if let Some(expires) = header(headers, EXPIRES).and_then(|v| chrono::DateTime::parse_from_rfc2822(&v).ok()) {
+        return Some(expires.timestamp_millis());
+    }
+    // 没有新鲜度信息时，只有能重新验证的资源才缓存
+    has_validator.then_some(0)
+}
+
+/// 上游没有给出明确类型时，根据内容开头猜测类型
+pub fn sniff_content_type(data: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"BM", "image/bmp"),
+        (b"\x00\x00\x01\x00", "image/x-icon"),
+        (b"%PDF-", "application/pdf"),
+        (b"OggS", "audio/ogg"),
+        (b"ID3", "audio/mpeg"),
+        (b"\x1a\x45\xdf\xa3", "video/webm"),
+    ];
+    if let Some((_, mime)) = SIGNATURES.iter().find(|(sig, _)| data.starts_with(sig)) {
+        return Some(mime);
+    }
+    if data.len() >= 12 && data.starts_with(b"RIFF") {
+        match &data[8..12] {
+            b"WEBP" => return Some("image/webp"),
+            b"WAVE" => return Some("audio/wav"),
+            _ => {}
+        }
+    }
+    if data.len() >= 12 && &data[4..8] == b"ftyp" {
+        return Some(match &data[8..12] {
+            b"avif" | b"avis" => "image/avif",
+            b"heic" | b"heix" => "image/heic",
+            _ => "video/mp4",
+        });
+    }
+    let text = String::from_utf8_lossy(&data[..data.len().min(512)]);
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+    if text.starts_with("<svg") || (text.starts_with("<?xml") && text.contains("<svg")) {
+        return Some("image/svg+xml");
+    }
+    None
+}
+
+fn resolve_content_type(upstream: Option<&str>, data: &[u8]) -> String {
+    let generic = |t: &str| t.is_empty() || t.starts_with("application/octet-stream") || t.starts_with("binary/");
+    match upstream {
+        Some(t) if !generic(t) => t.to_string(),
+        _ => sniff_content_type(data).unwrap_or("application/octet-stream").to_string(),
+    }
+}
+
+impl AssetCache {
+    pub fn new(config: AssetCacheConfig) -> Self {
+        Self {
+            dir: RwLock::new(None),
+            config: RwLock::new(config),
+            entries: Mutex::new(HashMap::new()),
+            fetching: Mutex::new(HashMap::new()),
+            next_fetch: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            revalidated: AtomicU64::new(0),
+        }
+    }
+
+    /// 设置缓存目录并加载已有的缓存索引，未设置目录前只代理不缓存
+    pub fn set_dir(&self, dir: PathBuf) -> Result<(), String> {
+        fs::create_dir_all(&dir).map_err(|e| format!("无法创建缓存目录 {}: {}", dir.display(), e))?;
+        let mut entries = HashMap::new();
+        for path in fs::read_dir(&dir).map_err(|e| e.to_string())?.flatten().map(|e| e.path()) {
+            let Some(key) = path.file_stem().and_then(|s| s.to_str()).map(str::to_string) else { continue };
+            match path.extension().and_then(|e| e.to_str()) {
+                Some("json") => {
+                    let entry = fs::read(&path).ok().and_then(|c| serde_json::from_slice::<CacheEntry>(&c).ok());
+                    match entry {
+                        Some(entry) if dir.join(format!("{}.bin", key)).exists() => {
+                            entries.insert(key, entry);
+                        }
+                        _ => Self::remove_files(&dir, &key),
+                    }
+                }
+                // 上次未完成的下载（<key>.<序号>.tmp）或缺少元数据的内容
+                Some("tmp") => {
+                    let _ = fs::remove_file(&path);
+                }
+                Some("bin") if !path.with_extension("json").exists() => {
+                    let _ = fs::remove_file(&path);
+                }
+                _ => {}
+            }
+        }
+        info!("资源缓存目录：{}，已缓存 {} 个资源", dir.display(), entries.len());
+        *self.entries.lock().unwrap() = entries;
+        *self.dir.write().unwrap() = Some(dir);
+        self.evict(None);
+        Ok(())
+    }
+
+    pub fn set_config(&self, config: AssetCacheConfig) {
+        *self.config.write().unwrap() = config;
+        self.evict(None);
+    }
+
+    pub fn stats(&self) -> AssetCacheStats {
+        let config = *self.config.read().unwrap();
+        let entries = self.entries.lock().unwrap();
+        AssetCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            revalidated: self.revalidated.load(Ordering::Relaxed),
+            entries: entries.len(),
+            size: entries.values().map(|e| e.size).sum(),
+            max_body_size: config.max_body_size,
+            max_cache_size: config.max_cache_size,
+        }
+    }
+
+    pub fn clear(&self) {
+        let dir = self.dir.read().unwrap().clone();
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(dir) = dir {
+            for key in entries.keys() {
+                Self::remove_files(&dir, key);
+            }
+        }
+        entries.clear();
+        // 进行中的下载完成后不再写入缓存
+        self.fetching.lock().unwrap().clear();
+    }
+
+    fn remove_files(dir: &Path, key: &str) {
+        let _ = fs::remove_file(dir.join(format!("{}.bin", key)));
+        let _ = fs::remove_file(dir.join(format!("{}.json", key)));
+    }
+
+    /// 超出容量时按最近访问时间淘汰，keep 为刚写入、不应被淘汰的条目
+    fn evict(&self, keep: Option<&str>) {
+        let Some(dir) = self.dir.read().unwrap().clone() else { return };
+        let max = self.config.read().unwrap().max_cache_size;
+        let mut entries = self.entries.lock().unwrap();
+        let mut total: u64 = entries.values().map(|e| e.size).sum();
+        let mut order: Vec<(i64, String)> = entries
+            .iter()
+            .filter(|(key, _)| Some(key.as_str()) != keep)
+            .map(|(key, e)| (e.last_access, key.clone()))
+            .collect();
+        order.sort();
+        for (_, key) in order {
+            if total <= max {
+                break;
+            }
+            if let Some(entry) = entries.remove(&key) {
+                total -= entry.size;
+                Self::remove_files(&dir, &key);
+                debug!("淘汰缓存资源：{}", entry.url);
+            }
+        }
+    }
+
+    fn remove_entry(&self, key: &str) {
+        let Some(dir) = self.dir.read().unwrap().clone() else { return };
+        self.entries.lock().unwrap().remove(key);
+        Self::remove_files(&dir, key);
+    }
+
+    /// 查找缓存条目并更新访问时间
+    fn lookup(&self, key: &str) -> Option<(PathBuf, CacheEntry)> {
+        let dir = self.dir.read().unwrap().clone()?;
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.get_mut(key)?;
+        entry.last_access = now();
+        Some((dir.join(format!("{}.bin", key)), entry.clone()))
+    }
+
+    fn store_entry(&self, key: &str, entry: CacheEntry) {
+        let Some(dir) = self.dir.read().unwrap().clone() else { return };
+        match serde_json::to_vec(&entry) {
+            Ok(meta) if fs::write(dir.join(format!("{}.json", key)), &meta).is_ok() => {
+                self.entries.lock().unwrap().insert(key.to_string(), entry);
+                self.evict(Some(key));
+            }
+            _ => {
+                warn!("写入缓存元数据失败：{}", entry.url);
+                self.entries.lock().unwrap().remove(key);
+                Self::remove_files(&dir, key);
+            }
+        }
+    }
+
+    /// 代理一个资源：新鲜的缓存直接返回，过期的缓存先重新验证，否则从上游获取
+    pub async fn serve(self: Arc<Self>, client: &Client, url: &str) -> Response {
+        let key = hex::encode(Sha256::digest(url.as_bytes()));
+        let mut cached = self.lookup(&key);
+        if let Some((path, entry)) = &cached {
+            if entry.expires_at > now() {
+                if let Some(response) = Self::respond_cached(path, entry, "HIT").await {
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                    return response;
+                }
+                self.remove_entry(&key);
+                cached = None;
+            }
+        }
+
+        let mut request = client.get(url);
+        if let Some((_, entry)) = &cached {
+            if let Some(etag) = &entry.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &entry.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let mut response = match request.send().await {
+            Ok(response) => response,
+            Err(_) => return error_response("请求失败", warp::http::StatusCode::BAD_GATEWAY),
+        };
+
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some((path, mut entry)) = cached {
+                entry.expires_at = expires_at(response.headers(), true, now()).unwrap_or(0);
+                if let Some(response) = Self::respond_cached(&path, &entry, "REVALIDATED").await {
+                    self.store_entry(&key, entry);
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                    self.revalidated.fetch_add(1, Ordering::Relaxed);
+                    return response;
+                }
+                // 缓存的内容已无法读取，丢弃后不带条件重新请求
+                self.remove_entry(&key);
+                response = match client.get(url).send().await {
+                    Ok(response) => response,
+                    Err(_) => return error_response("请求失败", warp::http::StatusCode::BAD_GATEWAY),
+                };
+            }
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        self.fetch(key, url, response).await
+    }
+
+    async fn respond_cached(path: &Path, entry: &CacheEntry, state: &'static str) -> Option<Response> {
+        let mut file = tokio::fs::File::open(path).await.ok()?;
+        let (mut sender, body) = Body::channel();
+        tokio::spawn(async move {
+            let mut buf = vec![0; CHUNK_SIZE];
+            loop {
+                match file.read(&mut buf).await {
+                    Ok(0) => break,
+                    Ok(n) => {
+                        if sender.send_data(buf[..n].to_vec().into()).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => {
+                        sender.abort();
+                        break;
+                    }
+                }
+            }
+        });
+        warp::http::Response::builder()
+            .header("Content-Type", &entry.content_type)
+            .header("Content-Length", entry.size)
+            .header("X-Content-Type-Options", "nosniff")
+            .header("X-Cache", state)
+            .body(body)
+            .ok()
+    }
+
+    /// 从上游流式转发资源，可缓存时同时写入缓存目录
+    async fn fetch(self: Arc<Self>, key: String, url: &str, response: reqwest::Response) -> Response {
+        let max_body_size = self.config.read().unwrap().max_body_size;
+        if response.content_length().is_some_and(|len| len > max_body_size) {
+            return error_response("资源过大", warp::http::StatusCode::PAYLOAD_TOO_LARGE);
+        }
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let content_length = response.content_length();
+        let etag = header(&headers, ETAG);
+        let last_modified = header(&headers, LAST_MODIFIED);
+        let expires = expires_at(&headers, etag.is_some() || last_modified.is_some(), now());
+        let dir = self.dir.read().unwrap().clone();
+        let cache_path = dir.filter(|_| status == StatusCode::OK && expires.is_some());
+
+        let mut stream = response.bytes_stream();
+        let first = match stream.next().await {
+            Some(Ok(chunk)) => chunk,
+            Some(Err(_)) => return error_response("请求失败", warp::http::StatusCode::BAD_GATEWAY),
+            None => Default::default(),
+        };
+        let content_type = resolve_content_type(header(&headers, CONTENT_TYPE).as_deref(), &first);
+
+        // 每次下载使用独立的临时文件，并发请求同一 URL 时内容不会互相混杂
+        let fetch_id = self.next_fetch.fetch_add(1, Ordering::Relaxed);
+        if cache_path.is_some() {
+            self.fetching.lock().unwrap().insert(key.clone(), fetch_id);
+        }
+
+        let (mut sender, body) = Body::channel();
+        let cache = self.clone();
+        let entry_type = content_type.clone();
+        let url = url.to_string();
+        tokio::spawn(async move {
+            let tmp = cache_path.as_ref().map(|dir| dir.join(format!("{}.{}.tmp", key, fetch_id)));
+            let mut file = match &tmp {
+                Some(tmp) => tokio::fs::File::create(tmp).await.ok(),
+                None => None,
+            };
+            let mut written: u64 = 0;
+            let mut next = Some(Ok(first));
+            let completed = loop {
+                let chunk = match next {
+                    None => break true,
+                    Some(Ok(chunk)) => chunk,
+                    Some(Err(_)) => break false,
+                };
+                written += chunk.len() as u64;
+                if written > max_body_size {
+                    warn!("资源超过大小限制，已中断：{}", url);
+                    break false;
+                }
+                if let Some(f) = file.as_mut() {
+                    if f.write_all(&chunk).await.is_err() {
+                        file = None;
+                    }
+                }
+                if sender.send_data(chunk).await.is_err() {
+                    break false;
+                }
+                next = stream.next().await;
+            };
+            if !completed {
+                sender.abort();
+            }
+
+            let Some(tmp) = tmp else { return };
+            let saved = match file {
+                Some(mut f) if completed => f.flush().await.is_ok() && f.sync_all().await.is_ok(),
+                _ => false,
+            };
+            // 只保存最后开始的下载，期间已有更新的下载或缓存被清空时丢弃
+            let current = {
+                let mut fetching = cache.fetching.lock().unwrap();
+                let current = fetching.get(&key) == Some(&fetch_id);
+                if current {
+                    fetching.remove(&key);
+                }
+                current
+            };
+            let target = tmp.with_file_name(format!("{}.bin", key));
+            if saved && current && tokio::fs::rename(&tmp, &target).await.is_ok() {
+                let entry = CacheEntry {
+                    url,
+                    content_type: entry_type,
+                    etag,
+                    last_modified,
+                    expires_at: expires.unwrap_or(0),
+                    size: written,
+                    last_access: now(),
+                };
+                cache.store_entry(&key, entry);
+            } else {
+                let _ = tokio::fs::remove_file(&tmp).await;
+            }
+        });
+
+        let mut builder = warp::http::Response::builder()
+            .status(status.as_u16())
+            .header("Content-Type", content_type)
+            .header("X-Content-Type-Options", "nosniff")
+            .header("X-Cache", "MISS");
+        if let Some(len) = content_length {
+            builder = builder.header("Content-Length", len);
+        }
+        builder
+            .body(body)
+            .unwrap_or_else(|_| error_response("请求失败", warp::http::StatusCode::BAD_GATEWAY))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_freshness_and_sniffs() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(expires_at(&headers, false, 1000), None);
+        assert_eq!(expires_at(&headers, true, 1000), Some(0));
+        headers.insert(CACHE_CONTROL, "public, max-age=60".parse().unwrap());
+        assert_eq!(expires_at(&headers, false, 1000), Some(61_000));
+        headers.insert(CACHE_CONTROL, "max-age=9223372036854775807".parse().unwrap());
+        assert_eq!(expires_at(&headers, false, 1000), Some(1000 + MAX_AGE * 1000));
+        headers.insert(CACHE_CONTROL, "max-age=-5".parse().unwrap());
+        assert_eq!(expires_at(&headers, false, 1000), Some(1000));
+        headers.insert(CACHE_CONTROL, "no-cache".parse().unwrap());
+        assert_eq!(expires_at(&headers, true, 1000), Some(0));
+        headers.insert(CACHE_CONTROL, "no-store, max-age=60".parse().unwrap());
+        assert_eq!(expires_at(&headers, true, 1000), None);
+        headers.remove(CACHE_CONTROL);
+        headers.insert(EXPIRES, "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        assert_eq!(expires_at(&headers, false, 0), Some(1_445_412_480_000));
+
+        assert_eq!(resolve_content_type(None, b"\x89PNG\r\n\x1a\n...."), "image/png");
+        assert_eq!(resolve_content_type(Some("application/octet-stream"), b"RIFF\0\0\0\0WEBPVP8 "), "image/webp");
+        assert_eq!(resolve_content_type(None, b"<?xml version=\"1.0\"?><svg/>"), "image/svg+xml");
+        assert_eq!(resolve_content_type(Some("image/jpeg"), b"GIF89a"), "image/jpeg");
+        assert_eq!(resolve_content_type(None, b"hello"), "application/octet-stream");
+    }
+
+    #[tokio::test]
+    async fn concurrent_fetches_do_not_mix() {
+        use std::convert::Infallible;
+        use warp::Filter;
+
+        // 每个请求返回不同长度、不同内容的数据，分块缓慢发送
+        let requests = Arc::new(AtomicU64::new(0));
+        let upstream = warp::any().map(move || {
+            let n = requests.fetch_add(1, Ordering::SeqCst);
+            let byte = b'a' + n as u8;
+            let chunks = futures_util::stream::iter(0..8 - 4 * n).then(move |_| async move {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                Ok::<_, Infallible>(vec![byte; 1024])
+            });
+            warp::http::Response::builder()
+                .header("Cache-Control", "max-age=60")
+                .body(Body::wrap_stream(chunks))
+                .unwrap()
+        });
+        let (addr, server) = warp::serve(upstream).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let dir = std::env::temp_dir().join(format!("renflow-assets-{}", std::process::id()));
+        let cache = Arc::new(AssetCache::new(AssetCacheConfig::default()));
+        cache.set_dir(dir.clone()).unwrap();
+        let client = Client::new();
+        let url = format!("http://{}/avatar", addr);
+        let body = |response: Response| async move { warp::hyper::body::to_bytes(response.into_body()).await.unwrap() };
+
+        let (a, b) = tokio::join!(cache.clone().serve(&client, &url), cache.clone().serve(&client, &url));
+        let (a, b) = tokio::join!(body(a), body(b));
+        assert_ne!(a, b);
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+        let cached = cache.clone().serve(&client, &url).await;
+        assert_eq!(cached.headers()["X-Cache"], "HIT");
+        let length: usize = cached.headers()["Content-Length"].to_str().unwrap().parse().unwrap();
+        let cached = body(cached).await;
+        assert_eq!(cached.len(), length);
+        assert!(cached == a || cached == b);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn refetches_when_cached_body_is_missing() {
+        use warp::Filter;
+
+        // 带 If-None-Match 时返回 304，否则返回完整内容
+        let upstream = warp::header::optional::<String>("if-none-match").map(|etag: Option<String>| {
+            let builder = warp::http::Response::builder().header("ETag", "\"v1\"").header("Cache-Control", "no-cache");
+            match etag {
+                Some(_) => builder.status(304).body(Body::empty()).unwrap(),
+                None => builder.body(Body::from("body")).unwrap(),
+            }
+        });
+        let (addr, server) = warp::serve(upstream).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let dir = std::env::temp_dir().join(format!("renflow-assets-304-{}", std::process::id()));
+        let cache = Arc::new(AssetCache::new(AssetCacheConfig::default()));
+        cache.set_dir(dir.clone()).unwrap();
+        let client = Client::new();
+        let url = format!("http://{}/avatar", addr);
+        let body = |response: Response| async move { warp::hyper::body::to_bytes(response.into_body()).await.unwrap() };
+
+        body(cache.clone().serve(&client, &url).await).await;
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let revalidated = cache.clone().serve(&client, &url).await;
+        assert_eq!(revalidated.headers()["X-Cache"], "REVALIDATED");
+        assert_eq!(body(revalidated).await, "body");
+
+        // 缓存内容被删除后，304 不能直接返回给客户端
+        let key = hex::encode(Sha256::digest(url.as_bytes()));
+        fs::remove_file(dir.join(format!("{}.bin", key))).unwrap();
+        let response = cache.clone().serve(&client, &url).await;
+        assert_eq!(response.status(), warp::http::StatusCode::OK);
+        assert_eq!(response.headers()["X-Cache"], "MISS");
+        assert_eq!(body(response).await, "body");
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,478 @@
+//! 下载管理
+//!
+//! 每个下载有唯一的 ID，先写入同目录的 `.part` 文件，完成并校验后再重命名。
+//! 暂停或失败后继续下载时通过 Range 请求续传，进度与状态通过 [`EventSink`] 推送。
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use futures_util::StreamExt;
+use log::{error, info};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use reqwest::header::{CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{watch, Semaphore};
+
+use super::http_client;
+use crate::connectors::EventSink;
+
+/// 进度事件的最小间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadRequest {
+    pub url: String,
+    /// 保存的文件名，未设置时从 URL 中获取
+    #[serde(default)]
+    pub file_name: Option<String>,
+    /// 保存目录，未设置时使用默认下载目录
+    #[serde(default)]
+    pub dir: Option<PathBuf>,
+    /// 文件校验值，格式为 `sha256:<hex>` 或 `sha1:<hex>`，也可以只写十六进制值
+    #[serde(default)]
+    pub checksum: Option<String>,
+    /// 覆盖同名文件，否则自动在文件名后追加序号
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadState {
+    /// 等待空闲的下载位
+    Queued,
+    Downloading,
+    Paused,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadInfo {
+    pub id: String,
+    pub url: String,
+    pub path: PathBuf,
+    pub state: DownloadState,
+    pub loaded: u64,
+    /// 服务器未返回长度时为空
+    pub total: Option<u64>,
+    pub error: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Control {
+    Run,
+    Pause,
+    Cancel,
+}
+
+struct Download {
+    info: DownloadInfo,
+    checksum: Option<String>,
+    /// 上次响应的 ETag 或 Last-Modified，续传时用于 If-Range
+    validator: Option<String>,
+    control: Option<watch::Sender<Control>>,
+}
+
+fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+/// 从 URL 中获取文件名，去掉路径分隔符等不能用于文件名的字符
+fn file_name_from_url(url: &str) -> String {
+    let name = url::Url::parse(url)
+        .ok()
+        .and_then(|url| url.path_segments()?.rev().find(|s| !s.is_empty()).map(str::to_string))
+        // 借用 form_urlencoded 解码路径中的百分号编码，`+` 在路径中不表示空格
+        .map(|s| {
+            let query = format!("n={}", s.replace('+', "%2B"));
+            url::form_urlencoded::parse(query.as_bytes()).next().map(|(_, v)| v.into_owned()).unwrap_or(s)
+        })
+        .unwrap_or_default();
+    sanitize(&name)
+}
+
+fn sanitize(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_control() || "/\\:*?\"<>|".contains(c) { '_' } else { c })
+        .collect();
+    let name = name.trim().trim_matches('.').to_string();
+    if name.is_empty() { "download".to_string() } else { name }
+}
+
+/// 目标文件已存在时在扩展名前追加 ` (n)`
+fn unique_path(path: PathBuf, taken: impl Fn(&Path) -> bool) -> PathBuf {
+    if !taken(&path) {
+        return path;
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+    let ext = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    (1..)
+        .map(|n| path.with_file_name(format!("{} ({}){}", stem, n, ext)))
+        .find(|p| !taken(p))
+        .unwrap()
+}
+
+fn hash_file<D: Digest>(path: &Path) -> std::io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = D::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// 校验文件，按前缀或长度判断算法
+fn verify_checksum(path: &Path, checksum: &str) -> Result<(), String> {
+    let checksum = checksum.trim().to_ascii_lowercase();
+    let (algorithm, expected) = match checksum.split_once(':') {
+        Some((algorithm, expected)) => (algorithm.to_string(), expected.trim().to_string()),
+        None if checksum.len() == 40 => ("sha1".to_string(), checksum.clone()),
+        None => ("sha256".to_string(), checksum.clone()),
+    };
+    let actual = match algorithm.as_str() {
+        "sha256" => hash_file::<Sha256>(path),
+        "sha1" => hash_file::<Sha1>(path),
+        other => return Err(format!("不支持的校验算法: {}", other)),
+    }
+    .map_err(|e| format!("读取文件失败: {}", e))?;
+    if actual != expected {
+        return Err(format!("文件校验失败，期望 {}，实际 {}", expected, actual));
+    }
+    Ok(())
+}
+
+/// 下载管理器，同时进行的下载数量受限，超出的下载排队等待
+pub struct DownloadManager {
+    sink: Arc<dyn EventSink>,
+    dir: RwLock<PathBuf>,
+    slots: Semaphore,
+    downloads: Mutex<HashMap<String, Download>>,
+}
+
+impl DownloadManager {
+    pub fn new(sink: Arc<dyn EventSink>, dir: PathBuf, max_concurrent: usize) -> Self {
+        Self {
+            sink,
+            dir: RwLock::new(dir),
+            slots: Semaphore::new(max_concurrent.max(1)),
+            downloads: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn dir(&self) -> PathBuf {
+        self.dir.read().unwrap().clone()
+    }
+
+    pub fn set_dir(&self, dir: PathBuf) {
+        *self.dir.write().unwrap() = dir;
+    }
+
+    pub fn list(&self) -> Vec<DownloadInfo> {
+        let mut list: Vec<DownloadInfo> = self.downloads.lock().unwrap().values().map(|d| d.info.clone()).collect();
+        list.sort_by_key(|info| info.created_at);
+        list
+    }
+
+    pub fn get(&self, id: &str) -> Option<DownloadInfo> {
+        self.downloads.lock().unwrap().get(id).map(|d| d.info.clone())
+    }
+
+    /// 创建下载并在后台开始，需要在 tokio 运行时中调用
+    pub fn start(self: &Arc<Self>, request: DownloadRequest) -> Result<DownloadInfo, String> {
+        let dir = request.dir.clone().unwrap_or_else(|| self.dir());
+        fs::create_dir_all(&dir).map_err(|e| format!("无法创建下载目录 {}: {}", dir.display(), e))?;
+        let name = request.file_name.as_deref().map(sanitize).unwrap_or_else(|| file_name_from_url(&request.url));
+
+        let id: String = rand::thread_rng().sample_iter(&Alphanumeric).take(12).map(char::from).collect();
+        let mut downloads = self.downloads.lock().unwrap();
+        // 同时进行的下载不能写入同一个文件
+        let active = |path: &Path| {
+            downloads.values().any(|d| {
+                d.info.path == path && !matches!(d.info.state, DownloadState::Completed | DownloadState::Cancelled)
+            })
+        };
+        let path = if request.overwrite {
+            let path = dir.join(&name);
+            if active(&path) {
+                return Err(format!("文件 {} 正在下载中", path.display()));
+            }
+            path
+        } else {
+            unique_path(dir.join(&name), |p| p.exists() || part_path(p).exists() || active(p))
+        };
+        // 清理之前残留的临时文件，避免续传到无关的内容上
+        let _ = fs::remove_file(part_path(&path));
+
+        let info = DownloadInfo {
+            id: id.clone(),
+            url: request.url,
+            path,
+            state: DownloadState::Queued,
+            loaded: 0,
+            total: None,
+            error: None,
+            created_at: chrono::Utc::now().timestamp_millis(),
+        };
+        info!("开始下载 {}：{} -> {}", id, info.url, info.path.display());
+        downloads.insert(
+            id.clone(),
+            Download { info: info.clone(), checksum: request.checksum, validator: None, control: None },
+        );
+        drop(downloads);
+        self.spawn(&id);
+        Ok(info)
+    }
+
+    fn spawn(self: &Arc<Self>, id: &str) {
+        let (control, receiver) = watch::channel(Control::Run);
+        if let Some(download) = self.downloads.lock().unwrap().get_mut(id) {
+            download.info.state = DownloadState::Queued;
+            download.info.error = None;
+            download.control = Some(control);
+        }
+        self.emit_state(id);
+        tokio::spawn(self.clone().run(id.to_string(), receiver));
+    }
+
+    /// 暂停下载，已下载的部分保留用于续传
+    pub fn pause(&self, id: &str) -> Result<(), String> {
+        self.signal(id, Control::Pause)
+    }
+
+    /// 继续暂停或失败的下载
+    pub fn resume(self: &Arc<Self>, id: &str) -> Result<(), String> {
+        match self.get(id).map(|info| info.state) {
+            Some(DownloadState::Paused | DownloadState::Failed) => {
+                self.spawn(id);
+                Ok(())
+            }
+            Some(_) => Err(format!("下载 {} 无法继续", id)),
+            None => Err(format!("下载 {} 不存在", id)),
+        }
+    }
+
+    /// 取消下载并删除已下载的部分
+    pub fn cancel(&self, id: &str) -> Result<(), String> {
+        let path = match self.get(id) {
+            Some(info) if matches!(info.state, DownloadState::Queued | DownloadState::Downloading) => {
+                return self.signal(id, Control::Cancel);
+            }
+            Some(info) if matches!(info.state, DownloadState::Paused | DownloadState::Failed) => info.path,
+            Some(_) => return Err(format!("下载 {} 已结束", id)),
+            None => return Err(format!("下载 {} 不存在", id)),
+        };
+        let _ = fs::remove_file(part_path(&path));
+        self.update(id, |info| info.state = DownloadState::Cancelled);
+        self.emit_state(id);
+        Ok(())
+    }
+
+    fn signal(&self, id: &str, control: Control) -> Result<(), String> {
+        let downloads = self.downloads.lock().unwrap();
+        let sender = downloads
+            .get(id)
+            .ok_or_else(|| format!("下载 {} 不存在", id))?
+            .control
+            .as_ref()
+            .filter(|sender| !sender.is_closed())
+            .ok_or_else(|| format!("下载 {} 未在进行", id))?;
+        let _ = sender.send(control);
+        Ok(())
+    }
+
+    fn update(&self, id: &str, f: impl FnOnce(&mut DownloadInfo)) {
+        if let Some(download) = self.downloads.lock().unwrap().get_mut(id) {
+            f(&mut download.info);
+        }
+    }
+
+    fn emit_state(&self, id: &str) {
+        if let Some(info) = self.get(id) {
+            self.sink.send("sys:downloadState", serde_json::to_value(info).unwrap_or_default());
+        }
+    }
+
+    fn emit_progress(&self, id: &str, loaded: u64, total: Option<u64>) {
+        self.sink.send(
+            "sys:downloadProgress",
+            json!({ "id": id, "lengthComputable": total.is_some(), "loaded": loaded, "total": total }),
+        );
+    }
+
+    async fn run(self: Arc<Self>, id: String, mut control: watch::Receiver<Control>) {
+        // 下载结束前收到暂停或取消时为 None
+        let result = tokio::select! {
+            result = self.transfer(&id) => Some(result),
+            _ = control.wait_for(|c| *c != Control::Run) => None,
+        };
+        let path = self.get(&id).map(|info| info.path).unwrap_or_default();
+        let signal = *control.borrow();
+        match (signal, result) {
+            (_, Some(Ok(()))) => {
+                info!("下载完成：{}", path.display());
+                self.update(&id, |info| info.state = DownloadState::Completed);
+            }
+            (_, Some(Err(err))) => {
+                error!("下载失败 {}: {}", id, err);
+                self.update(&id, |info| {
+                    info.state = DownloadState::Failed;
+                    info.error = Some(err);
+                });
+            }
+            (Control::Cancel, None) => {
+                let _ = fs::remove_file(part_path(&path));
+                info!("下载已取消：{}", id);
+                self.update(&id, |info| info.state = DownloadState::Cancelled);
+            }
+            (_, None) => {
+                info!("下载已暂停：{}", id);
+                self.update(&id, |info| info.state = DownloadState::Paused);
+            }
+        }
+        if let Some(download) = self.downloads.lock().unwrap().get_mut(&id) {
+            download.control = None;
+        }
+        self.emit_state(&id);
+    }
+
+    async fn transfer(&self, id: &str) -> Result<(), String> {
+        let _slot = self.slots.acquire().await.map_err(|e| e.to_string())?;
+        let (url, path, checksum, validator) = {
+            let mut downloads = self.downloads.lock().unwrap();
+            let download = downloads.get_mut(id).ok_or_else(|| format!("下载 {} 不存在", id))?;
+            download.info.state = DownloadState::Downloading;
+            (download.info.url.clone(), download.info.path.clone(), download.checksum.clone(), download.validator.clone())
+        };
+        self.emit_state(id);
+
+        let part = part_path(&path);
+        let offset = tokio::fs::metadata(&part).await.map(|m| m.len()).unwrap_or(0);
+        let mut request = http_client::client().get(&url);
+        if offset > 0 {
+            request = request.header(RANGE, format!("bytes={}-", offset));
+            if let Some(validator) = &validator {
+                request = request.header(IF_RANGE, validator);
+            }
+        }
+        let response = request.send().await.map_err(|e| format!("请求失败: {}", e))?;
+        let status = response.status();
+
+        // 已下载的部分就是完整的文件
+        let complete = status == StatusCode::RANGE_NOT_SATISFIABLE
+            && response
+                .headers()
+                .get(CONTENT_RANGE)
+                .and_then(|v| v.to_str().ok()?.strip_prefix("bytes */")?.parse::<u64>().ok())
+                == Some(offset);
+        if !complete {
+            if !status.is_success() {
+                return Err(format!("服务器返回 {}", status));
+            }
+            let validator = response.headers().get(ETAG).or_else(|| response.headers().get(LAST_MODIFIED));
+            let validator = validator.and_then(|v| v.to_str().ok()).map(str::to_string);
+            if let Some(download) = self.downloads.lock().unwrap().get_mut(id) {
+                download.validator = validator;
+            }
+
+            // 服务器不支持 Range 或文件已变化时返回 200，需要从头下载
+            let resumed = status == StatusCode::PARTIAL_CONTENT;
+            let mut loaded = if resumed { offset } else { 0 };
+            let total = response.content_length().map(|len| len + loaded);
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .write(true)
+                .append(resumed)
+                .truncate(!resumed)
+                .open(&part)
+                .await
+                .map_err(|e| format!("创建文件失败: {}", e))?;
+            self.update(id, |info| {
+                info.loaded = loaded;
+                info.total = total;
+            });
+            self.emit_progress(id, loaded, total);
+
+            let mut stream = response.bytes_stream();
+            let mut last_emit = Instant::now();
+            while let Some(chunk) = stream.next().await {
+                let chunk = chunk.map_err(|e| format!("下载中断: {}", e))?;
+                file.write_all(&chunk).await.map_err(|e| format!("写入文件失败: {}", e))?;
+                loaded += chunk.len() as u64;
+                self.update(id, |info| info.loaded = loaded);
+                if last_emit.elapsed() >= PROGRESS_INTERVAL {
+                    last_emit = Instant::now();
+                    self.emit_progress(id, loaded, total);
+                }
+            }
+            file.flush().await.map_err(|e| format!("写入文件失败: {}", e))?;
+            if total.is_some_and(|total| loaded < total) {
+                return Err("下载中断: 数据不完整".to_string());
+            }
+            self.update(id, |info| info.total = Some(loaded));
+            self.emit_progress(id, loaded, Some(loaded));
+        }
+
+        if let Some(checksum) = checksum.filter(|c| !c.trim().is_empty()) {
+            // 计算大文件的哈希较慢，放到阻塞线程中执行
+            let target = part.clone();
+            let result = tokio::task::spawn_blocking(move || verify_checksum(&target, &checksum))
+                .await
+                .map_err(|e| format!("校验文件失败: {}", e))?;
+            if let Err(err) = result {
+                // 校验失败的内容无法通过续传修复
+                let _ = tokio::fs::remove_file(&part).await;
+                return Err(err);
+            }
+        }
+        tokio::fs::rename(&part, &path).await.map_err(|e| format!("保存文件失败: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_and_verifies_files() {
+        assert_eq!(file_name_from_url("https://example.com/files/a%20b.zip?x=1"), "a b.zip");
+        assert_eq!(file_name_from_url("https://example.com/"), "download");
+        assert_eq!(sanitize("../evil:name"), "_evil_name");
+
+        let dir = std::env::temp_dir().join(format!("renflow-download-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("a.txt");
+        fs::write(&file, "hello").unwrap();
+        assert_eq!(unique_path(file.clone(), |p| p.exists()), dir.join("a (1).txt"));
+
+        let sha256 = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        assert!(verify_checksum(&file, sha256).is_ok());
+        assert!(verify_checksum(&file, &format!("SHA256:{}", sha256.to_uppercase())).is_ok());
+        assert!(verify_checksum(&file, "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d").is_ok());
+        assert!(verify_checksum(&file, "sha256:00").is_err());
+        assert!(verify_checksum(&file, "md5:00").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,480 @@
+//! 代理页面的 HTML / CSS 改写
+//!
+//! 将页面中的相对或外部引用改写为经过本地代理的绝对地址：图片、脚本、字体等资源走 `/assets`，
+//! 页面、框架与样式表走 `/proxy`（继续改写）。同时注入 `<base>`，未能改写的引用也会指向原站点。
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use url::Url;
+
+static CSS_URL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)url\(\s*(?:"([^"]*)"|'([^']*)'|([^)'"\s]*))\s*\)"#).unwrap());
+static CSS_IMPORT: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?i)@import\s+(?:"([^"]*)"|'([^']*)')"#).unwrap());
+static META_CHARSET: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)<meta[^>]+charset\s*=\s*["']?\s*([a-z0-9_\-:.]+)"#).unwrap());
+
+/// 内容按原样保留、不解析其中标签的元素
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "textarea", "title", "xmp"];
+
+pub struct Rewriter<'a> {
+    /// 页面地址，存在 `<base>` 时会被替换为其指向的地址
+    pub base: Url,
+    /// 代理自身的来源，如 `http://localhost:5001`
+    pub origin: &'a str,
+    pub token: &'a str,
+    pub strip_scripts: bool,
+}
+
+/// 从 Content-Type 或 HTML 开头的 `<meta>` 中获取编码
+pub fn detect_charset(content_type: Option<&str>, body: &[u8]) -> &'static encoding_rs::Encoding {
+    let from_header = content_type
+        .and_then(|t| t.split(';').find_map(|p| p.trim().strip_prefix("charset=")))
+        .map(|c| c.trim_matches('"').to_string());
+    let from_meta = || {
+        let head = String::from_utf8_lossy(&body[..body.len().min(1024)]);
+        META_CHARSET.captures(&head).map(|c| c[1].to_string())
+    };
+    from_header
+        .or_else(from_meta)
+        .and_then(|label| encoding_rs::Encoding::for_label(label.as_bytes()))
+        .unwrap_or(encoding_rs::UTF_8)
+}
+
+/// 解码属性值中的常见字符实体
+fn decode_entities(value: &str) -> String {
+    if !value.contains('&') {
+        return value.to_string();
+    }
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(pos) = rest.find('&') {
+        result.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let ch = match entity {
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .map(|hex| u32::from_str_radix(hex, 16).ok())
+                    .unwrap_or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            ch.map(|ch| (ch, end + 1))
+        });
+        match decoded {
+            Some((ch, len)) => {
+                result.push(ch);
+                rest = &rest[len..];
+            }
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+fn escape_attr(value: &str) -> String {
+    value.replace('&', "&amp;").replace('"', "&quot;")
+}
+
+/// 改写开始标签的结果
+enum Tag {
+    Keep,
+    Remove,
+    Replace(String),
+}
+
+/// 解析出的一个属性，raw 为原始文本，未改写的属性原样输出
+struct Attr<'h> {
+    name: String,
+    raw: &'h str,
+    value: Option<String>,
+}
+
+/// 解析开始标签中的属性，input 为标签名之后、`>` 之前的部分
+fn parse_attrs(input: &str) -> Vec<Attr<'_>> {
+    let bytes = input.as_bytes();
+    let mut attrs = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        while i < bytes.len() && (bytes[i].is_ascii_whitespace() || bytes[i] == b'/') {
+            i += 1;
+        }
+        let start = i;
+        while i < bytes.len() && !bytes[i].is_ascii_whitespace() && !b"=/>".contains(&bytes[i]) {
+            i += 1;
+        }
+        if i == start {
+            i += 1;
+            continue;
+        }
+        let name = input[start..i].to_ascii_lowercase();
+        let mut j = i;
+        while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+            j += 1;
+        }
+        let mut value = None;
+        if j < bytes.len() && bytes[j] == b'=' {
+            j += 1;
+            while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+                j += 1;
+            }
+            if j < bytes.len() && (bytes[j] == b'"' || bytes[j] == b'\'') {
+                let quote = bytes[j];
+                let end = input[j + 1..].find(quote as char).map_or(bytes.len(), |p| j + 1 + p);
+                value = Some(decode_entities(&input[j + 1..end]));
+                i = (end + 1).min(bytes.len());
+            } else {
+                let end = input[j..].find(|c: char| c.is_ascii_whitespace()).map_or(bytes.len(), |p| j + p);
+                value = Some(decode_entities(&input[j..end]));
+                i = end;
+            }
+        }
+        attrs.push(Attr { name, raw: &input[start..i], value });
+    }
+    attrs
+}
+
+/// 查找开始标签结束的 `>`，跳过引号中的内容
+fn tag_end(html: &str, from: usize) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in html[from..].char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Some(from + i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 不区分大小写地查找，needle 为小写的 ASCII
+///
+/// 逐字节比较而不是转换整个页面，页面较大时仍为线性
+fn find_ci(haystack: &str, needle: &str, from: usize) -> Option<usize> {
+    let needle = needle.as_bytes();
+    haystack.as_bytes()[from..]
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle))
+        .map(|p| from + p)
+}
+
+/// 页面中所有指定名称的开始标签的属性（名称为小写，值已解码）
+pub(crate) fn find_tags(html: &str, name: &str) -> Vec<Vec<(String, String)>> {
+    let open = format!("<{}", name);
+    let mut tags = Vec::new();
+    let mut pos = 0;
+    while let Some(start) = find_ci(html, &open, pos) {
+        let name_end = start + open.len();
+        pos = name_end;
+        if html[name_end..].starts_with(|c: char| c.is_ascii_alphanumeric() || c == '-') {
+            continue;
+        }
+        let Some(end) = tag_end(html, name_end) else {
+            break;
+        };
+        let attrs = parse_attrs(&html[name_end..end]);
+        tags.push(attrs.into_iter().map(|a| (a.name, a.value.unwrap_or_default())).collect());
+        pos = end + 1;
+    }
+    tags
+}
+
+impl Rewriter<'_> {
+    fn proxied(&self, route: &str, url: &str, rewrite: bool) -> Option<String> {
+        let url = url.trim();
+        let skip = url.is_empty()
+            || url.starts_with('#')
+            || ["data:", "blob:", "javascript:", "mailto:", "tel:", "about:"]
+                .iter()
+                .any(|p| url.len() >= p.len() && url[..p.len()].eq_ignore_ascii_case(p));
+        if skip {
+            return None;
+        }
+        let target = self.base.join(url).ok().filter(|u| matches!(u.scheme(), "http" | "https"))?;
+        let encoded: String = url::form_urlencoded::byte_serialize(target.as_str().as_bytes()).collect();
+        let mut result = format!("{}/{}?url={}&token={}", self.origin, route, encoded, self.token);
+        if rewrite {
+            result.push_str("&rewrite=1");
+            if self.strip_scripts {
+                result.push_str("&scripts=0");
+            }
+        }
+        Some(result)
+    }
+
+    /// 图片、脚本、字体等资源地址
+    pub fn asset_url(&self, url: &str) -> Option<String> {
+        self.proxied("assets", url, false)
+    }
+
+    /// 页面、框架、样式表等需要继续改写的地址
+    pub fn page_url(&self, url: &str) -> Option<String> {
+        self.proxied("proxy", url, true)
+    }
+
+    fn rewrite_srcset(&self, srcset: &str) -> String {
+        srcset
+            .split(',')
+            .map(|candidate| {
+                let candidate = candidate.trim();
+                let (url, descriptor) = candidate.split_once(char::is_whitespace).unwrap_or((candidate, ""));
+                let url = self.asset_url(url).unwrap_or_else(|| url.to_string());
+                if descriptor.is_empty() { url } else { format!("{} {}", url, descriptor.trim()) }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn rewrite_css(&self, css: &str) -> String {
+        let css = CSS_IMPORT.replace_all(css, |c: &Captures| {
+            let url = c.get(1).or(c.get(2)).map_or("", |m| m.as_str());
+            match self.page_url(url) {
+                Some(url) => format!("@import \"{}\"", url),
+                None => c[0].to_string(),
+            }
+        });
+        CSS_URL
+            .replace_all(&css, |c: &Captures| {
+                let url = c.get(1).or(c.get(2)).or(c.get(3)).map_or("", |m| m.as_str());
+                match self.asset_url(url) {
+                    Some(url) => format!("url(\"{}\")", url),
+                    None => c[0].to_string(),
+                }
+            })
+            .into_owned()
+    }
+
+    /// 改写一个开始标签
+    fn rewrite_tag(&self, name: &str, attrs_src: &str, self_closing: bool) -> Tag {
+        let mut attrs = parse_attrs(attrs_src);
+        let value_of = |attrs: &[Attr], key: &str| {
+            attrs.iter().find(|a| a.name == key).and_then(|a| a.value.clone()).unwrap_or_default().to_ascii_lowercase()
+        };
+
+        match name {
+            // 页面原有的 <base> 已用于解析地址，由注入的 <base> 替代
+            "base" => return Tag::Remove,
+            "meta" => {
+                let equiv = value_of(&attrs, "http-equiv");
+                // 内容已转换为 UTF-8，原有编码声明与 CSP 不再适用
+                if attrs.iter().any(|a| a.name == "charset")
+                    || equiv == "content-type"
+                    || equiv.starts_with("content-security-policy")
+                {
+                    return Tag::Remove;
+                }
+            }
+            _ => {}
+        }
+
+        let rel = value_of(&attrs, "rel");
+        let mut changed = false;
+        for attr in attrs.iter_mut() {
+            let Some(value) = attr.value.as_deref() else { continue };
+            let rewritten = match (name, attr.name.as_str()) {
+                (_, "style") => Some(self.rewrite_css(value)),
+                (_, "srcset") => Some(self.rewrite_srcset(value)),
+                (_, n) if self.strip_scripts && n.starts_with("on") => {
+                    attr.raw = "";
+                    attr.value = None;
+                    changed = true;
+                    continue;
+                }
+                (_, "href" | "src") if self.strip_scripts && value.trim_start().to_ascii_lowercase().starts_with("javascript:") => {
+                    Some("#".to_string())
+                }
+                ("a" | "area", "href") | ("form", "action") | ("iframe" | "frame", "src") => self.page_url(value),
+                ("link", "href") if rel.split_whitespace().any(|r| r == "stylesheet") => self.page_url(value),
+                ("link", "href") => self.asset_url(value),
+                ("img" | "source" | "video" | "audio" | "track" | "script" | "input" | "embed", "src") => {
+                    self.asset_url(value)
+                }
+                ("video", "poster") | ("object", "data") | ("body" | "table" | "td" | "th", "background") => {
+                    self.asset_url(value)
+                }
+                _ => None,
+            };
+            if let Some(rewritten) = rewritten {
+                attr.value = Some(rewritten);
+                attr.raw = "";
+                changed = true;
+            }
+        }
+
+        if !changed {
+            return Tag::Keep;
+        }
+        // 改写过的属性 raw 为空，重新输出；被移除的属性 value 也为空
+        let mut tag = format!("<{}", name);
+        for attr in &attrs {
+            match (attr.raw, &attr.value) {
+                ("", Some(value)) => tag.push_str(&format!(" {}=\"{}\"", attr.name, escape_attr(value))),
+                ("", None) => {}
+                (raw, _) => {
+                    tag.push(' ');
+                    tag.push_str(raw);
+                }
+            }
+        }
+        tag.push_str(if self_closing { " />" } else { ">" });
+        Tag::Replace(tag)
+    }
+
+    pub fn rewrite_html(&mut self, html: &str) -> String {
+        // 页面自带的 <base> 决定相对地址的解析方式
+        let bases = find_tags(html, "base");
+        let href = bases.iter().flatten().find(|(name, _)| name == "href").map(|(_, value)| value.as_str());
+        if let Some(base) = href.and_then(|href| self.base.join(href).ok()) {
+            self.base = base;
+        }
+
+        let mut out = String::with_capacity(html.len() + 256);
+        let mut pos = 0;
+        let mut injected = false;
+        while let Some(offset) = html[pos..].find('<') {
+            let start = pos + offset;
+            out.push_str(&html[pos..start]);
+            let rest = &html[start..];
+
+            if rest.starts_with("<!--") {
+                let end = rest.find("-->").map_or(html.len(), |e| start + e + 3);
+                out.push_str(&html[start..end]);
+                pos = end;
+                continue;
+            }
+            let closing = rest.starts_with("</");
+            let name_start = start + if closing { 2 } else { 1 };
+            let name_len = html[name_start..]
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+                .unwrap_or(html.len() - name_start);
+            let Some(end) = (name_len > 0).then(|| tag_end(html, name_start)).flatten() else {
+                // 不是标签的 <
+                out.push('<');
+                pos = start + 1;
+                continue;
+            };
+            let name = html[name_start..name_start + name_len].to_ascii_lowercase();
+            pos = end + 1;
+
+            if closing {
+                let strip = self.strip_scripts && name == "noscript";
+                if !strip {
+                    out.push_str(&html[start..pos]);
+                }
+                continue;
+            }
+
+            let attrs_src = html[name_start + name_len..end].trim_end_matches('/');
+            let self_closing = html[..end].ends_with('/');
+            let raw_end = RAW_TEXT_ELEMENTS
+                .contains(&name.as_str())
+                .then(|| find_ci(html, &format!("</{}", name), pos).unwrap_or(html.len()));
+
+            if self.strip_scripts && name == "script" {
+                // 跳过整个脚本元素，包括结束标签
+                let close = raw_end.unwrap_or(pos);
+                pos = tag_end(html, close).map_or(html.len(), |e| e + 1);
+                continue;
+            }
+            if self.strip_scripts && name == "noscript" {
+                continue;
+            }
+
+            match self.rewrite_tag(&name, attrs_src, self_closing) {
+                Tag::Keep => out.push_str(&html[start..pos]),
+                Tag::Remove => {}
+                Tag::Replace(tag) => out.push_str(&tag),
+            }
+            if name == "head" && !injected {
+                out.push_str(&self.head_injection());
+                injected = true;
+            }
+
+            if let Some(raw_end) = raw_end {
+                let content = &html[pos..raw_end];
+                if name == "style" {
+                    out.push_str(&self.rewrite_css(content));
+                } else {
+                    out.push_str(content);
+                }
+                pos = raw_end;
+            }
+        }
+        out.push_str(&html[pos..]);
+
+        if !injected {
+            out.insert_str(0, &self.head_injection());
+        }
+        out
+    }
+
+    fn head_injection(&self) -> String {
+        format!("<meta charset=\"utf-8\"><base href=\"{}\">", escape_attr(self.base.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrites_references() {
+        let mut rewriter = Rewriter {
+            base: Url::parse("https://example.com/blog/post.html").unwrap(),
+            origin: "http://localhost:5001",
+            token: "t",
+            strip_scripts: true,
+        };
+        let html = r#"<!DOCTYPE html><html><head><base href="/static/"><meta charset="gbk"><script src="a.js"></script>
+<style>body { background: url('bg.png') }</style></head>
+<body onload="x()"><img src="i.png?a=1&amp;b=2" srcset="s1.png 1x, https://cdn.com/s2.png 2x" alt="&quot;">
+<a href="../about" class=link>关于</a><a href="javascript:go()">go</a><noscript><p>no js</p></noscript>
+<div style="background-image:url(&quot;d.png&quot;)">x < y</div></body></html>"#;
+        let out = rewriter.rewrite_html(html);
+        let asset = |u: &str| format!("http://localhost:5001/assets?url={}&amp;token=t", url::form_urlencoded::byte_serialize(u.as_bytes()).collect::<String>());
+
+        assert!(out.contains(r#"<head><meta charset="utf-8"><base href="https://example.com/static/">"#));
+        assert!(!out.contains("gbk") && !out.contains("<script") && !out.contains("onload") && !out.contains("noscript"));
+        assert!(out.contains("<p>no js</p>"));
+        assert!(out.contains(&format!(r#"src="{}""#, asset("https://example.com/static/i.png?a=1&b=2"))));
+        assert!(out.contains(&format!("{} 2x", asset("https://cdn.com/s2.png"))));
+        assert!(out.contains(r#"alt="&quot;""#));
+        assert!(out.contains(r#"href="http://localhost:5001/proxy?url=https%3A%2F%2Fexample.com%2Fabout&amp;token=t&amp;rewrite=1&amp;scripts=0" class=link"#));
+        assert!(out.contains(r##"href="#""##));
+        assert!(out.contains("url(\"http://localhost:5001/assets?url=https%3A%2F%2Fexample.com%2Fstatic%2Fbg.png&token=t\")"));
+        assert!(out.contains("d.png") && out.contains("x < y"));
+
+        assert_eq!(find_ci("中文<SCRIPT>", "<script", 0), Some(6));
+        assert_eq!(find_ci("<Style></STYLE>", "</style", 1), Some(7));
+        assert_eq!(find_ci("<b>", "</style", 0), None);
+
+        assert_eq!(detect_charset(Some("text/html; charset=GBK"), b""), encoding_rs::GBK);
+        assert_eq!(detect_charset(None, br#"<meta http-equiv="Content-Type" content="text/html; charset=gb2312">"#), encoding_rs::GBK);
+        assert_eq!(detect_charset(None, b"<html>"), encoding_rs::UTF_8);
+    }
+
+    #[test]
+    fn basefont_is_not_base() {
+        let mut rewriter = Rewriter {
+            base: Url::parse("https://example.com/blog/post.html").unwrap(),
+            origin: "http://localhost:5001",
+            token: "t",
+            strip_scripts: false,
+        };
+        let out = rewriter.rewrite_html(r#"<head><basefont href="/fonts/"><base href="/static/"></head><img src="i.png">"#);
+        assert_eq!(rewriter.base.as_str(), "https://example.com/static/");
+        assert!(out.contains("<basefont"));
+        assert!(out.contains("https%3A%2F%2Fexample.com%2Fstatic%2Fi.png"));
+    }
+}
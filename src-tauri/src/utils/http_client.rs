@@ -0,0 +1,168 @@
+//! 共享的 HTTP 客户端
+//!
+//! 后端所有的 reqwest 请求都应通过 [`client`] 或 [`builder`] 创建客户端，
+//! 以统一使用用户配置的上游代理、自定义 CA 证书、User-Agent 与超时设置。
+//! 本地代理服务（`/proxy`、`/assets`）需要在本地检查目标地址，不使用上游代理。
+
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use log::info;
+use once_cell::sync::Lazy;
+use reqwest::{Certificate, Client, ClientBuilder, NoProxy, Proxy};
+
+/// 始终直连的地址，本地的 OneBot 实现与代理服务不应经过上游代理
+const ALWAYS_NO_PROXY: &str = "localhost,127.0.0.1,::1";
+
+#[derive(Clone, Debug)]
+pub struct HttpClientConfig {
+    /// 上游代理地址，支持 http://、https://、socks5://、socks5h://
+    pub proxy: Option<String>,
+    pub proxy_username: Option<String>,
+    pub proxy_password: Option<String>,
+    /// 不经过代理的主机，逗号分隔，格式同 NO_PROXY 环境变量
+    pub no_proxy: Option<String>,
+    /// 额外信任的 CA 证书文件（PEM，可包含多个证书）
+    pub ca_bundle: Option<PathBuf>,
+    pub user_agent: Option<String>,
+    pub connect_timeout: Duration,
+    /// 两次读取数据之间的最长等待时间，不限制下载等长时间请求的总时长
+    pub read_timeout: Duration,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            proxy: None,
+            proxy_username: None,
+            proxy_password: None,
+            no_proxy: None,
+            ca_bundle: None,
+            user_agent: None,
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl HttpClientConfig {
+    /// 从设置中读取配置，get 返回对应键的字符串值
+    pub fn from_settings(get: impl Fn(&str) -> Option<String>) -> Self {
+        let get = |key: &str| get(key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        let seconds = |key: &str| get(key).and_then(|v| v.parse::<u64>().ok()).map(Duration::from_secs);
+        let default = Self::default();
+        Self {
+            proxy: get("http_proxy"),
+            proxy_username: get("http_proxy_username"),
+            proxy_password: get("http_proxy_password"),
+            no_proxy: get("http_no_proxy"),
+            ca_bundle: get("http_ca_bundle").map(PathBuf::from),
+            user_agent: get("http_user_agent"),
+            connect_timeout: seconds("http_connect_timeout").unwrap_or(default.connect_timeout),
+            read_timeout: seconds("http_read_timeout").unwrap_or(default.read_timeout),
+        }
+    }
+}
+
+/// 已校验的配置，创建客户端时不会再失败
+struct Applied {
+    config: HttpClientConfig,
+    proxy: Option<Proxy>,
+    certs: Vec<Certificate>,
+    client: Client,
+}
+
+impl Applied {
+    fn new(config: HttpClientConfig) -> Result<Self, String> {
+        let proxy = match &config.proxy {
+            Some(url) => {
+                let mut proxy = Proxy::all(url.as_str()).map_err(|e| format!("无效的代理地址 {}: {}", url, e))?;
+                if let Some(username) = &config.proxy_username {
+                    proxy = proxy.basic_auth(username, config.proxy_password.as_deref().unwrap_or_default());
+                }
+                let no_proxy = match &config.no_proxy {
+                    Some(list) => format!("{},{}", ALWAYS_NO_PROXY, list),
+                    None => ALWAYS_NO_PROXY.to_string(),
+                };
+                Some(proxy.no_proxy(NoProxy::from_string(&no_proxy)))
+            }
+            None => None,
+        };
+        let certs = match &config.ca_bundle {
+            Some(path) => {
+                let pem = std::fs::read(path).map_err(|e| format!("无法读取 CA 证书 {}: {}", path.display(), e))?;
+                Certificate::from_pem_bundle(&pem).map_err(|e| format!("无效的 CA 证书 {}: {}", path.display(), e))?
+            }
+            None => Vec::new(),
+        };
+        let mut applied = Self { config, proxy, certs, client: Client::new() };
+        applied.client = applied.builder().build().map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+        Ok(applied)
+    }
+
+    fn builder(&self) -> ClientBuilder {
+        let mut builder = Client::builder()
+            .connect_timeout(self.config.connect_timeout)
+            .read_timeout(self.config.read_timeout)
+            .user_agent(self.config.user_agent.clone().unwrap_or_else(default_user_agent));
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        for cert in &self.certs {
+            builder = builder.add_root_certificate(cert.clone());
+        }
+        builder
+    }
+}
+
+static APPLIED: Lazy<RwLock<Applied>> =
+    Lazy::new(|| RwLock::new(Applied::new(HttpClientConfig::default()).expect("默认 HTTP 客户端配置无效")));
+
+fn default_user_agent() -> String {
+    format!("RenFlow/{}", env!("CARGO_PKG_VERSION"))
+}
+
+/// 应用新的配置，配置无效时保留原配置并返回错误
+pub fn configure(config: HttpClientConfig) -> Result<(), String> {
+    let applied = Applied::new(config)?;
+    match &applied.config.proxy {
+        Some(proxy) => info!("HTTP 请求将通过上游代理：{}", proxy),
+        None => info!("HTTP 请求不使用上游代理"),
+    }
+    *APPLIED.write().unwrap() = applied;
+    Ok(())
+}
+
+/// 共享的默认客户端
+pub fn client() -> Client {
+    APPLIED.read().unwrap().client.clone()
+}
+
+/// 已应用共享配置的 ClientBuilder，用于需要自定义重定向、证书校验等的场景
+pub fn builder() -> ClientBuilder {
+    APPLIED.read().unwrap().builder()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_and_validates_settings() {
+        let settings = [("http_proxy", "http://proxy.corp:3128"), ("http_read_timeout", "5"), ("http_user_agent", " ")];
+        let config = HttpClientConfig::from_settings(|key| {
+            settings.iter().find(|(k, _)| *k == key).map(|(_, v)| v.to_string())
+        });
+        assert_eq!(config.proxy.as_deref(), Some("http://proxy.corp:3128"));
+        assert_eq!(config.read_timeout, Duration::from_secs(5));
+        assert_eq!(config.connect_timeout, Duration::from_secs(10));
+        assert!(config.user_agent.is_none());
+
+        let applied = Applied::new(config).unwrap();
+        assert!(applied.proxy.is_some());
+
+        let invalid = HttpClientConfig { ca_bundle: Some("/nonexistent/ca.pem".into()), ..Default::default() };
+        assert!(Applied::new(invalid).is_err());
+    }
+}
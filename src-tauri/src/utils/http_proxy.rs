@@ -1,54 +1,457 @@
 use std::collections::HashMap;
 use std::convert::Infallible;
 use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, RwLock};
 
+use futures_util::{Stream, StreamExt};
+use log::warn;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
 use reqwest::Client;
-use warp::reply::Reply;
+use tokio::sync::oneshot;
+use url::Url;
+use warp::http::{HeaderMap, Method, StatusCode};
+use warp::hyper::body::Buf;
+use warp::hyper::Body;
+use warp::reply::{Reply, Response};
 use warp::Filter;
 
+use super::asset_cache::AssetCache;
+use super::html_rewrite::{detect_charset, Rewriter};
+use super::http_client;
+use super::proxy_cookies::CookieJar;
+use super::proxy_policy::{PolicyResolver, ProxyPolicy};
+
 const START_PORT: u16 = 5001;
 const MAX_PORT: u16 = 5100;
+const SHUTDOWN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// 转发给上游的请求头，其余请求头（Host、Origin、Referer 等）会暴露代理本身，不转发
+///
+/// 浏览器中的 Cookie 属于代理自身的来源，不转发，上游的 Cookie 由 [`CookieJar`] 保存
+const FORWARD_REQUEST_HEADERS: &[&str] = &[
+    "accept",
+    "accept-encoding",
+    "accept-language",
+    "authorization",
+    "cache-control",
+    "content-length",
+    "content-type",
+    "if-match",
+    "if-modified-since",
+    "if-none-match",
+    "if-range",
+    "pragma",
+    "range",
+    "user-agent",
+];
+
+/// 不转发给浏览器的逐跳响应头
+const HOP_BY_HOP_HEADERS: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-connection",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// 改写模式下不转发的响应头：内容已改变，且 CSP 会阻止页面加载代理后的资源
+const REWRITE_DROP_HEADERS: &[&str] = &[
+    "content-length",
+    "content-type",
+    "content-encoding",
+    "content-security-policy",
+    "etag",
+];
+
+/// 改写模式下读取到内存中的最大页面大小
+const MAX_REWRITE_SIZE: usize = 10 * 1024 * 1024;
+
+/// 代理地址
+fn proxy_url(target: &str, token: &str) -> String {
+    let target: String = url::form_urlencoded::byte_serialize(target.as_bytes()).collect();
+    format!("/proxy?url={}&token={}", target, token)
+}
+
+/// 将重定向地址改写为经过代理的地址，相对地址按当前请求的地址解析
+fn rewrite_location(location: &str, base: &Url, token: &str) -> String {
+    match base.join(location) {
+        Ok(target) if matches!(target.scheme(), "http" | "https") => proxy_url(target.as_str(), token),
+        _ => location.to_string(),
+    }
+}
+
+/// 移除 CSP 中的 frame-ancestors，其余指令保留
+fn strip_frame_ancestors(csp: &str) -> Option<String> {
+    let directives: Vec<&str> = csp
+        .split(';')
+        .map(str::trim)
+        .filter(|d| !d.is_empty() && !d.to_ascii_lowercase().starts_with("frame-ancestors"))
+        .collect();
+    (!directives.is_empty()).then(|| directives.join("; "))
+}
+
+/// 改写模式（`rewrite=1`），`scripts=0` 时同时移除页面脚本
+struct RewriteMode {
+    /// 代理自身的来源，改写后的地址需要是绝对地址，否则会被注入的 `<base>` 指回原站点
+    origin: String,
+    /// 写入页面的令牌，页面中的脚本可以读取，因此只能是 [`ProxyServer::page_token`]
+    token: String,
+    strip_scripts: bool,
+}
+
+impl RewriteMode {
+    fn from_request(params: &HashMap<String, String>, headers: &HeaderMap, page_token: &str) -> Option<Self> {
+        if params.get("rewrite").map(String::as_str) != Some("1") {
+            return None;
+        }
+        let host = headers.get("host").and_then(|v| v.to_str().ok()).unwrap_or("localhost");
+        Some(Self {
+            origin: format!("http://{}", host),
+            token: page_token.to_string(),
+            strip_scripts: params.get("scripts").map(String::as_str) == Some("0"),
+        })
+    }
+
+    fn rewriter(&self, base: &Url) -> Rewriter<'_> {
+        Rewriter { base: base.clone(), origin: &self.origin, token: &self.token, strip_scripts: self.strip_scripts }
+    }
+}
+
+/// 改写模式下读取并改写 HTML / CSS，超过大小限制时原样返回
+async fn rewrite_body(
+    response: reqwest::Response,
+    mode: &RewriteMode,
+    base: &Url,
+    content_type: &str,
+) -> Result<Body, Body> {
+    let mut stream = response.bytes_stream();
+    let mut buffer = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        let Ok(chunk) = chunk else { return Err(Body::from(buffer)) };
+        buffer.extend_from_slice(&chunk);
+        if buffer.len() > MAX_REWRITE_SIZE {
+            let head = futures_util::stream::once(async move { Ok::<_, reqwest::Error>(buffer.into()) });
+            return Err(Body::wrap_stream(head.chain(stream)));
+        }
+    }
+    let (text, _, _) = detect_charset(Some(content_type), &buffer).decode(&buffer);
+    let mut rewriter = mode.rewriter(base);
+    let output = if content_type.starts_with("text/css") {
+        rewriter.rewrite_css(&text)
+    } else {
+        rewriter.rewrite_html(&text)
+    };
+    Ok(Body::from(output))
+}
+
+/// 透传请求到上游，并流式返回上游响应
+#[allow(clippy::too_many_arguments)]
+async fn proxy_request<S, B>(
+    client: &Client,
+    cookies: &CookieJar,
+    target: &str,
+    token: &str,
+    rewrite: Option<RewriteMode>,
+    method: Method,
+    headers: HeaderMap,
+    body: S,
+) -> Response
+where
+    S: Stream<Item = Result<B, warp::Error>> + Send + Sync + 'static,
+    B: Buf + Send + 'static,
+{
+    let bad_gateway = || warp::reply::with_status("请求失败", warp::http::StatusCode::BAD_GATEWAY).into_response();
+    let Ok(base) = Url::parse(target) else {
+        return warp::reply::with_status("未知的 URL", warp::http::StatusCode::BAD_REQUEST).into_response();
+    };
+    let Ok(method) = reqwest::Method::from_bytes(method.as_str().as_bytes()) else {
+        return bad_gateway();
+    };
+
+    let mut request = client.request(method, base.clone());
+    for (name, value) in headers.iter().filter(|(name, _)| FORWARD_REQUEST_HEADERS.contains(&name.as_str())) {
+        // 改写模式需要未压缩的内容
+        if rewrite.is_none() || name != "accept-encoding" {
+            request = request.header(name.as_str(), value.as_bytes());
+        }
+    }
+    if let Some(cookie) = cookies.header(&base) {
+        request = request.header("cookie", cookie);
+    }
+    // 只有带请求体的请求才转发请求体，避免 GET 请求被改为分块传输
+    if headers.contains_key("content-length") || headers.contains_key("transfer-encoding") {
+        let body = body.map(|chunk| chunk.map(|mut buf| buf.copy_to_bytes(buf.remaining())));
+        request = request.body(reqwest::Body::wrap_stream(body));
+    }
+
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(_) => return bad_gateway(),
+    };
+
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let rewrite = rewrite.filter(|_| {
+        let encoded = response.headers().get("content-encoding").is_some_and(|v| v != "identity");
+        !encoded && (content_type.starts_with("text/html") || content_type.starts_with("text/css"))
+    });
+
+    let mut res = warp::http::Response::builder().status(response.status().as_u16());
+    // 客户端未启用自动解压，Content-Encoding 与 Content-Length 可以原样转发
+    for (name, value) in response.headers() {
+        if rewrite.is_some() && REWRITE_DROP_HEADERS.contains(&name.as_str()) {
+            continue;
+        }
+        let name = name.as_str();
+        let Ok(value) = value.to_str().map(str::to_string) else {
+            if !HOP_BY_HOP_HEADERS.contains(&name) {
+                res = res.header(name, value.as_bytes());
+            }
+            continue;
+        };
+        match name {
+            _ if HOP_BY_HOP_HEADERS.contains(&name) => {}
+            "x-frame-options" | "access-control-allow-origin" => {}
+            "content-security-policy" => {
+                if let Some(csp) = strip_frame_ancestors(&value) {
+                    res = res.header(name, csp);
+                }
+            }
+            "set-cookie" => cookies.store(&base, &value),
+            "location" => {
+                let location = match &rewrite {
+                    Some(mode) => mode.rewriter(&base).page_url(&value).unwrap_or(value),
+                    None => rewrite_location(&value, &base, token),
+                };
+                res = res.header(name, location)
+            }
+            _ => res = res.header(name, value),
+        }
+    }
+
+    let body = match &rewrite {
+        Some(mode) => match rewrite_body(response, mode, &base, &content_type).await {
+            Ok(body) => {
+                let kind = if content_type.starts_with("text/css") { "text/css" } else { "text/html" };
+                res = res.header("content-type", format!("{}; charset=utf-8", kind));
+                body
+            }
+            Err(body) => {
+                res = res.header("content-type", content_type);
+                body
+            }
+        },
+        None => Body::wrap_stream(response.bytes_stream()),
+    };
+    res.body(body).unwrap_or_else(|_| bad_gateway())
+}
 
 pub struct ProxyServer {
-    pub port: u16
+    /// 本次启动随机生成的访问令牌，只交给应用自身的 webview
+    pub token: String,
+    /// 改写后的页面中使用的令牌，只允许 GET 访问 `/proxy` 与 `/assets`
+    pub page_token: String,
+    /// 可访问的主机与 TLS 校验策略
+    pub policy: Arc<RwLock<ProxyPolicy>>,
+    /// `/assets` 路由使用的资源缓存
+    pub assets: Arc<AssetCache>,
+    /// 被代理站点的 Cookie，代理重启后保留
+    cookies: Arc<CookieJar>,
+    running: Mutex<Option<Running>>,
+}
+
+/// 正在运行的代理服务
+struct Running {
+    port: u16,
+    shutdown: oneshot::Sender<()>,
+    server: tokio::task::JoinHandle<()>,
+}
+
+/// 拒绝请求时的状态码与原因
+type Denied = (StatusCode, String);
+
+fn deny((status, message): Denied) -> Response {
+    warp::reply::with_status(message, status).into_response()
+}
+
+/// 按主机选择是否校验 TLS 证书的一组客户端
+#[derive(Clone)]
+struct Clients {
+    secure: Client,
+    insecure: Client,
+}
+
+impl Clients {
+    fn new(policy: &Arc<RwLock<ProxyPolicy>>, follow_redirects: bool) -> Self {
+        let build = |insecure: bool| {
+            let redirect = if follow_redirects {
+                // 跟随重定向时同样需要检查目标主机
+                let policy = policy.clone();
+                reqwest::redirect::Policy::custom(move |attempt| {
+                    let host = attempt.url().host_str().unwrap_or_default().to_string();
+                    if let Err(err) = policy.read().unwrap().check_host(&host) {
+                        attempt.error(err)
+                    } else if attempt.previous().len() >= 10 {
+                        attempt.stop()
+                    } else {
+                        attempt.follow()
+                    }
+                })
+            } else {
+                reqwest::redirect::Policy::none()
+            };
+            // 经过上游代理时目标主机由代理解析，无法检查实际访问的地址，因此始终直连
+            http_client::builder()
+                .no_proxy()
+                .danger_accept_invalid_certs(insecure)
+                .dns_resolver(Arc::new(PolicyResolver(policy.clone())))
+                .redirect(redirect)
+                .build()
+                .unwrap()
+        };
+        Self { secure: build(false), insecure: build(true) }
+    }
+
+    /// 检查目标地址是否允许访问，并返回对应的客户端
+    fn for_url(&self, policy: &RwLock<ProxyPolicy>, target: &str) -> Result<&Client, Denied> {
+        let url = Url::parse(target).map_err(|_| (StatusCode::BAD_REQUEST, "未知的 URL".to_string()))?;
+        let host = url.host_str().unwrap_or_default();
+        let policy = policy.read().unwrap();
+        policy.check_host(host).map_err(|err| (StatusCode::FORBIDDEN, err))?;
+        Ok(if policy.is_insecure(host) { &self.insecure } else { &self.secure })
+    }
+}
+
+/// 应用自身 webview 的来源
+fn is_trusted_origin(origin: &str) -> bool {
+    matches!(origin, "tauri://localhost" | "http://tauri.localhost" | "https://tauri.localhost")
+        || (cfg!(debug_assertions) && origin == "http://localhost:8080")
+}
+
+/// 代理的访问令牌
+#[derive(Clone)]
+struct Tokens {
+    full: String,
+    page: String,
+}
+
+impl Tokens {
+    /// 重定向地址中继续使用请求本身的令牌
+    fn for_request(&self, page: bool) -> &str {
+        if page { &self.page } else { &self.full }
+    }
+}
+
+/// 校验访问令牌与请求来源，通过时返回是否为页面令牌与允许跨域访问的来源
+fn authorize(
+    tokens: &Tokens,
+    method: &Method,
+    params: &HashMap<String, String>,
+    headers: &HeaderMap,
+) -> Result<(bool, Option<String>), Denied> {
+    let forbidden = |msg: &str| (StatusCode::FORBIDDEN, msg.to_string());
+    let provided = params
+        .get("token")
+        .map(String::as_str)
+        .or_else(|| headers.get("x-proxy-token").and_then(|v| v.to_str().ok()));
+    let page = match provided {
+        Some(token) if token == tokens.full => false,
+        // 页面令牌可能被页面中的脚本读取，只允许读取
+        Some(token) if token == tokens.page && matches!(*method, Method::GET | Method::HEAD) => true,
+        _ => return Err(forbidden("无效的访问令牌")),
+    };
+    let Some(origin) = headers.get("origin").and_then(|v| v.to_str().ok()) else {
+        return Ok((page, None));
+    };
+    // 被代理的页面与代理同源
+    let host = headers.get("host").and_then(|v| v.to_str().ok()).unwrap_or_default();
+    if is_trusted_origin(origin) || origin == format!("http://{}", host) {
+        Ok((page, Some(origin.to_string())))
+    } else {
+        Err(forbidden("不允许的请求来源"))
+    }
+}
+
+fn allow_origin(mut response: Response, origin: Option<String>) -> Response {
+    if let Some(value) = origin.and_then(|o| o.parse().ok()) {
+        response.headers_mut().insert("access-control-allow-origin", value);
+        response.headers_mut().append("vary", warp::http::HeaderValue::from_static("Origin"));
+    }
+    response
 }
 
 impl ProxyServer {
-    pub async fn new() -> Self {
-        let client = Client::builder()
-            .danger_accept_invalid_certs(true)
-            .build()
-            .unwrap();
-        let ptoxy_client = client.clone();
-        let assets_client = client.clone();
+    pub fn new(assets: Arc<AssetCache>) -> Self {
+        let random_token = || rand::thread_rng().sample_iter(&Alphanumeric).take(32).map(char::from).collect();
+        Self {
+            token: random_token(),
+            page_token: random_token(),
+            policy: Arc::new(RwLock::new(ProxyPolicy::default())),
+            assets,
+            cookies: Arc::new(CookieJar::default()),
+            running: Mutex::new(None),
+        }
+    }
+
+    /// 当前监听的端口，未运行时为 None
+    pub fn port(&self) -> Option<u16> {
+        self.running.lock().unwrap().as_ref().map(|r| r.port)
+    }
+
+    /// 启动代理服务，已在运行时直接返回当前端口，需要在 tokio 运行时中调用
+    ///
+    /// 依次尝试指定的端口、默认端口范围，都被占用时由系统分配端口。
+    pub fn start(&self, port: Option<u16>) -> Result<u16, String> {
+        let mut running = self.running.lock().unwrap();
+        if let Some(running) = running.as_ref() {
+            return Ok(running.port);
+        }
+
+        let tokens = Tokens { full: self.token.clone(), page: self.page_token.clone() };
+        let policy = self.policy.clone();
+        let assets = self.assets.clone();
+        let cookies = self.cookies.clone();
+        // 透传代理不跟随重定向，由浏览器通过改写后的 Location 继续访问
+        let proxy_clients = Clients::new(&policy, false);
+        let assets_clients = Clients::new(&policy, true);
 
         let proxy_filter = warp::path("proxy")
             .and(warp::query::<HashMap<String, String>>())
+            .and(warp::method())
+            .and(warp::header::headers_cloned())
+            .and(warp::body::stream())
             .and_then({
-                let client = ptoxy_client;
-                move |params: HashMap<String, String>| {
-                    let client = client.clone();
+                let clients = proxy_clients;
+                let policy = policy.clone();
+                let tokens = tokens.clone();
+                move |params: HashMap<String, String>, method: Method, headers: HeaderMap, body| {
+                    let clients = clients.clone();
+                    let policy = policy.clone();
+                    let tokens = tokens.clone();
+                    let cookies = cookies.clone();
                     async move {
+                        let (page, origin) = match authorize(&tokens, &method, &params, &headers) {
+                            Ok(authorized) => authorized,
+                            Err(denied) => return Ok::<_, Infallible>(deny(denied)),
+                        };
                         if let Some(target_url) = params.get("url") {
-                            match client.get(target_url).send().await {
-                                Ok(response) => {
-                                    let mut res = warp::http::Response::builder()
-                                        .status(warp::http::StatusCode::from_u16(response.status().as_u16()).unwrap());
-
-                                    let body = response.bytes().await.unwrap_or_default();
-
-                                    res = res
-                                        .header("Access-Control-Allow-Origin", "*")
-                                        .header("X-Frame-Options", "")
-                                        .header("Content-Type", "text/html; charset=utf-8");
-
-                                    Ok::<_, Infallible>(res.body(body).into_response())
+                            let token = tokens.for_request(page);
+                            let rewrite = RewriteMode::from_request(&params, &headers, &tokens.page);
+                            let response = match clients.for_url(&policy, target_url) {
+                                Ok(client) => {
+                                    proxy_request(client, &cookies, target_url, token, rewrite, method, headers, body).await
                                 }
-                                Err(_) => Ok::<_, Infallible>(
-                                    warp::reply::with_status("请求失败", warp::http::StatusCode::BAD_GATEWAY)
-                                        .into_response(),
-                                ),
-                            }
+                                Err(denied) => deny(denied),
+                            };
+                            Ok::<_, Infallible>(allow_origin(response, origin))
                         } else {
                             Ok::<_, Infallible>(
                                 warp::reply::with_status("未知的 URL", warp::http::StatusCode::BAD_REQUEST)
@@ -60,37 +463,29 @@ impl ProxyServer {
 
         let assets_filter = warp::path("assets")
             .and(warp::query::<HashMap<String, String>>())
+            .and(warp::method())
+            .and(warp::header::headers_cloned())
             .and_then({
-                let client = assets_client;
-                move |params: HashMap<String, String>| {
-                    let client = client.clone();
+                let clients = assets_clients;
+                let policy = policy.clone();
+                let tokens = tokens.clone();
+                let cache = assets.clone();
+                move |params: HashMap<String, String>, method: Method, headers: HeaderMap| {
+                    let clients = clients.clone();
+                    let policy = policy.clone();
+                    let tokens = tokens.clone();
+                    let cache = cache.clone();
                     async move {
+                        let origin = match authorize(&tokens, &method, &params, &headers) {
+                            Ok((_, origin)) => origin,
+                            Err(denied) => return Ok::<_, Infallible>(deny(denied)),
+                        };
                         if let Some(target_url) = params.get("url") {
-                            match client.get(target_url).send().await {
-                                Ok(response) => {
-                                    let content_type = response
-                                        .headers()
-                                        .get("Content-Type")
-                                        .and_then(|v| v.to_str().ok())
-                                        .unwrap_or("application/octet-stream")
-                                        .to_string();
-
-                                        let res = warp::http::Response::builder()
-                                        .status(warp::http::StatusCode::from_u16(response.status().as_u16()).unwrap());
-
-                                    let body = response.bytes().await.unwrap_or_default();
-
-                                    let res = res
-                                        .header("Content-Type", content_type)
-                                        .header("Access-Control-Allow-Origin", "*");
-
-                                    Ok::<_, Infallible>(res.body(body).into_response())
-                                }
-                                Err(_) => Ok::<_, Infallible>(
-                                    warp::reply::with_status("请求失败", warp::http::StatusCode::BAD_GATEWAY)
-                                        .into_response(),
-                                ),
-                            }
+                            let response = match clients.for_url(&policy, target_url) {
+                                Ok(client) => cache.serve(client, target_url).await,
+                                Err(denied) => deny(denied),
+                            };
+                            Ok::<_, Infallible>(allow_origin(response, origin))
                         } else {
                             Ok::<_, Infallible>(
                                 warp::reply::with_status(
@@ -104,24 +499,127 @@ impl ProxyServer {
                 }
             });
 
-        let mut port = START_PORT;
-        let routes = proxy_filter.or(assets_filter);
+        let health_filter = warp::path("health")
+            .and(warp::path::end())
+            .map(|| warp::reply::json(&serde_json::json!({ "status": "ok" })));
 
-        loop {
-            let addr: SocketAddr = ([127, 0, 0, 1], port).into();
+        let routes = health_filter.or(proxy_filter).or(assets_filter);
+        let candidates = port
+            .into_iter()
+            .chain(START_PORT..=MAX_PORT)
+            // 0 表示由系统分配端口
+            .chain(std::iter::once(0));
 
-            match warp::serve(routes.clone()).try_bind_ephemeral(addr) {
-                Ok((_, server)) => {
-                    tokio::spawn(server);
-                    return Self { port };
+        for candidate in candidates {
+            let addr: SocketAddr = ([127, 0, 0, 1], candidate).into();
+            let (shutdown, signal) = oneshot::channel::<()>();
+            let signal = async move {
+                let _ = signal.await;
+            };
+            if let Ok((addr, server)) = warp::serve(routes.clone()).try_bind_with_graceful_shutdown(addr, signal) {
+                let server = tokio::spawn(server);
+                if port.is_some_and(|p| p != addr.port()) {
+                    warn!("代理端口 {} 不可用，已改用 {}", port.unwrap_or_default(), addr.port());
                 }
-                Err(_) => {
-                    port += 1;
-                    if port > MAX_PORT {
-                        panic!("❌ 本地反代服务无法绑定 {}-{}", START_PORT, MAX_PORT);
-                    }
+                *running = Some(Running { port: addr.port(), shutdown, server });
+                return Ok(addr.port());
+            }
+        }
+        Err("本地代理服务无法绑定端口".to_string())
+    }
+
+    /// 停止代理服务并等待端口释放，返回停止前是否在运行
+    pub async fn stop(&self) -> bool {
+        let Some(running) = self.running.lock().unwrap().take() else {
+            return false;
+        };
+        let _ = running.shutdown.send(());
+        // 等待进行中的请求结束，长时间的流式响应不阻塞停止
+        let _ = tokio::time::timeout(SHUTDOWN_TIMEOUT, running.server).await;
+        true
+    }
+
+    pub async fn restart(&self, port: Option<u16>) -> Result<u16, String> {
+        self.stop().await;
+        self.start(port)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrites_response_headers() {
+        let base = Url::parse("https://example.com/a/b?x=1").unwrap();
+        assert_eq!(rewrite_location("../c", &base, "t"), "/proxy?url=https%3A%2F%2Fexample.com%2Fc&token=t");
+        assert_eq!(
+            rewrite_location("http://other.com/", &base, "t"),
+            "/proxy?url=http%3A%2F%2Fother.com%2F&token=t"
+        );
+        assert_eq!(rewrite_location("mailto:a@b.c", &base, "t"), "mailto:a@b.c");
+
+        assert_eq!(
+            strip_frame_ancestors("default-src 'self'; frame-ancestors 'none'"),
+            Some("default-src 'self'".to_string())
+        );
+        assert_eq!(strip_frame_ancestors("frame-ancestors 'self'"), None);
+    }
+
+    #[test]
+    fn page_token_is_read_only() {
+        let tokens = Tokens { full: "full".to_string(), page: "page".to_string() };
+        let params = |token: &str| HashMap::from([("token".to_string(), token.to_string())]);
+        let headers = HeaderMap::new();
+
+        assert_eq!(authorize(&tokens, &Method::POST, &params("full"), &headers), Ok((false, None)));
+        assert_eq!(authorize(&tokens, &Method::GET, &params("page"), &headers), Ok((true, None)));
+        assert!(authorize(&tokens, &Method::POST, &params("page"), &headers).is_err());
+        assert!(authorize(&tokens, &Method::GET, &params("other"), &headers).is_err());
+        assert_eq!(tokens.for_request(true), "page");
+    }
+
+    #[tokio::test]
+    async fn bypasses_upstream_proxy() {
+        use reqwest::dns::Resolve;
+
+        // 记录是否有请求经过上游代理
+        let upstream = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_addr = upstream.local_addr().unwrap();
+        let proxied = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        tokio::spawn({
+            let proxied = proxied.clone();
+            async move {
+                while upstream.accept().await.is_ok() {
+                    proxied.store(true, std::sync::atomic::Ordering::SeqCst);
                 }
             }
+        });
+        http_client::configure(http_client::HttpClientConfig {
+            proxy: Some(format!("http://localhost:{}", upstream_addr.port())),
+            ..Default::default()
+        })
+        .unwrap();
+
+        // 127.0.0.2 不在上游代理的直连列表中
+        let (target, server) = warp::serve(warp::any().map(|| "ok")).bind_ephemeral(([127, 0, 0, 2], 0));
+        tokio::spawn(server);
+        let proxy = ProxyServer::new(Arc::new(AssetCache::new(Default::default())));
+        *proxy.policy.write().unwrap() = ProxyPolicy::from_lists("127.0.0.2", "", "");
+        let port = proxy.start(None).unwrap();
+        let url = |path: &str| format!("http://127.0.0.1:{}/{}?url=http://{}/&token={}", port, path, target, proxy.token);
+        for path in ["proxy", "assets"] {
+            let response = reqwest::get(url(path)).await.unwrap();
+            assert_eq!(response.status(), reqwest::StatusCode::OK);
+            assert_eq!(response.text().await.unwrap(), "ok");
         }
+        assert!(!proxied.load(std::sync::atomic::Ordering::SeqCst));
+
+        // 与上游代理同名的主机同样按策略检查解析结果
+        let resolver = PolicyResolver(Arc::new(RwLock::new(ProxyPolicy::default())));
+        assert!(resolver.resolve("localhost".parse().unwrap()).await.is_err());
+
+        proxy.stop().await;
+        http_client::configure(Default::default()).unwrap();
     }
 }
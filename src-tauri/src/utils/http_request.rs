@@ -0,0 +1,424 @@
+//! 通用 HTTP 请求
+//!
+//! 供工作流中的 HTTP 请求节点使用，由后端发起请求，不受 CORS 限制，可以设置任意请求头，
+//! 也可以跳过自签名证书的校验。请求带有 requestId 时可以通过 [`HttpRequests::cancel`] 取消。
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use log::debug;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE, RETRY_AFTER};
+use reqwest::multipart;
+use reqwest::{Client, Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tokio::sync::oneshot;
+
+use super::http_client;
+
+/// 单次重试等待的上限
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum HttpBody {
+    Text {
+        content: String,
+        #[serde(default, rename = "contentType")]
+        content_type: Option<String>,
+    },
+    Json {
+        content: Value,
+    },
+    /// application/x-www-form-urlencoded
+    Form {
+        fields: Map<String, Value>,
+    },
+    Multipart {
+        parts: Vec<MultipartPart>,
+    },
+    /// base64 编码的二进制数据
+    Binary {
+        content: String,
+        #[serde(default, rename = "contentType")]
+        content_type: Option<String>,
+    },
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MultipartPart {
+    pub name: String,
+    /// 文本字段的值
+    #[serde(default)]
+    pub value: Option<String>,
+    /// 文件字段的 base64 内容
+    #[serde(default)]
+    pub base64: Option<String>,
+    #[serde(default)]
+    pub file_name: Option<String>,
+    #[serde(default)]
+    pub content_type: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RedirectMode {
+    /// 自动跟随重定向
+    #[default]
+    Follow,
+    /// 不跟随，直接返回 3xx 响应
+    Manual,
+    /// 遇到重定向时报错
+    Error,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ResponseType {
+    /// 按 Content-Type 判断：JSON 解析为对象，文本返回字符串，其他返回 base64
+    #[default]
+    Auto,
+    Text,
+    Json,
+    Base64,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+fn default_retry_delay() -> u64 {
+    1000
+}
+
+fn default_max_redirects() -> usize {
+    10
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpRequest {
+    /// 用于取消请求的 ID
+    #[serde(default)]
+    pub request_id: Option<String>,
+    #[serde(default = "default_method")]
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: Map<String, Value>,
+    /// 附加到 URL 上的查询参数，数组值会生成多个同名参数
+    #[serde(default)]
+    pub query: Map<String, Value>,
+    #[serde(default)]
+    pub body: Option<HttpBody>,
+    /// 单次请求的超时时间（毫秒），未设置时使用全局的连接与读取超时
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    /// 连接失败、超时、429 和 5xx 时的重试次数
+    #[serde(default)]
+    pub retries: u32,
+    /// 首次重试前的等待时间（毫秒），之后每次翻倍
+    #[serde(default = "default_retry_delay")]
+    pub retry_delay: u64,
+    #[serde(default)]
+    pub redirect: RedirectMode,
+    #[serde(default = "default_max_redirects")]
+    pub max_redirects: usize,
+    #[serde(default)]
+    pub response_type: ResponseType,
+    /// 跳过 TLS 证书校验，用于自签名证书的内网服务
+    #[serde(default)]
+    pub insecure: bool,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpTiming {
+    /// 收到最后一次请求的响应头所用时间（毫秒）
+    pub headers: u64,
+    /// 包括重试与读取响应体在内的总耗时（毫秒）
+    pub total: u64,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpResponse {
+    pub status: u16,
+    pub status_text: String,
+    /// 重定向后的最终地址
+    pub url: String,
+    /// 同名响应头以 ", " 合并，名称均为小写
+    pub headers: BTreeMap<String, String>,
+    pub body: Value,
+    pub attempts: u32,
+    pub timing: HttpTiming,
+}
+
+/// 把对象转为键值对，数组展开为多个同名项，非字符串的值转为 JSON 文本
+fn to_pairs(map: &Map<String, Value>) -> Vec<(String, String)> {
+    let text = |value: &Value| match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    };
+    let mut pairs = Vec::new();
+    for (key, value) in map {
+        match value {
+            Value::Array(items) => pairs.extend(items.iter().map(|item| (key.clone(), text(item)))),
+            value => pairs.push((key.clone(), text(value))),
+        }
+    }
+    pairs
+}
+
+fn decode_base64(content: &str) -> Result<Vec<u8>, String> {
+    BASE64.decode(content.trim()).map_err(|e| format!("无效的 base64 数据: {}", e))
+}
+
+impl HttpRequest {
+    fn client(&self) -> Result<Client, String> {
+        let max_redirects = self.max_redirects;
+        let redirect = match self.redirect {
+            RedirectMode::Follow => reqwest::redirect::Policy::limited(max_redirects),
+            RedirectMode::Manual => reqwest::redirect::Policy::none(),
+            RedirectMode::Error => reqwest::redirect::Policy::custom(|attempt| {
+                let url = attempt.url().to_string();
+                attempt.error(format!("请求被重定向到 {}", url))
+            }),
+        };
+        http_client::builder()
+            .redirect(redirect)
+            .danger_accept_invalid_certs(self.insecure)
+            .build()
+            .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))
+    }
+
+    fn header_map(&self) -> Result<HeaderMap, String> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in to_pairs(&self.headers) {
+            let name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| format!("无效的请求头名称: {}", name))?;
+            let value = HeaderValue::from_str(&value).map_err(|_| format!("请求头 {} 的值无效", name))?;
+            headers.append(name, value);
+        }
+        Ok(headers)
+    }
+
+    /// 构建一次请求，重试时需要重新构建请求体
+    fn build(&self, client: &Client, method: &Method, headers: &HeaderMap) -> Result<reqwest::RequestBuilder, String> {
+        let mut request = client.request(method.clone(), &self.url).headers(headers.clone());
+        if !self.query.is_empty() {
+            request = request.query(&to_pairs(&self.query));
+        }
+        if let Some(timeout) = self.timeout.filter(|t| *t > 0) {
+            request = request.timeout(Duration::from_millis(timeout));
+        }
+        // 用户设置的 Content-Type 优先
+        let content_type = |request: reqwest::RequestBuilder, value: &str| match headers.contains_key(CONTENT_TYPE) {
+            true => request,
+            false => request.header(CONTENT_TYPE, value),
+        };
+        Ok(match &self.body {
+            None => request,
+            Some(HttpBody::Text { content, content_type: ty }) => {
+                content_type(request, ty.as_deref().unwrap_or("text/plain; charset=utf-8")).body(content.clone())
+            }
+            Some(HttpBody::Json { content }) => {
+                content_type(request, "application/json").body(content.to_string())
+            }
+            Some(HttpBody::Form { fields }) => request.form(&to_pairs(fields)),
+            Some(HttpBody::Binary { content, content_type: ty }) => {
+                content_type(request, ty.as_deref().unwrap_or("application/octet-stream")).body(decode_base64(content)?)
+            }
+            Some(HttpBody::Multipart { parts }) => {
+                let mut form = multipart::Form::new();
+                for part in parts {
+                    let mut item = match (&part.base64, &part.value) {
+                        (Some(content), _) => multipart::Part::bytes(decode_base64(content)?),
+                        (None, Some(value)) => multipart::Part::text(value.clone()),
+                        (None, None) => multipart::Part::text(""),
+                    };
+                    if let Some(file_name) = &part.file_name {
+                        item = item.file_name(file_name.clone());
+                    }
+                    if let Some(ty) = &part.content_type {
+                        item = item.mime_str(ty).map_err(|_| format!("无效的 Content-Type: {}", ty))?;
+                    }
+                    form = form.part(part.name.clone(), item);
+                }
+                request.multipart(form)
+            }
+        })
+    }
+
+    /// 第 attempt 次重试前的等待时间，优先使用响应的 Retry-After（秒）
+    fn retry_delay(&self, attempt: u32, response: Option<&reqwest::Response>) -> Duration {
+        let retry_after = response
+            .and_then(|r| r.headers().get(RETRY_AFTER))
+            .and_then(|v| v.to_str().ok()?.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+        let backoff = Duration::from_millis(self.retry_delay.saturating_mul(1 << attempt.min(16)));
+        retry_after.unwrap_or(backoff).min(MAX_RETRY_DELAY)
+    }
+
+    async fn send(&self) -> Result<HttpResponse, String> {
+        let started = Instant::now();
+        let client = self.client()?;
+        let method = Method::from_bytes(self.method.trim().to_ascii_uppercase().as_bytes())
+            .map_err(|_| format!("无效的请求方法: {}", self.method))?;
+        let headers = self.header_map()?;
+
+        let mut attempt = 0;
+        let response = loop {
+            let result = self.build(&client, &method, &headers)?.send().await;
+            let retryable = match &result {
+                Ok(response) => {
+                    let status = response.status();
+                    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+                }
+                Err(err) => err.is_timeout() || err.is_connect(),
+            };
+            if !retryable || attempt >= self.retries {
+                break result.map_err(|e| format!("请求失败: {}", e))?;
+            }
+            let delay = self.retry_delay(attempt, result.as_ref().ok());
+            match &result {
+                Ok(response) => debug!("请求 {} 返回 {}，{:?} 后重试", self.url, response.status(), delay),
+                Err(err) => debug!("请求 {} 失败: {}，{:?} 后重试", self.url, err, delay),
+            }
+            attempt += 1;
+            tokio::time::sleep(delay).await;
+        };
+        let headers_elapsed = started.elapsed();
+
+        let status = response.status();
+        let url = response.url().to_string();
+        let mut headers: BTreeMap<String, String> = BTreeMap::new();
+        for (name, value) in response.headers() {
+            let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+            headers
+                .entry(name.as_str().to_string())
+                .and_modify(|v| {
+                    v.push_str(", ");
+                    v.push_str(&value);
+                })
+                .or_insert(value);
+        }
+        let content_type = headers.get("content-type").cloned().unwrap_or_default();
+        let bytes = response.bytes().await.map_err(|e| format!("读取响应失败: {}", e))?;
+        let body = parse_body(&bytes, &content_type, self.response_type)?;
+
+        Ok(HttpResponse {
+            status: status.as_u16(),
+            status_text: status.canonical_reason().unwrap_or_default().to_string(),
+            url,
+            headers,
+            body,
+            attempts: attempt + 1,
+            timing: HttpTiming {
+                headers: headers_elapsed.as_millis() as u64,
+                total: started.elapsed().as_millis() as u64,
+            },
+        })
+    }
+}
+
+fn parse_body(bytes: &[u8], content_type: &str, response_type: ResponseType) -> Result<Value, String> {
+    let content_type = content_type.to_ascii_lowercase();
+    let response_type = match response_type {
+        ResponseType::Auto if content_type.contains("json") => ResponseType::Json,
+        ResponseType::Auto
+            if content_type.starts_with("text/")
+                || content_type.contains("xml")
+                || content_type.contains("javascript")
+                || content_type.contains("x-www-form-urlencoded")
+                || (content_type.is_empty() && std::str::from_utf8(bytes).is_ok()) =>
+        {
+            ResponseType::Text
+        }
+        ResponseType::Auto => ResponseType::Base64,
+        other => other,
+    };
+    Ok(match response_type {
+        ResponseType::Json if bytes.is_empty() => Value::Null,
+        ResponseType::Json => serde_json::from_slice(bytes).map_err(|e| format!("响应不是有效的 JSON: {}", e))?,
+        ResponseType::Base64 => Value::String(BASE64.encode(bytes)),
+        _ => Value::String(String::from_utf8_lossy(bytes).into_owned()),
+    })
+}
+
+/// 进行中的可取消请求
+#[derive(Default)]
+pub struct HttpRequests {
+    pending: Mutex<HashMap<String, oneshot::Sender<()>>>,
+}
+
+impl HttpRequests {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 发送请求，带有 requestId 时可在完成前取消
+    pub async fn send(&self, request: HttpRequest) -> Result<HttpResponse, String> {
+        let Some(id) = request.request_id.clone() else {
+            return request.send().await;
+        };
+        let (cancel, cancelled) = oneshot::channel();
+        if self.pending.lock().unwrap().insert(id.clone(), cancel).is_some() {
+            debug!("请求 ID {} 重复，之前的请求将无法取消", id);
+        }
+        let result = tokio::select! {
+            result = request.send() => result,
+            _ = cancelled => Err(format!("请求已取消: {}", id)),
+        };
+        let mut pending = self.pending.lock().unwrap();
+        // 只移除自己的记录，同 ID 的新请求可能已经替换了它
+        if pending.get(&id).is_some_and(|sender| sender.is_closed()) {
+            pending.remove(&id);
+        }
+        result
+    }
+
+    /// 取消请求，返回请求是否仍在进行
+    pub fn cancel(&self, id: &str) -> bool {
+        match self.pending.lock().unwrap().remove(id) {
+            Some(sender) => sender.send(()).is_ok(),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_request_and_body() {
+        let request: HttpRequest = serde_json::from_value(serde_json::json!({
+            "url": "https://example.com/api",
+            "query": { "tag": ["a", "b"], "page": 2 },
+            "body": { "type": "json", "content": { "ok": true } },
+            "redirect": "manual",
+            "responseType": "base64"
+        }))
+        .unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.redirect, RedirectMode::Manual);
+        assert_eq!(request.response_type, ResponseType::Base64);
+        let mut query = to_pairs(&request.query);
+        query.sort();
+        assert_eq!(
+            query,
+            vec![("page".into(), "2".into()), ("tag".into(), "a".into()), ("tag".into(), "b".into())]
+        );
+        assert_eq!(request.retry_delay(2, None), Duration::from_millis(4000));
+
+        assert_eq!(parse_body(b"{\"a\":1}", "application/json; charset=utf-8", ResponseType::Auto).unwrap()["a"], 1);
+        assert_eq!(parse_body(b"hi", "text/plain", ResponseType::Auto).unwrap(), "hi");
+        assert_eq!(parse_body(&[0xff, 0x00], "image/png", ResponseType::Auto).unwrap(), "/wA=");
+        assert!(parse_body(b"oops", "text/plain", ResponseType::Json).is_err());
+    }
+}
@@ -1,2 +1,12 @@
+pub mod asset_cache;
 pub mod colored_encoder;
+pub mod download;
+pub mod html_rewrite;
+pub mod http_client;
 pub mod http_proxy;
+pub mod http_request;
+pub mod notification;
+pub mod process;
+pub mod proxy_cookies;
+pub mod proxy_policy;
+pub mod redirect;
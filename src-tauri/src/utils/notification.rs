@@ -0,0 +1,153 @@
+//! 系统通知的响应处理
+//!
+//! 通知被点击、关闭或回复时通过 [`EventSink`] 推送 `sys:notificationResponse`，
+//! 输入了回复内容时额外推送 `sys:notificationReplied`，其数据与消息触发数据结构一致，
+//! 可以直接作为 `notification_replied` 触发器的触发数据。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use log::info;
+use serde_json::{json, Value};
+use user_notify::{NotificationCategory, NotificationCategoryAction, NotificationManager, NotificationResponse, NotificationResponseAction};
+
+use crate::connectors::EventSink;
+
+/// 消息通知的分类，带有回复输入框
+pub const REPLY_CATEGORY: &str = "cn.stapxs.qqweb.reply";
+pub const REPLY_ACTION: &str = "reply";
+/// user_info 中的会话信息，格式为 `<会话 ID>/<会话类型>`
+pub const PAYLOAD_KEY: &str = "NotificationPayload";
+/// user_info 中发出此通知的 Bot ID
+pub const BOT_KEY: &str = "BotId";
+
+pub fn categories() -> Vec<NotificationCategory> {
+    vec![NotificationCategory {
+        identifier: REPLY_CATEGORY.to_string(),
+        actions: vec![NotificationCategoryAction::TextInputAction {
+            identifier: REPLY_ACTION.to_string(),
+            title: "回复".to_string(),
+            input_button_title: "发送".to_string(),
+            input_placeholder: "输入回复内容".to_string(),
+        }],
+    }]
+}
+
+/// 消息通知的 user_info，回复时据此找到原会话与发出通知的 Bot
+pub fn reply_user_info(target_id: &str, message_type: &str, bot: Option<&str>) -> HashMap<String, String> {
+    let mut user_info = HashMap::from([(PAYLOAD_KEY.to_owned(), format!("{}/{}", target_id, message_type))]);
+    if let Some(bot) = bot {
+        user_info.insert(BOT_KEY.to_owned(), bot.to_owned());
+    }
+    user_info
+}
+
+fn action_name(action: &NotificationResponseAction) -> &str {
+    match action {
+        NotificationResponseAction::Default => "default",
+        NotificationResponseAction::Dismiss => "dismiss",
+        NotificationResponseAction::Other(id) => id,
+    }
+}
+
+/// 通知响应对应的事件
+pub fn response_events(response: &NotificationResponse) -> Vec<(&'static str, Value)> {
+    events(
+        &response.notification_id,
+        action_name(&response.action),
+        response.user_text.as_deref(),
+        &response.user_info,
+    )
+}
+
+fn events(id: &str, action: &str, user_text: Option<&str>, user_info: &HashMap<String, String>) -> Vec<(&'static str, Value)> {
+    let (target_id, message_type) = user_info
+        .get(PAYLOAD_KEY)
+        .and_then(|payload| payload.rsplit_once('/'))
+        .map(|(id, kind)| (id.to_string(), kind.to_string()))
+        .unwrap_or_default();
+    let bot_id = user_info.get(BOT_KEY).cloned();
+    let mut events = vec![(
+        "sys:notificationResponse",
+        json!({
+            "notificationId": id,
+            "action": action,
+            "userText": user_text,
+            "userInfo": user_info,
+            "targetId": target_id,
+            "messageType": message_type,
+            "botId": bot_id,
+        }),
+    )];
+
+    let text = user_text.map(str::trim).unwrap_or_default();
+    if !text.is_empty() && !target_id.is_empty() {
+        let id_key = if message_type == "group" { "groupId" } else { "userId" };
+        events.push((
+            "sys:notificationReplied",
+            json!({
+                "notificationId": id,
+                "botId": bot_id,
+                "messageType": message_type,
+                "targetId": target_id,
+                id_key: target_id,
+                "message": text,
+                "rawMessage": text,
+                "isMine": false,
+                "time": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            }),
+        ));
+    }
+    events
+}
+
+/// 注册通知分类与响应处理
+pub fn register(manager: &Arc<dyn NotificationManager>, sink: Arc<dyn EventSink>) -> Result<(), String> {
+    manager
+        .register(
+            Box::new(move |response| {
+                info!("收到通知响应：{} {}", response.notification_id, action_name(&response.action));
+                for (event, payload) in response_events(&response) {
+                    sink.send(event, payload);
+                }
+            }),
+            categories(),
+        )
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reply_becomes_trigger_data() {
+        // 与 sys_send_notice 写入的 user_info 一致
+        let user_info = reply_user_info("12345", "group", Some("bot-1"));
+        let replied = events("n1", REPLY_ACTION, Some(" 好的 "), &user_info);
+        assert_eq!(replied.len(), 2);
+        assert_eq!(replied[0].0, "sys:notificationResponse");
+        assert_eq!(replied[0].1["action"], "reply");
+        let (event, data) = &replied[1];
+        assert_eq!(*event, "sys:notificationReplied");
+        assert_eq!(data["groupId"], "12345");
+        assert_eq!(data["messageType"], "group");
+        assert_eq!(data["message"], "好的");
+        assert_eq!(data["botId"], "bot-1");
+        assert!(chrono::DateTime::parse_from_rfc3339(data["time"].as_str().unwrap()).is_ok());
+
+        let clicked = events("n1", "default", None, &user_info);
+        assert_eq!(clicked.len(), 1);
+        assert_eq!(clicked[0].1["targetId"], "12345");
+    }
+
+    #[test]
+    fn private_reply_targets_user() {
+        let user_info = reply_user_info("10001", "private", Some("bot-1"));
+        let replied = events("n2", REPLY_ACTION, Some("收到"), &user_info);
+        let (_, data) = &replied[1];
+        assert_eq!(data["userId"], "10001");
+        assert!(data.get("groupId").is_none());
+        assert_eq!(data["botId"], "bot-1");
+    }
+}
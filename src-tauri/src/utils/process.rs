@@ -0,0 +1,385 @@
+//! 外部程序执行
+//!
+//! 只允许运行用户在设置中加入白名单的程序。白名单中的完整路径只匹配该文件，
+//! 只写程序名时匹配通过 PATH 找到的同名程序。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
+
+use crate::connectors::EventSink;
+
+/// 结束进程后等待输出读取完成的最长时间，避免后台子进程占用管道时一直等待
+const OUTPUT_GRACE: Duration = Duration::from_secs(2);
+
+/// 会让程序加载或执行其他代码的环境变量，即使程序在白名单中也不允许设置
+const BLOCKED_ENV: &[&str] = &[
+    "PATH",
+    "PATHEXT",
+    "BASH_ENV",
+    "ENV",
+    "SHELLOPTS",
+    "BASHOPTS",
+    "PS4",
+    "IFS",
+    "ZDOTDIR",
+    "EDITOR",
+    "VISUAL",
+    "PAGER",
+    "SSH_ASKPASS",
+    "JAVA_TOOL_OPTIONS",
+    "_JAVA_OPTIONS",
+    "JDK_JAVA_OPTIONS",
+    "PHPRC",
+    "PHP_INI_SCAN_DIR",
+    "COMSPEC",
+];
+
+/// 同上，按前缀匹配：动态链接器、git、各语言解释器的启动钩子
+const BLOCKED_ENV_PREFIXES: &[&str] = &[
+    "LD_",
+    "DYLD_",
+    "GIT_",
+    "BASH_FUNC_",
+    "PYTHON",
+    "NODE_",
+    "NPM_CONFIG_",
+    "PERL",
+    "RUBY",
+    "LUA_",
+    "DOTNET_",
+    "COR_",
+    "CORECLR_",
+];
+
+fn is_blocked_env(key: &str) -> bool {
+    // Windows 的环境变量名不区分大小写
+    let key = key.trim().to_ascii_uppercase();
+    key.is_empty()
+        || key.contains('=')
+        || BLOCKED_ENV.contains(&key.as_str())
+        || BLOCKED_ENV_PREFIXES.iter().any(|prefix| key.starts_with(prefix))
+}
+
+/// 检查请求中追加的环境变量
+fn check_env(env: &HashMap<String, String>) -> Result<(), String> {
+    let mut blocked: Vec<&str> = env.keys().map(String::as_str).filter(|key| is_blocked_env(key)).collect();
+    if blocked.is_empty() {
+        return Ok(());
+    }
+    blocked.sort_unstable();
+    Err(format!("不允许设置以下环境变量: {}", blocked.join(", ")))
+}
+
+fn default_timeout() -> u64 {
+    60_000
+}
+
+fn default_max_output() -> usize {
+    1024 * 1024
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessRequest {
+    /// 程序名或完整路径
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// 追加的环境变量，不能包含 [`BLOCKED_ENV`] 等会让程序执行其他代码的变量
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    /// 写入标准输入的内容
+    #[serde(default)]
+    pub stdin: Option<String>,
+    /// 超时时间（毫秒），超时后结束进程
+    #[serde(default = "default_timeout")]
+    pub timeout: u64,
+    /// stdout 与 stderr 各自保留的最大字节数，超出部分丢弃
+    #[serde(default = "default_max_output")]
+    pub max_output: usize,
+    /// 设置后按行推送 sys:processOutput 事件
+    #[serde(default)]
+    pub run_id: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessOutput {
+    /// 被信号结束或超时时为空
+    pub code: Option<i32>,
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+    pub timed_out: bool,
+    /// 是否有输出因超出大小限制被丢弃
+    pub truncated: bool,
+    /// 从启动到进程结束的耗时（毫秒）
+    pub duration: u64,
+}
+
+/// 从设置值读取白名单，支持 JSON 数组或按行分隔的字符串
+pub fn parse_allowlist(value: &Value) -> Vec<String> {
+    let entries: Vec<String> = match value {
+        Value::Array(items) => items.iter().filter_map(|v| v.as_str()).map(str::to_string).collect(),
+        Value::String(s) => match serde_json::from_str::<Vec<String>>(s) {
+            Ok(items) => items,
+            Err(_) => s.lines().map(str::to_string).collect(),
+        },
+        _ => Vec::new(),
+    };
+    entries.into_iter().map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect()
+}
+
+fn has_separator(program: &str) -> bool {
+    program.contains('/') || program.contains('\\')
+}
+
+/// 程序名（不含 Windows 上的可执行扩展名），用于和白名单中的名称比较
+fn program_name(path: &Path) -> String {
+    let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+    if cfg!(windows) {
+        let lower = name.to_ascii_lowercase();
+        for ext in [".exe", ".cmd", ".bat", ".com"] {
+            if let Some(stem) = lower.strip_suffix(ext) {
+                return stem.to_string();
+            }
+        }
+        lower
+    } else {
+        name
+    }
+}
+
+/// 在 PATH 中查找程序
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    let extensions: Vec<String> = if cfg!(windows) {
+        let pathext = std::env::var("PATHEXT").unwrap_or_else(|_| ".EXE;.CMD;.BAT;.COM".to_string());
+        std::iter::once(String::new()).chain(pathext.split(';').map(str::to_string)).collect()
+    } else {
+        vec![String::new()]
+    };
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path).find_map(|dir| {
+        extensions
+            .iter()
+            .map(|ext| dir.join(format!("{}{}", program, ext)))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+pub struct ProcessRunner {
+    sink: Arc<dyn EventSink>,
+    allowlist: RwLock<Vec<String>>,
+}
+
+impl ProcessRunner {
+    pub fn new(sink: Arc<dyn EventSink>, allowlist: Vec<String>) -> Self {
+        Self { sink, allowlist: RwLock::new(allowlist) }
+    }
+
+    pub fn allowlist(&self) -> Vec<String> {
+        self.allowlist.read().unwrap().clone()
+    }
+
+    pub fn set_allowlist(&self, allowlist: Vec<String>) {
+        *self.allowlist.write().unwrap() = allowlist;
+    }
+
+    /// 查找程序并检查白名单，返回实际运行的文件
+    pub fn resolve(&self, program: &str) -> Result<PathBuf, String> {
+        let program = program.trim();
+        let path = if has_separator(program) {
+            let path = PathBuf::from(program);
+            if !path.is_absolute() {
+                return Err(format!("程序路径必须是绝对路径: {}", program));
+            }
+            path
+        } else {
+            find_in_path(program).ok_or_else(|| format!("找不到程序: {}", program))?
+        };
+        let resolved = path.canonicalize().map_err(|e| format!("找不到程序 {}: {}", program, e))?;
+
+        let allowed = self.allowlist.read().unwrap().iter().any(|entry| {
+            if has_separator(entry) {
+                Path::new(entry).canonicalize().is_ok_and(|entry| entry == resolved)
+            } else {
+                // 只写程序名的条目不匹配以路径指定的程序
+                !has_separator(program) && program_name(Path::new(entry)) == program_name(Path::new(program))
+            }
+        });
+        if !allowed {
+            return Err(format!("程序 {} 不在允许运行的白名单中，请先在设置中添加", program));
+        }
+        Ok(resolved)
+    }
+
+    pub async fn run(&self, request: ProcessRequest) -> Result<ProcessOutput, String> {
+        let program = self.resolve(&request.program)?;
+        check_env(&request.env)?;
+        info!("运行程序：{} {:?}", program.display(), request.args);
+
+        let mut command = tokio::process::Command::new(&program);
+        command
+            .args(&request.args)
+            .envs(&request.env)
+            .stdin(if request.stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(cwd) = &request.cwd {
+            command.current_dir(cwd);
+        }
+        #[cfg(windows)]
+        {
+            // CREATE_NO_WINDOW，不弹出控制台窗口
+            command.creation_flags(0x0800_0000);
+        }
+
+        let started = Instant::now();
+        let mut child = command.spawn().map_err(|e| format!("启动程序失败: {}", e))?;
+        if let (Some(input), Some(mut stdin)) = (request.stdin.clone(), child.stdin.take()) {
+            // 写完后关闭标准输入，程序读取到 EOF
+            tokio::spawn(async move {
+                let _ = stdin.write_all(input.as_bytes()).await;
+            });
+        }
+
+        let sink = request.run_id.clone().map(|id| (id, self.sink.clone()));
+        let stdout = Arc::new(Mutex::new(Captured::default()));
+        let stderr = Arc::new(Mutex::new(Captured::default()));
+        let readers = [
+            child.stdout.take().map(|out| spawn_reader(out, "stdout", stdout.clone(), request.max_output, sink.clone())),
+            child.stderr.take().map(|err| spawn_reader(err, "stderr", stderr.clone(), request.max_output, sink.clone())),
+        ];
+
+        let timeout = Duration::from_millis(request.timeout.max(1));
+        let (status, timed_out) = match tokio::time::timeout(timeout, child.wait()).await {
+            Ok(status) => (Some(status.map_err(|e| format!("等待程序结束失败: {}", e))?), false),
+            Err(_) => {
+                info!("程序运行超时，已结束：{}", program.display());
+                let _ = child.kill().await;
+                (None, true)
+            }
+        };
+        let duration = started.elapsed();
+        let deadline = tokio::time::Instant::now() + OUTPUT_GRACE;
+        for reader in readers.into_iter().flatten() {
+            let abort = reader.abort_handle();
+            if tokio::time::timeout_at(deadline, reader).await.is_err() {
+                abort.abort();
+            }
+        }
+
+        let stdout = std::mem::take(&mut *stdout.lock().unwrap());
+        let stderr = std::mem::take(&mut *stderr.lock().unwrap());
+        Ok(ProcessOutput {
+            code: status.and_then(|s| s.code()),
+            success: status.is_some_and(|s| s.success()),
+            stdout: String::from_utf8_lossy(&stdout.data).into_owned(),
+            stderr: String::from_utf8_lossy(&stderr.data).into_owned(),
+            timed_out,
+            truncated: stdout.truncated || stderr.truncated,
+            duration: duration.as_millis() as u64,
+        })
+    }
+}
+
+#[derive(Default)]
+struct Captured {
+    data: Vec<u8>,
+    truncated: bool,
+}
+
+/// 按行读取输出，保留不超过 limit 字节，设置了 runId 时逐行推送事件
+fn spawn_reader<R>(
+    reader: R,
+    stream: &'static str,
+    captured: Arc<Mutex<Captured>>,
+    limit: usize,
+    sink: Option<(String, Arc<dyn EventSink>)>,
+) -> tokio::task::JoinHandle<()>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut reader = BufReader::new(reader);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match reader.read_until(b'\n', &mut line).await {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            if let Some((id, sink)) = &sink {
+                let text = String::from_utf8_lossy(&line);
+                sink.send(
+                    "sys:processOutput",
+                    json!({ "runId": id, "stream": stream, "line": text.trim_end_matches(['\r', '\n']) }),
+                );
+            }
+            let mut captured = captured.lock().unwrap();
+            let room = limit.saturating_sub(captured.data.len());
+            if line.len() > room {
+                captured.truncated = true;
+            }
+            captured.data.extend_from_slice(&line[..line.len().min(room)]);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct NoopSink;
+
+    impl EventSink for NoopSink {
+        fn send(&self, _event: &str, _payload: Value) {}
+    }
+
+    #[test]
+    fn rejects_hook_env() {
+        let env = |keys: &[&str]| keys.iter().map(|k| (k.to_string(), "x".to_string())).collect::<HashMap<_, _>>();
+        assert!(check_env(&env(&["LANG", "MY_TOKEN", "HTTP_PROXY"])).is_ok());
+        assert_eq!(
+            check_env(&env(&["ld_preload", "GIT_SSH_COMMAND", "LANG"])),
+            Err("不允许设置以下环境变量: GIT_SSH_COMMAND, ld_preload".to_string())
+        );
+        for key in ["DYLD_INSERT_LIBRARIES", "Path", "NODE_OPTIONS", "PYTHONSTARTUP", "BASH_FUNC_ls%%", "A=B", ""] {
+            assert!(is_blocked_env(key), "{}", key);
+        }
+    }
+
+    #[test]
+    fn checks_allowlist() {
+        assert_eq!(parse_allowlist(&json!("git\n  \n/usr/bin/env ")), vec!["git", "/usr/bin/env"]);
+        assert_eq!(parse_allowlist(&json!("[\"git\"]")), vec!["git"]);
+        assert_eq!(parse_allowlist(&json!(["a", 1, " b "])), vec!["a", "b"]);
+
+        let runner = ProcessRunner::new(Arc::new(NoopSink), Vec::new());
+        let Some(found) = find_in_path(if cfg!(windows) { "cmd" } else { "sh" }) else {
+            return;
+        };
+        let name = program_name(&found);
+        assert!(runner.resolve(&name).is_err());
+
+        runner.set_allowlist(vec![name.clone()]);
+        assert!(runner.resolve(&name).is_ok());
+        // 只写程序名的条目不允许以路径运行
+        assert!(runner.resolve(&found.to_string_lossy()).is_err());
+        assert!(runner.resolve("./sh").is_err());
+
+        runner.set_allowlist(vec![found.to_string_lossy().into_owned()]);
+        assert!(runner.resolve(&found.to_string_lossy()).is_ok());
+    }
+}
@@ -0,0 +1,178 @@
+//! 本地代理的 Cookie 存储
+//!
+//! 所有被代理的站点都与代理同源，Cookie 若交给浏览器保存会在站点之间共享，也能被其他页面的脚本读取。
+//! 因此上游的 Set-Cookie 只保存在代理中，按上游站点的域名、路径与 Secure 规则在请求时附加，
+//! 浏览器自身的 Cookie 不会转发给上游。
+
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, Utc};
+use url::Url;
+
+/// 保存的 Cookie 数量上限，超出时丢弃最早保存的
+const MAX_COOKIES: usize = 3000;
+
+#[derive(Debug, Clone)]
+struct StoredCookie {
+    name: String,
+    value: String,
+    /// 小写的域名，不含开头的点
+    domain: String,
+    /// 没有 Domain 属性时只发送给设置它的主机
+    host_only: bool,
+    path: String,
+    secure: bool,
+    /// 为空时为会话 Cookie，代理运行期间有效
+    expires: Option<DateTime<Utc>>,
+}
+
+impl StoredCookie {
+    fn expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    fn matches(&self, url: &Url, now: DateTime<Utc>) -> bool {
+        let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+        let domain_match = if self.host_only {
+            host == self.domain
+        } else {
+            domain_matches(&host, &self.domain)
+        };
+        domain_match
+            && path_matches(url.path(), &self.path)
+            && (!self.secure || url.scheme() == "https")
+            && !self.expired(now)
+    }
+}
+
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+fn path_matches(path: &str, cookie_path: &str) -> bool {
+    path == cookie_path
+        || (path.starts_with(cookie_path) && (cookie_path.ends_with('/') || path[cookie_path.len()..].starts_with('/')))
+}
+
+/// 没有 Path 属性时的默认路径：请求路径所在的目录
+fn default_path(url: &Url) -> String {
+    match url.path().rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(end) => url.path()[..end].to_string(),
+    }
+}
+
+fn parse_expires(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value)
+        .or_else(|_| DateTime::parse_from_str(value, "%a, %d-%b-%Y %H:%M:%S GMT"))
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+#[derive(Default)]
+pub struct CookieJar {
+    cookies: Mutex<Vec<StoredCookie>>,
+}
+
+impl CookieJar {
+    /// 保存上游响应中的一条 Set-Cookie
+    pub fn store(&self, url: &Url, set_cookie: &str) {
+        let Some(host) = url.host_str().map(str::to_ascii_lowercase) else {
+            return;
+        };
+        let mut parts = set_cookie.split(';');
+        let Some((name, value)) = parts.next().and_then(|pair| pair.split_once('=')) else {
+            return;
+        };
+        let now = Utc::now();
+        let mut cookie = StoredCookie {
+            name: name.trim().to_string(),
+            value: value.trim().to_string(),
+            domain: host.clone(),
+            host_only: true,
+            path: default_path(url),
+            secure: false,
+            expires: None,
+        };
+        let mut max_age = None;
+        for attr in parts {
+            let (key, value) = attr.split_once('=').unwrap_or((attr, ""));
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "domain" => {
+                    let domain = value.trim_start_matches('.').to_ascii_lowercase();
+                    if domain.is_empty() {
+                        continue;
+                    }
+                    // 只能设置为当前主机或其上级域名，且不能是顶级域名
+                    if !domain.contains('.') || !domain_matches(&host, &domain) {
+                        return;
+                    }
+                    cookie.domain = domain;
+                    cookie.host_only = false;
+                }
+                "path" if value.starts_with('/') => cookie.path = value.to_string(),
+                "secure" => cookie.secure = true,
+                "max-age" => max_age = value.parse::<i64>().ok(),
+                "expires" => cookie.expires = cookie.expires.or(parse_expires(value)),
+                _ => {}
+            }
+        }
+        // Max-Age 优先于 Expires
+        if let Some(seconds) = max_age {
+            cookie.expires = Some(now + Duration::seconds(seconds.clamp(-1, 400 * 24 * 3600)));
+        }
+        if cookie.name.is_empty() || (cookie.secure && url.scheme() != "https") {
+            return;
+        }
+
+        let mut cookies = self.cookies.lock().unwrap();
+        let same = |c: &StoredCookie| c.name == cookie.name && c.domain == cookie.domain && c.path == cookie.path;
+        cookies.retain(|c| !same(c) && !c.expired(now));
+        // 已过期的 Cookie 用于删除同名 Cookie，不再保存
+        if !cookie.expired(now) {
+            cookies.push(cookie);
+        }
+        if cookies.len() > MAX_COOKIES {
+            let excess = cookies.len() - MAX_COOKIES;
+            cookies.drain(..excess);
+        }
+    }
+
+    /// 请求上游时附加的 Cookie 请求头
+    pub fn header(&self, url: &Url) -> Option<String> {
+        let now = Utc::now();
+        let cookies = self.cookies.lock().unwrap();
+        let mut matched: Vec<&StoredCookie> = cookies.iter().filter(|c| c.matches(url, now)).collect();
+        // 路径更具体的 Cookie 在前
+        matched.sort_by_key(|c| std::cmp::Reverse(c.path.len()));
+        let header = matched.iter().map(|c| format!("{}={}", c.name, c.value)).collect::<Vec<_>>().join("; ");
+        (!header.is_empty()).then_some(header)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_cookies_to_upstream_site() {
+        let jar = CookieJar::default();
+        let url = |u: &str| Url::parse(u).unwrap();
+        jar.store(&url("https://a.example.com/login"), "sid=1; Path=/; HttpOnly");
+        jar.store(&url("https://a.example.com/login"), "shared=2; Domain=.example.com; Secure");
+        jar.store(&url("https://a.example.com/app/x"), "local=3");
+        jar.store(&url("https://a.example.com/"), "evil=4; Domain=com");
+        jar.store(&url("https://a.example.com/"), "other=5; Domain=b.com");
+
+        assert_eq!(jar.header(&url("https://a.example.com/")).as_deref(), Some("sid=1; shared=2"));
+        assert_eq!(jar.header(&url("https://a.example.com/app/y")).as_deref(), Some("local=3; sid=1; shared=2"));
+        assert_eq!(jar.header(&url("https://b.example.com/")).as_deref(), Some("shared=2"));
+        assert_eq!(jar.header(&url("http://b.example.com/")), None);
+        assert_eq!(jar.header(&url("https://b.com/")), None);
+
+        // Max-Age=0 删除 Cookie
+        jar.store(&url("https://a.example.com/"), "sid=; Path=/; Max-Age=0");
+        assert_eq!(jar.header(&url("https://a.example.com/")).as_deref(), Some("shared=2"));
+    }
+}
@@ -0,0 +1,199 @@
+//! 本地代理的访问策略
+//!
+//! 默认禁止访问本机与内网地址，`allow` 中的主机可以绕过此限制，`deny` 中的主机始终禁止访问。
+//! 规则支持完整主机名、`*.example.com` 通配和 `192.168.0.0/16` 形式的网段。
+//! TLS 证书默认校验，只有 `insecure` 中的主机跳过校验。
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, RwLock};
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+
+#[derive(Default, Clone, Debug)]
+pub struct ProxyPolicy {
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+    pub insecure: Vec<String>,
+}
+
+/// 本机、内网、链路本地等不应被网页通过代理访问的地址
+pub fn is_private(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                // 0.0.0.0/8 本网络
+                || ip.octets()[0] == 0
+                // 100.64.0.0/10 运营商级 NAT
+                || (ip.octets()[0] == 100 && (ip.octets()[1] & 0xc0) == 64)
+        }
+        IpAddr::V6(ip) => match embedded_ipv4(ip) {
+            Some(v4) => is_private(IpAddr::V4(v4)),
+            None => {
+                let first = ip.segments()[0];
+                ip.is_unspecified()
+                    || ip.is_multicast()
+                    || (first & 0xfe00) == 0xfc00
+                    || (first & 0xffc0) == 0xfe80
+                    // 64:ff9b:1::/48 本地 NAT64
+                    || ip.segments()[..3] == [0x64, 0xff9b, 1]
+            }
+        },
+    }
+}
+
+/// 内嵌 IPv4 地址的 IPv6 地址：IPv4 映射、IPv4 兼容（包括 ::1）、NAT64（64:ff9b::/96）与 6to4（2002::/16）
+fn embedded_ipv4(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    let segments = ip.segments();
+    let [.., a, b, c, d] = ip.octets();
+    match segments {
+        [0x2002, high, low, ..] => Some(Ipv4Addr::from((u32::from(high) << 16) | u32::from(low))),
+        [0x64, 0xff9b, 0, 0, 0, 0, _, _] => Some(Ipv4Addr::new(a, b, c, d)),
+        _ if !ip.is_unspecified() => ip.to_ipv4(),
+        _ => None,
+    }
+}
+
+fn in_network(ip: IpAddr, network: &str) -> bool {
+    let Some((addr, prefix)) = network.split_once('/') else {
+        return addr_eq(ip, network);
+    };
+    let (Ok(addr), Ok(prefix)) = (addr.parse::<IpAddr>(), prefix.parse::<u32>()) else {
+        return false;
+    };
+    match (ip, addr) {
+        (IpAddr::V4(ip), IpAddr::V4(addr)) if prefix <= 32 => {
+            let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(addr) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(addr)) if prefix <= 128 => {
+            let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(addr) & mask
+        }
+        _ => false,
+    }
+}
+
+fn addr_eq(ip: IpAddr, pattern: &str) -> bool {
+    pattern.trim_matches(|c| c == '[' || c == ']').parse::<IpAddr>() == Ok(ip)
+}
+
+/// 主机名或地址是否匹配某条规则
+fn matches(pattern: &str, host: &str, ip: Option<IpAddr>) -> bool {
+    let pattern = pattern.trim().to_ascii_lowercase();
+    let host = host.trim_matches(|c| c == '[' || c == ']').to_ascii_lowercase();
+    if let Some(suffix) = pattern.strip_prefix("*.") {
+        return host.ends_with(&format!(".{}", suffix));
+    }
+    if pattern == host {
+        return true;
+    }
+    ip.is_some_and(|ip| in_network(ip, &pattern))
+}
+
+impl ProxyPolicy {
+    /// 从以逗号、空白或换行分隔的规则列表创建策略
+    pub fn from_lists(allow: &str, deny: &str, insecure: &str) -> Self {
+        let split = |list: &str| {
+            list.split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        Self { allow: split(allow), deny: split(deny), insecure: split(insecure) }
+    }
+
+    fn listed(list: &[String], host: &str, ip: Option<IpAddr>) -> bool {
+        list.iter().any(|pattern| matches(pattern, host, ip))
+    }
+
+    /// 检查主机是否允许访问，主机名对应的地址在 DNS 解析时由 [`PolicyResolver`] 再次检查
+    pub fn check_host(&self, host: &str) -> Result<(), String> {
+        let ip = host.trim_matches(|c| c == '[' || c == ']').parse::<IpAddr>().ok();
+        if Self::listed(&self.deny, host, ip) {
+            return Err(format!("主机 {} 已被禁止访问", host));
+        }
+        let private = ip.map_or(host.eq_ignore_ascii_case("localhost"), is_private);
+        if private && !Self::listed(&self.allow, host, ip) {
+            return Err(format!("不允许通过代理访问内网地址 {}", host));
+        }
+        Ok(())
+    }
+
+    /// 检查主机名解析出的地址是否允许访问
+    pub fn check_addr(&self, host: &str, ip: IpAddr) -> bool {
+        if Self::listed(&self.deny, host, Some(ip)) {
+            return false;
+        }
+        !is_private(ip) || Self::listed(&self.allow, host, Some(ip))
+    }
+
+    /// 是否跳过此主机的 TLS 证书校验
+    pub fn is_insecure(&self, host: &str) -> bool {
+        Self::listed(&self.insecure, host, None)
+    }
+}
+
+/// 按策略过滤解析结果的 DNS 解析器，避免主机名指向内网地址（包括 DNS 重绑定）
+///
+/// 只在直连时生效，经过上游代理的请求由代理解析目标主机，使用此解析器的客户端不能设置上游代理。
+pub struct PolicyResolver(pub Arc<RwLock<ProxyPolicy>>);
+
+impl Resolve for PolicyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let policy = self.0.clone();
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            let policy = policy.read().unwrap();
+            let allowed: Vec<SocketAddr> = addrs.into_iter().filter(|addr| policy.check_addr(&host, addr.ip())).collect();
+            if allowed.is_empty() {
+                return Err(format!("主机 {} 解析到的地址不允许访问", host).into());
+            }
+            Ok(Box::new(allowed.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_private_hosts_by_default() {
+        let policy = ProxyPolicy::from_lists("nas.local, 192.168.1.0/24", "*.blocked.com", "self-signed.example.com");
+        assert!(policy.check_host("example.com").is_ok());
+        assert!(policy.check_host("127.0.0.1").is_err());
+        assert!(policy.check_host("localhost").is_err());
+        assert!(policy.check_host("[::1]").is_err());
+        assert!(policy.check_host("10.0.0.1").is_err());
+        assert!(policy.check_host("192.168.1.20").is_ok());
+        assert!(policy.check_host("a.blocked.com").is_err());
+
+        assert!(!policy.check_addr("evil.com", "10.1.2.3".parse().unwrap()));
+        assert!(!policy.check_addr("evil.com", "::ffff:127.0.0.1".parse().unwrap()));
+        assert!(policy.check_addr("nas.local", "10.1.2.3".parse().unwrap()));
+        assert!(policy.check_addr("example.com", "93.184.216.34".parse().unwrap()));
+        for private in [
+            "0.1.2.3",
+            "224.0.0.1",
+            "239.255.255.250",
+            "ff02::1",
+            "::127.0.0.1",
+            "64:ff9b::7f00:1",
+            "64:ff9b::a00:1",
+            "64:ff9b:1::1",
+            "2002:c0a8:101::1",
+        ] {
+            assert!(!policy.check_addr("evil.com", private.parse().unwrap()), "{}", private);
+        }
+        assert!(policy.check_addr("example.com", "64:ff9b::5db8:d822".parse().unwrap()));
+        assert!(policy.check_addr("example.com", "2606:2800:220:1::1".parse().unwrap()));
+
+        assert!(policy.is_insecure("self-signed.example.com"));
+        assert!(!policy.is_insecure("example.com"));
+    }
+}
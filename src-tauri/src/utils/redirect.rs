@@ -0,0 +1,265 @@
+//! 重定向解析
+//!
+//! 逐跳请求并记录完整的重定向链，用于短链接展开等场景。
+//! 除 HTTP 3xx 外，可选地跟随 HTML 中的 `<meta http-equiv="refresh">` 与 canonical 链接。
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use reqwest::header::{CONTENT_TYPE, LOCATION};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::html_rewrite::find_tags;
+use super::http_client;
+
+/// 查找 meta refresh 与 canonical 时最多读取的页面大小
+const MAX_HTML_SIZE: usize = 256 * 1024;
+
+fn default_max_hops() -> usize {
+    20
+}
+
+fn default_timeout() -> u64 {
+    10_000
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectOptions {
+    pub url: String,
+    /// 最多跟随的跳数
+    #[serde(default = "default_max_hops")]
+    pub max_hops: usize,
+    /// 整个解析过程的超时时间（毫秒）
+    #[serde(default = "default_timeout")]
+    pub timeout: u64,
+    #[serde(default)]
+    pub follow_meta_refresh: bool,
+    #[serde(default)]
+    pub follow_canonical: bool,
+}
+
+impl RedirectOptions {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            max_hops: default_max_hops(),
+            timeout: default_timeout(),
+            follow_meta_refresh: false,
+            follow_canonical: false,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum HopKind {
+    /// HTTP 3xx 与 Location
+    Http,
+    MetaRefresh,
+    Canonical,
+}
+
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectHop {
+    pub url: String,
+    pub status: u16,
+    /// 跳转到的下一个地址，最后一跳为空
+    pub location: Option<String>,
+    pub kind: Option<HopKind>,
+    /// 从 https 跳转到了 http
+    pub downgrade: bool,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RedirectChain {
+    pub url: String,
+    /// 最后到达的地址，出现循环或达到跳数上限时为最后一次请求的地址
+    pub final_url: String,
+    pub hops: Vec<RedirectHop>,
+    pub loop_detected: bool,
+    pub downgraded: bool,
+    pub max_hops_reached: bool,
+    /// 中途请求失败的原因，之前的跳转仍会返回
+    pub error: Option<String>,
+}
+
+/// 比较地址时忽略片段
+fn normalize(url: &Url) -> String {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    url.to_string()
+}
+
+/// 解析 meta refresh 的 content，如 `0; url=https://example.com`
+fn parse_refresh(content: &str) -> Option<&str> {
+    let (_, rest) = content.split_once([';', ','])?;
+    let rest = rest.trim_start();
+    let url = rest.get(..3).filter(|p| p.eq_ignore_ascii_case("url")).map_or(rest, |_| rest[3..].trim_start());
+    let url = url.strip_prefix('=').map_or(url, str::trim_start);
+    let url = url.trim().trim_matches(|c| c == '"' || c == '\'');
+    (!url.is_empty()).then_some(url)
+}
+
+/// 从页面中查找下一跳，meta refresh 优先于 canonical
+fn next_from_html(html: &str, base: &Url, options: &RedirectOptions) -> Option<(Url, HopKind)> {
+    let attr = |attrs: &[(String, String)], name: &str| attrs.iter().find(|(n, _)| n == name).map(|(_, v)| v.clone());
+    let base = find_tags(html, "base")
+        .iter()
+        .find_map(|attrs| base.join(&attr(attrs, "href")?).ok())
+        .unwrap_or_else(|| base.clone());
+
+    if options.follow_meta_refresh {
+        let refresh = find_tags(html, "meta").into_iter().find_map(|attrs| {
+            let equiv = attr(&attrs, "http-equiv")?;
+            if !equiv.eq_ignore_ascii_case("refresh") {
+                return None;
+            }
+            base.join(parse_refresh(&attr(&attrs, "content")?)?).ok()
+        });
+        if let Some(url) = refresh {
+            return Some((url, HopKind::MetaRefresh));
+        }
+    }
+    if options.follow_canonical {
+        let canonical = find_tags(html, "link").into_iter().find_map(|attrs| {
+            let rel = attr(&attrs, "rel")?;
+            if !rel.split_whitespace().any(|r| r.eq_ignore_ascii_case("canonical")) {
+                return None;
+            }
+            base.join(&attr(&attrs, "href")?).ok()
+        });
+        // canonical 指向自身时不算跳转
+        if let Some(url) = canonical.filter(|url| normalize(url) != normalize(&base)) {
+            return Some((url, HopKind::Canonical));
+        }
+    }
+    None
+}
+
+/// 逐跳解析重定向，请求失败时返回已解析的部分并在 error 中说明原因
+pub async fn resolve(options: &RedirectOptions) -> Result<RedirectChain, String> {
+    let start = Url::parse(options.url.trim()).map_err(|e| format!("无效的 URL {}: {}", options.url, e))?;
+    let mut chain = RedirectChain { url: start.to_string(), final_url: start.to_string(), ..Default::default() };
+    let timeout = Duration::from_millis(options.timeout.max(1));
+    if tokio::time::timeout(timeout, follow(&mut chain, start, options)).await.is_err() {
+        chain.error = Some(format!("解析超时（{} 毫秒）", options.timeout));
+    }
+    Ok(chain)
+}
+
+async fn follow(chain: &mut RedirectChain, start: Url, options: &RedirectOptions) {
+    let client = match http_client::builder().redirect(reqwest::redirect::Policy::none()).build() {
+        Ok(client) => client,
+        Err(err) => {
+            chain.error = Some(format!("创建 HTTP 客户端失败: {}", err));
+            return;
+        }
+    };
+    let mut visited = HashSet::new();
+    let mut current = start;
+    loop {
+        chain.final_url = current.to_string();
+        visited.insert(normalize(&current));
+        let response = match client.get(current.clone()).send().await {
+            Ok(response) => response,
+            Err(err) => {
+                chain.error = Some(format!("请求 {} 失败: {}", current, err));
+                return;
+            }
+        };
+        let status = response.status();
+
+        let location = response.headers().get(LOCATION).and_then(|v| v.to_str().ok());
+        let next = match location.filter(|_| status.is_redirection()) {
+            Some(location) => current.join(location).ok().map(|url| (url, HopKind::Http)),
+            None if status.is_success() && (options.follow_meta_refresh || options.follow_canonical) => {
+                let is_html = response
+                    .headers()
+                    .get(CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .is_some_and(|ct| ct.to_ascii_lowercase().contains("html"));
+                if is_html {
+                    let html = read_head(response).await;
+                    next_from_html(&html, &current, options)
+                } else {
+                    None
+                }
+            }
+            None => None,
+        };
+        // 只跟随 http(s) 地址
+        let next = next.filter(|(url, _)| matches!(url.scheme(), "http" | "https"));
+
+        let downgrade = next.as_ref().is_some_and(|(url, _)| current.scheme() == "https" && url.scheme() == "http");
+        chain.downgraded |= downgrade;
+        chain.hops.push(RedirectHop {
+            url: current.to_string(),
+            status: status.as_u16(),
+            location: next.as_ref().map(|(url, _)| url.to_string()),
+            kind: next.as_ref().map(|(_, kind)| *kind),
+            downgrade,
+        });
+
+        let Some((next, _)) = next else {
+            return;
+        };
+        if visited.contains(&normalize(&next)) {
+            chain.loop_detected = true;
+            return;
+        }
+        if chain.hops.len() > options.max_hops {
+            chain.max_hops_reached = true;
+            return;
+        }
+        current = next;
+    }
+}
+
+/// 读取页面开头的部分，meta 与 link 标签通常在 head 中
+async fn read_head(response: reqwest::Response) -> String {
+    let mut body = Vec::new();
+    let mut stream = response.bytes_stream();
+    while let Some(Ok(chunk)) = stream.next().await {
+        body.extend_from_slice(&chunk);
+        if body.len() >= MAX_HTML_SIZE || body.windows(7).any(|w| w.eq_ignore_ascii_case(b"</head>")) {
+            break;
+        }
+    }
+    String::from_utf8_lossy(&body).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_next_hop_in_html() {
+        assert_eq!(parse_refresh("0; URL='/next'"), Some("/next"));
+        assert_eq!(parse_refresh("5;url=https://a.com/?x=1"), Some("https://a.com/?x=1"));
+        assert_eq!(parse_refresh("5"), None);
+
+        let base = Url::parse("https://short.ly/abc").unwrap();
+        let mut options = RedirectOptions::new(base.as_str());
+        let html = r#"<head><META HTTP-EQUIV="Refresh" content="0;url=/landing"><link rel="canonical" href="https://site.com/page"></head>"#;
+        assert_eq!(next_from_html(html, &base, &options), None);
+
+        options.follow_canonical = true;
+        assert_eq!(
+            next_from_html(html, &base, &options),
+            Some((Url::parse("https://site.com/page").unwrap(), HopKind::Canonical))
+        );
+        options.follow_meta_refresh = true;
+        assert_eq!(
+            next_from_html(html, &base, &options),
+            Some((Url::parse("https://short.ly/landing").unwrap(), HopKind::MetaRefresh))
+        );
+
+        let own = r#"<link rel="canonical" href="/abc#top">"#;
+        assert_eq!(next_from_html(own, &base, &options), None);
+    }
+}
@@ -0,0 +1,540 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures_util::future::{try_join_all, BoxFuture};
+use log::{error, info};
+use serde::Serialize;
+use serde_json::Value;
+use tokio::task::{AbortHandle, JoinHandle};
+
+use crate::connectors::BotConnector;
+
+use super::nodes::{NodeContext, NodeExecutionResult, NodeLogger, NodeManager};
+use super::types::{ExecutionNode, WorkflowExecution};
+use super::utils::{is_truthy, js_string, to_number};
+
+/// 全局状态（节点间共享数据）
+pub type GlobalState = Arc<Mutex<HashMap<String, Value>>>;
+
+/// 执行日志
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionLog {
+    pub timestamp: i64,
+    pub node_id: String,
+    /// log / error / warn
+    pub level: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+/// 执行结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowExecutionResult {
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub logs: Vec<ExecutionLog>,
+    pub final_state: HashMap<String, Value>,
+}
+
+/// 执行状态回调
+pub trait ExecutionCallback: Send + Sync {
+    /// 节点开始执行
+    fn on_node_start(&self, _node_id: &str, _node_type: &str) {}
+    /// 节点执行完成
+    fn on_node_complete(&self, _node_id: &str, _result: &NodeExecutionResult) {}
+    /// 节点执行失败
+    fn on_node_error(&self, _node_id: &str, _error: &str) {}
+    /// 工作流执行完成
+    fn on_workflow_complete(&self, _result: &WorkflowExecutionResult) {}
+}
+
+/// 执行选项
+#[derive(Clone, Default)]
+pub struct ExecutionOptions {
+    /// 每个节点的最小执行延迟（毫秒），用于可视化
+    pub min_delay: u64,
+    /// 执行超时时间（毫秒），0 表示不限制
+    pub timeout: u64,
+    pub callback: Option<Arc<dyn ExecutionCallback>>,
+    /// 初始全局变量（会被复制到全局状态）
+    pub initial_globals: HashMap<String, Value>,
+    /// 触发工作流的 Bot，供发送消息类节点使用
+    pub bot: Option<Arc<dyn BotConnector>>,
+}
+
+/// 工作流执行引擎
+pub struct WorkflowEngine {
+    node_manager: Arc<NodeManager>,
+}
+
+#[derive(Default)]
+struct MergeState {
+    inputs: Vec<Value>,
+    expected: u32,
+    executed: bool,
+    timer: Option<AbortHandle>,
+}
+
+/// 合并节点的超时计时器，被丢弃时一并取消
+struct MergeTimer(JoinHandle<Result<(), String>>);
+
+impl Drop for MergeTimer {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// 单次执行的上下文
+struct ExecutionRun {
+    workflow: WorkflowExecution,
+    global_state: GlobalState,
+    logs: Arc<Mutex<Vec<ExecutionLog>>>,
+    options: ExecutionOptions,
+    node_manager: Arc<NodeManager>,
+    pending_merge: Mutex<HashMap<String, MergeState>>,
+    /// 合并节点的超时计时器，超时后的执行也属于本次执行
+    merge_timers: Mutex<Vec<MergeTimer>>,
+}
+
+impl WorkflowEngine {
+    pub fn new() -> Self {
+        Self::with_node_manager(Arc::new(NodeManager::new()))
+    }
+
+    pub fn with_node_manager(node_manager: Arc<NodeManager>) -> Self {
+        Self { node_manager }
+    }
+
+    pub fn node_manager(&self) -> &NodeManager {
+        &self.node_manager
+    }
+
+    /// 获取工作流中后端无法执行的节点类型
+    pub fn unsupported_node_types(&self, workflow: &WorkflowExecution) -> Vec<String> {
+        let mut types: Vec<String> = workflow
+            .nodes
+            .values()
+            .filter(|node| !self.node_manager.has_node(&node.node_type))
+            .map(|node| node.node_type.clone())
+            .collect();
+        types.sort();
+        types.dedup();
+        types
+    }
+
+    /// 执行工作流
+    pub async fn execute(
+        &self,
+        workflow: WorkflowExecution,
+        trigger_data: Value,
+        options: ExecutionOptions,
+    ) -> WorkflowExecutionResult {
+        info!("开始执行工作流: {} ({})", workflow.name, workflow.id);
+
+        let mut globals = options.initial_globals.clone();
+        // 如果执行时包含触发数据，把触发数据写入全局存储，键名为 'trigger'
+        if !trigger_data.is_null() {
+            globals.insert("trigger".to_string(), trigger_data.clone());
+        }
+
+        let timeout = options.timeout;
+        let run = Arc::new(ExecutionRun {
+            workflow,
+            global_state: Arc::new(Mutex::new(globals)),
+            logs: Arc::new(Mutex::new(Vec::new())),
+            options,
+            node_manager: self.node_manager.clone(),
+            pending_merge: Mutex::new(HashMap::new()),
+            merge_timers: Mutex::new(Vec::new()),
+        });
+
+        let execution = {
+            let run = run.clone();
+            async move {
+                run.clone().execute_internal(trigger_data).await?;
+                run.wait_merge_timers().await
+            }
+        };
+        let outcome = if timeout > 0 {
+            match tokio::time::timeout(Duration::from_millis(timeout), execution).await {
+                Ok(outcome) => outcome,
+                Err(_) => Err(format!("执行超时 ({}ms)", timeout)),
+            }
+        } else {
+            execution.await
+        };
+
+        // 失败或超时时不再执行还在等待的合并节点
+        run.abort_merge_timers();
+
+        match &outcome {
+            Ok(_) => info!("工作流执行完成: {}", run.workflow.name),
+            Err(err) => error!("工作流执行失败 > {}", err),
+        }
+
+        let result = WorkflowExecutionResult {
+            success: outcome.is_ok(),
+            error: outcome.err(),
+            logs: run.logs.lock().unwrap().clone(),
+            final_state: run.global_state.lock().unwrap().clone(),
+        };
+        if let Some(callback) = &run.options.callback {
+            callback.on_workflow_complete(&result);
+        }
+        result
+    }
+}
+
+impl Default for WorkflowEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExecutionRun {
+    async fn execute_internal(self: Arc<Self>, trigger_data: Value) -> Result<(), String> {
+        let entry = self
+            .workflow
+            .entry_node
+            .clone()
+            .ok_or_else(|| "工作流没有入口节点".to_string())?;
+        self.execute_node(entry, trigger_data).await
+    }
+
+    fn node_context(&self, node: &ExecutionNode) -> NodeContext {
+        NodeContext {
+            node_id: node.id.clone(),
+            node_type: node.node_type.clone(),
+            global_state: self.global_state.clone(),
+            logger: NodeLogger::new(node.id.clone(), self.logs.clone()),
+            bot: self.options.bot.clone(),
+        }
+    }
+
+    fn callback(&self) -> Option<&Arc<dyn ExecutionCallback>> {
+        self.options.callback.as_ref()
+    }
+
+    /// 执行单个节点
+    fn execute_node(self: Arc<Self>, node_id: String, input: Value) -> BoxFuture<'static, Result<(), String>> {
+        Box::pin(async move {
+            let node = self
+                .workflow
+                .nodes
+                .get(&node_id)
+                .cloned()
+                .ok_or_else(|| format!("节点不存在: {}", node_id))?;
+            let start = Instant::now();
+
+            info!("执行节点: {} ({})", node_id, node.node_type);
+            if let Some(callback) = self.callback() {
+                callback.on_node_start(&node_id, &node.node_type);
+            }
+
+            let context = self.node_context(&node);
+            let result = if is_merge_all(&node) {
+                match self.collect_merge_input(&node, input, context.clone(), start) {
+                    Some(inputs) => {
+                        self.node_manager
+                            .execute_node(&node.node_type, Value::Array(inputs), &node.params, &context)
+                            .await
+                    }
+                    None => return Ok(()),
+                }
+            } else {
+                self.node_manager
+                    .execute_node(&node.node_type, input, &node.params, &context)
+                    .await
+            };
+
+            if !result.success {
+                let err = format!("节点执行失败 > {}", result.error.clone().unwrap_or_default());
+                if let Some(callback) = self.callback() {
+                    callback.on_node_error(&node_id, &err);
+                }
+                return Err(err);
+            }
+
+            self.after_node_success(node, result, start).await
+        })
+    }
+
+    /// 收集合并节点（ALL 模式）的输入，输入收齐时返回全部输入
+    fn collect_merge_input(
+        self: &Arc<Self>,
+        node: &ExecutionNode,
+        input: Value,
+        context: NodeContext,
+        start: Instant,
+    ) -> Option<Vec<Value>> {
+        let expected = node.expected_inputs.unwrap_or(0);
+        let mut pending = self.pending_merge.lock().unwrap();
+        let state = pending.entry(node.id.clone()).or_default();
+        state.inputs.push(input);
+        state.expected = expected;
+
+        if !state.executed && expected > 0 && state.inputs.len() >= expected as usize {
+            state.executed = true;
+            if let Some(timer) = state.timer.take() {
+                timer.abort();
+            }
+            return Some(state.inputs.clone());
+        }
+
+        let timeout = node.params.get("timeout").map(to_number).unwrap_or(0.0);
+        if timeout > 0.0 && state.timer.is_none() {
+            let run = self.clone();
+            let node = node.clone();
+            let timer = tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(timeout as u64)).await;
+                run.on_merge_timeout(node, context, start).await
+            });
+            state.timer = Some(timer.abort_handle());
+            self.merge_timers.lock().unwrap().push(MergeTimer(timer));
+        }
+        None
+    }
+
+    /// 等待所有合并节点的计时器结束，计时期间新建的计时器也会等待
+    async fn wait_merge_timers(&self) -> Result<(), String> {
+        loop {
+            let Some(mut timer) = self.merge_timers.lock().unwrap().pop() else {
+                return Ok(());
+            };
+            match (&mut timer.0).await {
+                Ok(result) => result?,
+                // 输入在超时前收齐，计时器已取消
+                Err(err) if err.is_cancelled() => {}
+                Err(err) => return Err(format!("合并节点执行异常 > {}", err)),
+            }
+        }
+    }
+
+    fn abort_merge_timers(&self) {
+        self.merge_timers.lock().unwrap().clear();
+    }
+
+    async fn on_merge_timeout(self: Arc<Self>, node: ExecutionNode, context: NodeContext, start: Instant) -> Result<(), String> {
+        let (inputs, expected) = {
+            let mut pending = self.pending_merge.lock().unwrap();
+            let Some(state) = pending.get_mut(&node.id) else { return Ok(()) };
+            if state.executed {
+                return Ok(());
+            }
+            state.executed = true;
+            state.timer = None;
+            (state.inputs.clone(), state.expected)
+        };
+
+        let behavior = node
+            .params
+            .get("timeoutBehavior")
+            .map(js_string)
+            .unwrap_or_else(|| "execute".to_string());
+        let result = if behavior == "throw" {
+            Err(format!("合并节点等待超时: 已收到 {}/{}", inputs.len(), expected))
+        } else {
+            let result = self
+                .node_manager
+                .execute_node(&node.node_type, Value::Array(inputs), &node.params, &context)
+                .await;
+            if result.success {
+                Ok(result)
+            } else {
+                Err(format!("节点执行失败 > {}", result.error.unwrap_or_default()))
+            }
+        };
+
+        match result {
+            Ok(result) => self.after_node_success(node, result, start).await,
+            Err(err) => {
+                if let Some(callback) = self.callback() {
+                    callback.on_node_error(&node.id, &err);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    async fn after_node_success(
+        self: Arc<Self>,
+        node: ExecutionNode,
+        result: NodeExecutionResult,
+        start: Instant,
+    ) -> Result<(), String> {
+        let min_delay = Duration::from_millis(self.options.min_delay);
+        let elapsed = start.elapsed();
+        if min_delay > elapsed {
+            tokio::time::sleep(min_delay - elapsed).await;
+        }
+        info!("节点执行成功: {}", node.id);
+        if let Some(callback) = self.callback() {
+            callback.on_node_complete(&node.id, &result);
+        }
+        self.execute_next_nodes(&node, result.output).await
+    }
+
+    /// 执行下一个节点
+    async fn execute_next_nodes(self: Arc<Self>, node: &ExecutionNode, output: Value) -> Result<(), String> {
+        if node.branches.is_some() {
+            return self.execute_branch(node, output).await;
+        }
+        // 普通节点：并行执行所有下一个节点，任一失败时向上抛出
+        self.execute_parallel(&node.next, output).await
+    }
+
+    async fn execute_parallel(self: &Arc<Self>, next: &[String], output: Value) -> Result<(), String> {
+        let executions = next
+            .iter()
+            .map(|next_id| self.clone().execute_node(next_id.clone(), output.clone()));
+        try_join_all(executions).await.map(|_| ())
+    }
+
+    /// 执行条件分支
+    async fn execute_branch(self: Arc<Self>, node: &ExecutionNode, output: Value) -> Result<(), String> {
+        if node.node_type == "ifelse" {
+            // ifelse 节点：根据输出的布尔值选择分支
+            let condition = output.get("_branch").map(is_truthy).unwrap_or(false);
+            let branch_key = if condition { "true" } else { "false" };
+            match node.branch(branch_key).or_else(|| node.branch("default")) {
+                Some(next_id) => self.clone().execute_node(next_id.to_string(), output).await,
+                None => {
+                    info!("分支 {} 没有连接节点，流程结束", branch_key);
+                    Ok(())
+                }
+            }
+        } else {
+            // 其他条件节点：使用 output 作为分支键
+            let branch_key = match output.get("_branchKey") {
+                Some(key) => js_string(key),
+                None => js_string(&output),
+            };
+            match node.branch(&branch_key).or_else(|| node.branch("default")) {
+                Some(next_id) => self.clone().execute_node(next_id.to_string(), output).await,
+                None if !node.next.is_empty() => self.execute_parallel(&node.next, output).await,
+                None => {
+                    info!("没有匹配的分支: {}，流程结束", branch_key);
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+fn is_merge_all(node: &ExecutionNode) -> bool {
+    node.node_type == "merge"
+        && node
+            .params
+            .get("mode")
+            .map(js_string)
+            .unwrap_or_else(|| "ANY".to_string())
+            .to_uppercase()
+            == "ALL"
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn workflow(entry: &str, nodes: Value) -> WorkflowExecution {
+        serde_json::from_value(json!({
+            "id": "wf-test",
+            "name": "test",
+            "description": "",
+            "trigger": { "type": "manual", "typeLabel": "手动", "name": "start", "label": "开始" },
+            "entryNode": entry,
+            "nodes": nodes,
+            "createdAt": 0,
+            "updatedAt": 0
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn ifelse_falls_back_to_default_branch() {
+        let wf = workflow("if1", json!({
+            "if1": {
+                "id": "if1", "type": "ifelse", "next": [],
+                "params": { "condition": { "parameter": "input.value", "mode": "exists", "value": "" } },
+                "branches": { "true": "nTrue", "default": "nDefault" }
+            },
+            "nTrue": { "id": "nTrue", "type": "console-log", "params": { "message": "Branch TRUE" }, "next": [] },
+            "nDefault": { "id": "nDefault", "type": "console-log", "params": { "message": "Branch DEFAULT" }, "next": [] }
+        }));
+
+        let result = WorkflowEngine::new().execute(wf, json!({}), ExecutionOptions::default()).await;
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.logs.iter().any(|l| l.node_id == "nDefault"));
+        assert!(!result.logs.iter().any(|l| l.node_id == "nTrue"));
+    }
+
+    #[tokio::test]
+    async fn merge_all_waits_for_every_input() {
+        let wf = workflow("start", json!({
+            "start": { "id": "start", "type": "note", "params": {}, "next": ["a", "b"] },
+            "a": { "id": "a", "type": "console-log", "params": { "message": "a" }, "next": ["m"] },
+            "b": { "id": "b", "type": "console-log", "params": { "message": "b" }, "next": ["m"] },
+            "m": {
+                "id": "m", "type": "merge", "next": ["out"], "expectedInputs": 2,
+                "params": { "mode": "ALL", "timeout": 0, "outputToGlobal": true }
+            },
+            "out": { "id": "out", "type": "console-log", "params": { "message": "{inputs.0.logs}-{inputs.1.logs}" }, "next": [] }
+        }));
+
+        let result = WorkflowEngine::new().execute(wf, json!({}), ExecutionOptions::default()).await;
+        assert!(result.success, "{:?}", result.error);
+        let out: Vec<_> = result.logs.iter().filter(|l| l.node_id == "out").collect();
+        assert_eq!(out.len(), 1);
+        assert!(out[0].message == "a-b" || out[0].message == "b-a");
+        assert_eq!(result.final_state["m"]["inputs"].as_array().unwrap().len(), 2);
+    }
+
+    fn waiting_merge(timeout: u64, behavior: &str) -> WorkflowExecution {
+        workflow("a", json!({
+            "a": { "id": "a", "type": "console-log", "params": { "message": "a" }, "next": ["m"] },
+            "m": {
+                "id": "m", "type": "merge", "next": ["out"], "expectedInputs": 2,
+                "params": { "mode": "ALL", "timeout": timeout, "timeoutBehavior": behavior }
+            },
+            "out": { "id": "out", "type": "console-log", "params": { "message": "out" }, "next": [] }
+        }))
+    }
+
+    #[tokio::test]
+    async fn merge_timeout_finishes_before_result() {
+        let result = WorkflowEngine::new().execute(waiting_merge(50, "execute"), json!({}), ExecutionOptions::default()).await;
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.logs.iter().any(|l| l.node_id == "out"));
+
+        let result = WorkflowEngine::new().execute(waiting_merge(50, "throw"), json!({}), ExecutionOptions::default()).await;
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("合并节点等待超时"));
+    }
+
+    #[derive(Default)]
+    struct Completed(Mutex<Vec<String>>);
+
+    impl ExecutionCallback for Completed {
+        fn on_node_complete(&self, node_id: &str, _result: &NodeExecutionResult) {
+            self.0.lock().unwrap().push(node_id.to_string());
+        }
+    }
+
+    #[tokio::test]
+    async fn merge_timer_is_aborted_after_execution_timeout() {
+        let completed = Arc::new(Completed::default());
+        let options = ExecutionOptions { timeout: 30, callback: Some(completed.clone()), ..Default::default() };
+        let result = WorkflowEngine::new().execute(waiting_merge(100, "execute"), json!({}), options).await;
+        assert!(!result.success);
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(*completed.0.lock().unwrap(), vec!["a"]);
+    }
+}
@@ -0,0 +1,13 @@
+//! 后端工作流执行引擎
+//!
+//! 执行前端 WorkflowConverter 转换得到的 WorkflowExecution，逻辑与 renflow.runner 中的 WorkflowEngine 保持一致
+
+pub mod engine;
+pub mod nodes;
+pub mod trigger;
+pub mod types;
+pub mod utils;
+
+pub use engine::{ExecutionCallback, ExecutionOptions, WorkflowEngine, WorkflowExecutionResult};
+pub use trigger::check_trigger_config;
+pub use types::{ExecutionNode, TriggerConfig, WorkflowExecution};
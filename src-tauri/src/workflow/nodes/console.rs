@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use regex::Regex;
+use serde_json::{json, Value};
+
+use super::{Node, NodeContext, NodeExecutionResult};
+use crate::workflow::utils::{is_truthy, js_string};
+
+/// 日志节点，将内容写入到运行日志中
+pub struct ConsoleNode;
+
+/// 支持 a.b.c、a[0].b 或 a.0.b 形式的路径
+fn resolve_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    let normalized = Regex::new(r"\[(\d+)\]").unwrap().replace_all(path, ".$1").to_string();
+    let mut current = value;
+    for part in normalized.split('.') {
+        current = match current {
+            Value::Object(map) => map.get(part)?,
+            Value::Array(items) => items.get(part.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(current)
+}
+
+fn render_template(template: &str, input: &Value) -> String {
+    let regex = Regex::new(r"\{([^}]+)\}").unwrap();
+    regex
+        .replace_all(template, |captures: &regex::Captures| {
+            match resolve_path(input, captures[1].trim()) {
+                None => String::new(),
+                Some(value @ (Value::String(_) | Value::Number(_) | Value::Bool(_))) => js_string(value),
+                Some(value) => value.to_string(),
+            }
+        })
+        .to_string()
+}
+
+#[async_trait]
+impl Node for ConsoleNode {
+    fn id(&self) -> &'static str {
+        "console-log"
+    }
+
+    fn required_params(&self) -> &'static [(&'static str, &'static str)] {
+        &[("message", "输出内容")]
+    }
+
+    async fn execute(&self, input: Value, params: &Value, context: &NodeContext) -> NodeExecutionResult {
+        let message = params.get("message").map(js_string).unwrap_or_default();
+        let level = params.get("logLevel").map(js_string).unwrap_or_else(|| "log".to_string());
+        let include_input = params.get("includeInput").map(is_truthy).unwrap_or(false);
+
+        let mut output = render_template(&message, &input);
+        if include_input {
+            output = format!("{} | 输入数据: {}", output, input);
+        }
+
+        match level.as_str() {
+            "warn" => {
+                log::warn!("{}", output);
+                context.logger.warn(output.clone());
+            }
+            "error" => {
+                log::error!("{}", output);
+                context.logger.error(output.clone());
+            }
+            _ => {
+                log::info!("{}", output);
+                context.logger.log(output.clone());
+            }
+        }
+
+        NodeExecutionResult::ok(json!({ "logs": output, "input": input }))
+    }
+}
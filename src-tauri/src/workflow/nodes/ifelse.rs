@@ -0,0 +1,87 @@
+use async_trait::async_trait;
+use regex::Regex;
+use serde_json::{json, Value};
+
+use super::{Node, NodeContext, NodeExecutionResult};
+use crate::workflow::utils::{js_string, loose_equals, to_number};
+
+/// If-Else 条件分支节点，根据条件判断执行哪个分支
+pub struct IfElseNode;
+
+fn evaluate(mode: &str, param_value: &Value, expected: &Value) -> Result<bool, String> {
+    let exists = !param_value.is_null();
+    let result = match mode {
+        "exists" => exists,
+        "not_exists" => !exists,
+        "equals" => loose_equals(param_value, expected),
+        "not_equals" => !loose_equals(param_value, expected),
+        "strict_equals" => param_value == expected,
+        "strict_not_equals" => param_value != expected,
+        "greater_than" => to_number(param_value) > to_number(expected),
+        "less_than" => to_number(param_value) < to_number(expected),
+        "greater_or_equal" => to_number(param_value) >= to_number(expected),
+        "less_or_equal" => to_number(param_value) <= to_number(expected),
+        "contains" => js_string(param_value).contains(&js_string(expected)),
+        "not_contains" => !js_string(param_value).contains(&js_string(expected)),
+        "regex" => Regex::new(&js_string(expected))
+            .map_err(|e| e.to_string())?
+            .is_match(&js_string(param_value)),
+        _ => false,
+    };
+    Ok(result)
+}
+
+#[async_trait]
+impl Node for IfElseNode {
+    fn id(&self) -> &'static str {
+        "ifelse"
+    }
+
+    fn required_params(&self) -> &'static [(&'static str, &'static str)] {
+        &[("condition", "条件配置")]
+    }
+
+    async fn execute(&self, input: Value, params: &Value, context: &NodeContext) -> NodeExecutionResult {
+        let condition = params.get("condition").cloned().unwrap_or(Value::Null);
+        let parameter = condition.get("parameter").and_then(Value::as_str).unwrap_or("input");
+        let mode = condition.get("mode").and_then(Value::as_str).unwrap_or("exists");
+        let expected = condition.get("value").cloned().unwrap_or(Value::Null);
+
+        if parameter == "custom" {
+            let message = "后端运行时不支持自定义 JS 条件";
+            context.logger.error(format!("[If-Else] 条件判断失败: {}", message));
+            return NodeExecutionResult::fail(format!("条件表达式错误: {}", message));
+        }
+
+        // 解析参数路径 (例如: "input.value" 或 "input")
+        let param_value = parameter
+            .split('.')
+            .nth(1)
+            .and_then(|key| input.get(key))
+            .cloned()
+            .unwrap_or(Value::Null);
+
+        let is_true = match evaluate(mode, &param_value, &expected) {
+            Ok(result) => result,
+            Err(err) => {
+                context.logger.error(format!("[If-Else] 条件判断失败: {}", err));
+                return NodeExecutionResult::fail(format!("条件表达式错误: {}", err));
+            }
+        };
+
+        context.logger.log(format!(
+            "[If-Else] 条件判断: {}（{} / {}）",
+            is_true,
+            js_string(&param_value),
+            js_string(&expected)
+        ));
+
+        let mut output = match input {
+            Value::Object(map) => map,
+            _ => serde_json::Map::new(),
+        };
+        output.insert("_branch".to_string(), json!(is_true));
+        output.insert("_conditionResult".to_string(), json!(is_true));
+        NodeExecutionResult::ok(Value::Object(output))
+    }
+}
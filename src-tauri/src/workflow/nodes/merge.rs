@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{Node, NodeContext, NodeExecutionResult};
+use crate::workflow::utils::js_string;
+
+/// 合并节点，ALL 模式的等待逻辑由引擎处理，这里只负责组装输出
+pub struct MergeNode;
+
+#[async_trait]
+impl Node for MergeNode {
+    fn id(&self) -> &'static str {
+        "merge"
+    }
+
+    async fn execute(&self, input: Value, params: &Value, _context: &NodeContext) -> NodeExecutionResult {
+        let mode = params
+            .get("mode")
+            .map(js_string)
+            .unwrap_or_else(|| "ANY".to_string())
+            .to_uppercase();
+        match mode.as_str() {
+            "ANY" => NodeExecutionResult::ok(input),
+            "ALL" => {
+                let inputs = if input.is_array() { input } else { json!([]) };
+                NodeExecutionResult::ok(json!({ "inputs": inputs }))
+            }
+            _ => NodeExecutionResult::fail(format!("未知的合并模式: {}", mode)),
+        }
+    }
+}
@@ -0,0 +1,186 @@
+mod console;
+mod ifelse;
+mod merge;
+mod note;
+mod send_message;
+mod send_text;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::engine::{ExecutionLog, GlobalState};
+use crate::connectors::BotConnector;
+use super::utils::is_truthy;
+
+/// 节点日志记录器，写入当前执行上下文的日志列表
+#[derive(Clone)]
+pub struct NodeLogger {
+    node_id: String,
+    logs: Arc<Mutex<Vec<ExecutionLog>>>,
+}
+
+impl NodeLogger {
+    pub(crate) fn new(node_id: String, logs: Arc<Mutex<Vec<ExecutionLog>>>) -> Self {
+        Self { node_id, logs }
+    }
+
+    pub fn log(&self, message: impl Into<String>) {
+        self.push("log", message.into());
+    }
+
+    pub fn warn(&self, message: impl Into<String>) {
+        self.push("warn", message.into());
+    }
+
+    pub fn error(&self, message: impl Into<String>) {
+        self.push("error", message.into());
+    }
+
+    fn push(&self, level: &str, message: String) {
+        self.logs.lock().unwrap().push(ExecutionLog {
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            node_id: self.node_id.clone(),
+            level: level.to_string(),
+            message,
+            data: None,
+        });
+    }
+}
+
+/// 节点执行上下文
+#[derive(Clone)]
+pub struct NodeContext {
+    pub node_id: String,
+    pub node_type: String,
+    /// 全局状态（可用于节点间共享数据）
+    pub global_state: GlobalState,
+    pub logger: NodeLogger,
+    /// 触发工作流的 Bot（对应 renflow.runner 中全局变量 bot）
+    pub bot: Option<Arc<dyn BotConnector>>,
+}
+
+impl NodeContext {
+    /// 从全局存储读取值
+    pub fn get_global(&self, key: &str) -> Option<Value> {
+        self.global_state.lock().unwrap().get(key).cloned()
+    }
+
+    /// 写入全局存储
+    pub fn set_global(&self, key: &str, value: Value) {
+        self.global_state.lock().unwrap().insert(key.to_string(), value);
+    }
+}
+
+/// 节点执行结果
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeExecutionResult {
+    pub success: bool,
+    pub output: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl NodeExecutionResult {
+    pub fn ok(output: Value) -> Self {
+        Self { success: true, output, error: None }
+    }
+
+    pub fn fail(error: impl Into<String>) -> Self {
+        Self { success: false, output: Value::Null, error: Some(error.into()) }
+    }
+}
+
+/// 后端可执行的节点
+#[async_trait]
+pub trait Node: Send + Sync {
+    /// 节点类型 ID（对应 renflow.runner 中节点元数据的 id）
+    fn id(&self) -> &'static str;
+
+    /// 必填参数列表 (key, label)
+    fn required_params(&self) -> &'static [(&'static str, &'static str)] {
+        &[]
+    }
+
+    async fn execute(&self, input: Value, params: &Value, context: &NodeContext)
+        -> NodeExecutionResult;
+}
+
+/// 节点管理器，负责注册和执行后端节点
+pub struct NodeManager {
+    nodes: HashMap<&'static str, Arc<dyn Node>>,
+}
+
+impl NodeManager {
+    pub fn new() -> Self {
+        let mut manager = Self { nodes: HashMap::new() };
+        manager.register(Arc::new(console::ConsoleNode));
+        manager.register(Arc::new(note::NoteNode));
+        manager.register(Arc::new(ifelse::IfElseNode));
+        manager.register(Arc::new(merge::MergeNode));
+        manager.register(Arc::new(send_text::SendTextNode));
+        manager.register(Arc::new(send_message::SendMessageNode));
+        manager
+    }
+
+    pub fn register(&mut self, node: Arc<dyn Node>) {
+        self.nodes.insert(node.id(), node);
+    }
+
+    pub fn has_node(&self, node_type: &str) -> bool {
+        self.nodes.contains_key(node_type)
+    }
+
+    /// 获取所有已注册的节点类型
+    pub fn node_types(&self) -> Vec<String> {
+        let mut types: Vec<String> = self.nodes.keys().map(|k| k.to_string()).collect();
+        types.sort();
+        types
+    }
+
+    /// 执行节点（包含参数验证和输出到全局的处理）
+    pub async fn execute_node(
+        &self,
+        node_type: &str,
+        input: Value,
+        params: &Value,
+        context: &NodeContext,
+    ) -> NodeExecutionResult {
+        let Some(node) = self.nodes.get(node_type) else {
+            return NodeExecutionResult::fail(format!("节点不存在: {}", node_type));
+        };
+
+        for (key, label) in node.required_params() {
+            if !params.get(key).map(is_truthy).unwrap_or(false) {
+                return NodeExecutionResult::fail(format!("参数 \"{} ({})\" 是必填项", label, key));
+            }
+        }
+
+        let result = node.execute(input, params, context).await;
+
+        // 当节点配置了 outputToGlobal 时，把输出写入全局变量，键名为节点 id（合并存在数据）
+        let output_to_global = params.get("outputToGlobal").map(is_truthy).unwrap_or(false);
+        if result.success && output_to_global {
+            let mut state = context.global_state.lock().unwrap();
+            let mut merged = match state.get(&context.node_id) {
+                Some(Value::Object(existing)) => existing.clone(),
+                _ => serde_json::Map::new(),
+            };
+            if let Value::Object(output) = &result.output {
+                merged.extend(output.clone());
+            }
+            state.insert(context.node_id.clone(), Value::Object(merged));
+        }
+
+        result
+    }
+}
+
+impl Default for NodeManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,26 @@
+use async_trait::async_trait;
+use serde_json::Value;
+
+use super::{Node, NodeContext, NodeExecutionResult};
+use crate::workflow::utils::js_string;
+
+/// 注释节点，不执行任何操作，直接透传输入
+pub struct NoteNode;
+
+#[async_trait]
+impl Node for NoteNode {
+    fn id(&self) -> &'static str {
+        "note"
+    }
+
+    async fn execute(&self, input: Value, params: &Value, context: &NodeContext) -> NodeExecutionResult {
+        let title = params.get("title").map(js_string).filter(|s| !s.is_empty());
+        let content = params.get("content").map(js_string).filter(|s| !s.is_empty());
+        context.logger.log(format!(
+            "[注释] {}: {}",
+            title.as_deref().unwrap_or("注释"),
+            content.as_deref().unwrap_or("(无内容)")
+        ));
+        NodeExecutionResult::ok(input)
+    }
+}
@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::send_text::check_response;
+use super::{Node, NodeContext, NodeExecutionResult};
+use crate::workflow::utils::{fill_text_template, js_string, to_number};
+
+/// 发送消息节点，组装自定义的消息结构发送到指定目标
+pub struct SendMessageNode;
+
+#[async_trait]
+impl Node for SendMessageNode {
+    fn id(&self) -> &'static str {
+        "send-message"
+    }
+
+    fn required_params(&self) -> &'static [(&'static str, &'static str)] {
+        &[("sender", "发送者"), ("targetType", "接收者类型"), ("target", "接收者")]
+    }
+
+    async fn execute(&self, input: Value, params: &Value, context: &NodeContext) -> NodeExecutionResult {
+        let Some(bot) = &context.bot else {
+            return NodeExecutionResult::fail("bot 参数无效");
+        };
+        let message = context.get_global("trigger").unwrap_or(Value::Null);
+
+        let mut target = params.get("target").cloned().unwrap_or(Value::Null);
+        if js_string(&target).contains("{trigger.targetId}") {
+            target = message
+                .get("targetId")
+                .or_else(|| message.get("groupId"))
+                .cloned()
+                .unwrap_or(Value::Null);
+        }
+        let target_type = params.get("targetType").and_then(Value::as_str).unwrap_or("private");
+
+        // 组装消息体
+        let mut body = Vec::new();
+        for item in params.get("msgList").and_then(Value::as_array).into_iter().flatten() {
+            let mut data = item.get("data").map(js_string).unwrap_or_default();
+            if data.contains('{') && data.contains('}') {
+                data = match fill_text_template(&data, &input, context, false) {
+                    Ok(data) => data,
+                    Err(err) => return NodeExecutionResult::fail(err),
+                };
+            }
+            match item.get("value").and_then(Value::as_str) {
+                Some("text") => body.push(json!({ "type": "text", "data": { "text": data } })),
+                Some("image") => body.push(json!({ "type": "image", "data": { "file": data } })),
+                _ => {}
+            }
+        }
+
+        let target = to_number(&target);
+        if !target.is_finite() || target.fract() != 0.0 {
+            return NodeExecutionResult::fail("接收者 ID 无效");
+        }
+        let key = if target_type == "group" { "group_id" } else { "user_id" };
+        let params = json!({ "message": body, key: target as i64 });
+        let response = match bot.call_api("send_msg", params, None).await {
+            Ok(response) => response,
+            Err(err) => return NodeExecutionResult::fail(err),
+        };
+        match check_response(&response) {
+            Ok(()) => NodeExecutionResult::ok(json!({ "sent": true, "message": "消息发送成功" })),
+            Err(err) => NodeExecutionResult::fail(err),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use super::{Node, NodeContext, NodeExecutionResult};
+use crate::workflow::utils::{fill_text_template, js_string};
+
+/// 发送文本消息节点，回复到触发消息的来源
+pub struct SendTextNode;
+
+/// 检查 API 响应的 retcode
+pub(super) fn check_response(response: &Value) -> Result<(), String> {
+    match response.get("retcode").and_then(Value::as_i64) {
+        Some(0) => Ok(()),
+        retcode => Err(format!(
+            "发送消息失败 > 错误码：{}，信息：{}",
+            retcode.map(|c| c.to_string()).unwrap_or_else(|| "undefined".to_string()),
+            response.get("message").map(js_string).unwrap_or_else(|| "undefined".to_string())
+        )),
+    }
+}
+
+#[async_trait]
+impl Node for SendTextNode {
+    fn id(&self) -> &'static str {
+        "send-text"
+    }
+
+    fn required_params(&self) -> &'static [(&'static str, &'static str)] {
+        &[("text", "消息内容")]
+    }
+
+    async fn execute(&self, input: Value, params: &Value, context: &NodeContext) -> NodeExecutionResult {
+        let text = params.get("text").map(js_string).unwrap_or_default();
+        let Some(bot) = &context.bot else {
+            return NodeExecutionResult::fail("bot 参数无效");
+        };
+
+        let message = context.get_global("trigger").unwrap_or(Value::Null);
+        let Some(id) = message.get("groupId").or_else(|| message.get("userId")).cloned() else {
+            return NodeExecutionResult::fail("消息体异常");
+        };
+        let content = match fill_text_template(&text, &input, context, false) {
+            Ok(content) => content,
+            Err(err) => return NodeExecutionResult::fail(err),
+        };
+
+        let target = if message.get("messageType").and_then(Value::as_str) == Some("group") {
+            "group_id"
+        } else {
+            "user_id"
+        };
+        let params = json!({
+            "message": [{ "type": "text", "data": { "text": content } }],
+            target: id,
+        });
+        let response = match bot.call_api("send_msg", params, None).await {
+            Ok(response) => response,
+            Err(err) => return NodeExecutionResult::fail(err),
+        };
+        match check_response(&response) {
+            Ok(()) => NodeExecutionResult::ok(json!({ "text": text, "sent": true, "message": "消息发送成功" })),
+            Err(err) => NodeExecutionResult::fail(err),
+        }
+    }
+}
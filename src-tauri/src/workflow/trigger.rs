@@ -0,0 +1,161 @@
+use regex::Regex;
+use serde_json::Value;
+
+/// 按 filterParam（简化的 JSONPath，如 `message`、`$.message.[*].type`）查询数据
+fn query(data: &Value, path: &str) -> Vec<Value> {
+    let path = path.trim().trim_start_matches('$').trim_start_matches('.');
+    let mut current = vec![data.clone()];
+    if path.is_empty() {
+        return current;
+    }
+    let normalized = path.replace('[', ".[").replace("..", ".");
+    for part in normalized.split('.').filter(|p| !p.is_empty()) {
+        current = current
+            .iter()
+            .flat_map(|value| -> Vec<Value> {
+                match (part, value) {
+                    ("[*]" | "*", Value::Array(items)) => items.clone(),
+                    ("[*]" | "*", Value::Object(map)) => map.values().cloned().collect(),
+                    (_, Value::Array(items)) => part
+                        .trim_start_matches('[')
+                        .trim_end_matches(']')
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|i| items.get(i).cloned())
+                        .into_iter()
+                        .collect(),
+                    (_, Value::Object(map)) => {
+                        let key = part.trim_start_matches("['").trim_end_matches("']");
+                        map.get(key).cloned().into_iter().collect()
+                    }
+                    _ => Vec::new(),
+                }
+            })
+            .collect();
+    }
+    current
+}
+
+/// 获取消息中的文本内容
+fn text_content(message: &Value) -> String {
+    match message {
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items
+            .iter()
+            .filter(|item| item.get("type").and_then(Value::as_str) == Some("text"))
+            .filter_map(|item| item.pointer("/data/text").and_then(Value::as_str))
+            .collect(),
+        _ => String::new(),
+    }
+}
+
+/// 按 shell 规则拆分参数（支持引号和转义，忽略管道等操作符）
+fn shell_split(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut has_token = false;
+    let mut quote: Option<char> = None;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            (Some(_), c) => current.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                has_token = true;
+            }
+            (None, '\\') => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                    has_token = true;
+                }
+            }
+            (None, c) if c.is_whitespace() || "|&;<>()".contains(c) => {
+                if has_token {
+                    tokens.push(std::mem::take(&mut current));
+                    has_token = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                has_token = true;
+            }
+        }
+    }
+    if has_token {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// 取出命令中的位置参数（与 mri 的解析规则一致，选项后不以 - 开头的参数视为选项值）
+fn positionals(tokens: &[String]) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        if token == "--" {
+            result.extend(tokens[i + 1..].iter().cloned());
+            break;
+        }
+        if !token.starts_with('-') || token == "-" {
+            result.push(token.clone());
+            i += 1;
+            continue;
+        }
+        let name = token.trim_start_matches('-');
+        let takes_value = !name.contains('=') && !name.starts_with("no-");
+        if takes_value && tokens.get(i + 1).is_some_and(|next| !next.starts_with('-')) {
+            i += 2;
+        } else {
+            i += 1;
+        }
+    }
+    result
+}
+
+/// 检查触发数据是否满足触发器配置（与 renflow.runner 中的 checkTriggerConfig 一致）
+pub fn check_trigger_config(params: Option<&Value>, data: &Value) -> Result<bool, String> {
+    let Some(config) = params.filter(|p| p.is_object()) else {
+        return Ok(false);
+    };
+    let str_param = |key: &str| config.get(key).and_then(Value::as_str).unwrap_or_default();
+
+    let filter_param = str_param("filterParam");
+    let results = query(data, filter_param);
+    // 只有消息类触发数据才做匹配
+    let is_message = data.get("messageType").is_some() && data.get("message").is_some();
+    if results.len() != 1 || !is_message {
+        return Ok(false);
+    }
+    let include_self = config.get("includeSelf").and_then(Value::as_bool).unwrap_or(false);
+    if !include_self && data.get("isMine").and_then(Value::as_bool).unwrap_or(false) {
+        // 跳过自己发送的消息
+        return Ok(false);
+    }
+
+    let text = text_content(data.get("message").unwrap_or(&Value::Null));
+    let triggered = match str_param("filterMode") {
+        "regex" => Regex::new(str_param("regexExpression"))
+            .map_err(|e| format!("触发器验证出错: {}", e))?
+            .is_match(&text),
+        "shell" => {
+            let prefix = str_param("prefix");
+            match text.strip_prefix(prefix) {
+                Some(command) => {
+                    let expected = positionals(&shell_split(str_param("shellCommand")));
+                    let given = positionals(&shell_split(command.trim()));
+                    expected.len() <= given.len() && expected.iter().zip(&given).all(|(a, b)| a == b)
+                }
+                None => false,
+            }
+        }
+        _ => false,
+    };
+    Ok(triggered)
+}
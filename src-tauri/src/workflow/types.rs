@@ -0,0 +1,122 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 执行节点，与 renflow.runner 中的 ExecutionNode 保持一致
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionNode {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub node_type: String,
+    #[serde(default)]
+    pub params: Value,
+    #[serde(default)]
+    pub next: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branches: Option<HashMap<String, Option<String>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_inputs: Option<u32>,
+}
+
+impl ExecutionNode {
+    /// 获取指定分支连接的节点 ID
+    pub fn branch(&self, key: &str) -> Option<&str> {
+        self.branches
+            .as_ref()
+            .and_then(|b| b.get(key))
+            .and_then(|v| v.as_deref())
+            .filter(|v| !v.is_empty())
+    }
+}
+
+/// 触发器配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TriggerConfig {
+    #[serde(rename = "type", default)]
+    pub trigger_type: String,
+    #[serde(default)]
+    pub type_label: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub label: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+}
+
+/// 工作流执行数据，由前端 WorkflowConverter 转换得到
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowExecution {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub trigger: TriggerConfig,
+    pub entry_node: Option<String>,
+    #[serde(default)]
+    pub nodes: HashMap<String, ExecutionNode>,
+    /// 执行超时时间（毫秒），未设置时由运行方决定
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    #[serde(default)]
+    pub created_at: i64,
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+impl WorkflowExecution {
+    /// 验证执行数据的完整性，返回错误列表
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        match &self.entry_node {
+            None => errors.push("工作流没有入口节点（触发器未连接到任何节点）".to_string()),
+            Some(entry) if !self.nodes.contains_key(entry) => {
+                errors.push(format!("入口节点 {} 不存在", entry))
+            }
+            _ => {}
+        }
+
+        let mut referenced: HashSet<&str> = HashSet::new();
+        if let Some(entry) = &self.entry_node {
+            referenced.insert(entry);
+        }
+
+        for (node_id, node) in self.nodes.iter() {
+            for next_id in node.next.iter().filter(|id| !id.trim().is_empty()) {
+                if !self.nodes.contains_key(next_id) {
+                    errors.push(format!("节点 {} 引用了不存在的节点: {}", node_id, next_id));
+                }
+                referenced.insert(next_id);
+            }
+            if let Some(branches) = &node.branches {
+                for (branch, target) in branches.iter() {
+                    let Some(target) = target.as_deref().filter(|t| !t.trim().is_empty()) else {
+                        continue;
+                    };
+                    if !self.nodes.contains_key(target) {
+                        errors.push(format!(
+                            "节点 {} 的分支 {} 引用了不存在的节点: {}",
+                            node_id, branch, target
+                        ));
+                    }
+                    referenced.insert(target);
+                }
+            }
+        }
+
+        for node_id in self.nodes.keys() {
+            if !referenced.contains(node_id.as_str()) {
+                errors.push(format!("节点 {} 是孤立节点（未被任何节点引用）", node_id));
+            }
+        }
+
+        errors
+    }
+}
@@ -0,0 +1,114 @@
+use regex::Regex;
+use serde_json::Value;
+
+use super::nodes::NodeContext;
+
+/// 按 JavaScript 的规则判断值是否为真
+pub fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().map(|f| f != 0.0 && !f.is_nan()).unwrap_or(false),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(_) | Value::Object(_) => true,
+    }
+}
+
+/// 按 JavaScript 的 String() 规则将值转为字符串
+pub fn js_string(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => match n.as_f64() {
+            Some(f) if f.fract() == 0.0 && f.abs() < 1e21 => format!("{}", f as i64),
+            _ => n.to_string(),
+        },
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(|v| if v.is_null() { String::new() } else { js_string(v) })
+            .collect::<Vec<_>>()
+            .join(","),
+        Value::Object(_) => "[object Object]".to_string(),
+    }
+}
+
+/// 按 JavaScript 的 Number() 规则将值转为数字
+pub fn to_number(value: &Value) -> f64 {
+    match value {
+        Value::Null => 0.0,
+        Value::Bool(b) => if *b { 1.0 } else { 0.0 },
+        Value::Number(n) => n.as_f64().unwrap_or(f64::NAN),
+        Value::String(s) => {
+            let s = s.trim();
+            if s.is_empty() { 0.0 } else { s.parse().unwrap_or(f64::NAN) }
+        }
+        _ => f64::NAN,
+    }
+}
+
+/// 近似 JavaScript 的宽松相等（==）
+pub fn loose_equals(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Null, Value::Null) => true,
+        (Value::Null, _) | (_, Value::Null) => false,
+        (Value::String(x), Value::String(y)) => x == y,
+        (Value::Object(_), _) | (_, Value::Object(_)) | (Value::Array(_), _) | (_, Value::Array(_)) => {
+            js_string(a) == js_string(b)
+        }
+        _ => to_number(a) == to_number(b),
+    }
+}
+
+/// 根据形似 'a.b.c' 的路径从对象中取值，对象会被转为 JSON 字符串
+pub fn get_value(value: &Value, path: &str) -> Option<Value> {
+    if path.is_empty() {
+        return Some(value.clone());
+    }
+    let mut current = value;
+    for part in path.split('.') {
+        current = match current {
+            Value::Object(map) => map.get(part)?,
+            Value::Array(items) => items.get(part.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    match current {
+        Value::Object(_) => Some(Value::String(current.to_string())),
+        _ => Some(current.clone()),
+    }
+}
+
+/// 填充文本模板中的 {path} 占位符，多级路径的第一段为全局存储中的键
+pub fn fill_text_template(
+    template: &str,
+    input: &Value,
+    context: &NodeContext,
+    throw_error: bool,
+) -> Result<String, String> {
+    let regex = Regex::new(r"\{([^}]+)\}").unwrap();
+    let mut result = String::with_capacity(template.len());
+    let mut last = 0;
+    for captures in regex.captures_iter(template) {
+        let placeholder = captures.get(0).unwrap();
+        let path: Vec<&str> = captures[1].split('.').collect();
+        let value = if path.len() == 1 {
+            get_value(input, path[0])
+        } else {
+            context
+                .get_global(path[0])
+                .and_then(|data| get_value(&data, &path[1..].join(".")))
+        };
+        result.push_str(&template[last..placeholder.start()]);
+        match value {
+            Some(value) => result.push_str(&js_string(&value)),
+            None if throw_error => {
+                return Err(format!("无法解析模板中的占位符: {}", placeholder.as_str()))
+            }
+            None => result.push_str(placeholder.as_str()),
+        }
+        last = placeholder.end();
+    }
+    result.push_str(&template[last..]);
+    Ok(result)
+}
@@ -0,0 +1,396 @@
+//! 工作流存储
+//!
+//! 每个工作流（前端的 WorkflowData）保存为应用数据目录下 `workflows/<id>.json`，
+//! 写入时先写临时文件再重命名，单个文件损坏不会影响其他工作流。
+//! 每次保存同时在 `workflows/.history/<id>/` 下记录一个历史版本，最多保留的数量由设置决定，默认 [`DEFAULT_MAX_REVISIONS`] 个。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 旧版本保存所有工作流的 Store 键（.settings.dat）
+pub const LEGACY_STORE_KEY: &str = "renflow_workflows";
+/// 每个工作流默认保留的历史版本数量
+pub const DEFAULT_MAX_REVISIONS: usize = 20;
+const HISTORY_DIR: &str = ".history";
+
+/// 工作流的一个历史版本
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Revision {
+    pub id: u64,
+    pub timestamp: i64,
+    pub message: Option<String>,
+    /// 列出历史版本时不包含工作流内容
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workflow: Option<Value>,
+}
+
+/// 按 ID 比较的节点或连线变化
+#[derive(Serialize, Default, Debug, PartialEq)]
+pub struct ChangeSet {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RevisionDiff {
+    pub nodes: ChangeSet,
+    pub edges: ChangeSet,
+}
+
+impl ChangeSet {
+    fn between(from: &Value, to: &Value) -> Self {
+        let index = |v: &Value| -> Vec<(String, Value)> {
+            v.as_array()
+                .map(|items| {
+                    items
+                        .iter()
+                        .filter_map(|item| Some((item.get("id")?.as_str()?.to_string(), item.clone())))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let (from, to) = (index(from), index(to));
+        let find = |list: &[(String, Value)], id: &str| list.iter().find(|(i, _)| i == id).map(|(_, v)| v.clone());
+
+        let mut set = Self::default();
+        for (id, value) in &to {
+            match find(&from, id) {
+                None => set.added.push(id.clone()),
+                Some(old) if old != *value => set.changed.push(id.clone()),
+                _ => {}
+            }
+        }
+        set.removed = from.iter().filter(|(id, _)| find(&to, id).is_none()).map(|(id, _)| id.clone()).collect();
+        set
+    }
+}
+
+pub struct WorkflowStore {
+    dir: PathBuf,
+    /// 每个工作流保留的历史版本数量，至少为 1
+    max_revisions: usize,
+    lock: Mutex<()>,
+}
+
+fn workflow_id(workflow: &Value) -> Result<&str, String> {
+    workflow.get("id").and_then(Value::as_str).ok_or("工作流缺少 ID".to_string())
+}
+
+/// 先写入临时文件再重命名，避免写入中断导致文件损坏
+pub(crate) fn write_atomic(path: &Path, content: &[u8]) -> Result<(), String> {
+    let tmp = path.with_extension("tmp");
+    let write = || -> std::io::Result<()> {
+        use std::io::Write;
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    };
+    write().map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        format!("写入 {} 失败: {}", path.display(), e)
+    })
+}
+
+impl WorkflowStore {
+    pub fn new(dir: PathBuf, max_revisions: usize) -> Result<Self, String> {
+        fs::create_dir_all(&dir).map_err(|e| format!("无法创建工作流目录 {}: {}", dir.display(), e))?;
+        Ok(Self { dir, max_revisions: max_revisions.max(1), lock: Mutex::new(()) })
+    }
+
+    fn path(&self, id: &str) -> Result<PathBuf, String> {
+        let valid = !id.is_empty()
+            && !id.starts_with('.')
+            && id.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c));
+        if !valid {
+            return Err(format!("无效的工作流 ID: {}", id));
+        }
+        Ok(self.dir.join(format!("{}.json", id)))
+    }
+
+    fn read(path: &Path) -> Result<Value, String> {
+        let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| e.to_string())
+    }
+
+    /// 读取所有工作流，按创建时间排序，无法解析的文件会被跳过
+    pub fn all(&self) -> Vec<Value> {
+        let _guard = self.lock.lock().unwrap();
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut workflows: Vec<Value> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| match Self::read(&path) {
+                Ok(workflow) => Some(workflow),
+                Err(err) => {
+                    warn!("跳过无法读取的工作流 {}: {}", path.display(), err);
+                    None
+                }
+            })
+            .collect();
+        workflows.sort_by_key(|w| w.get("createdAt").and_then(Value::as_i64).unwrap_or_default());
+        workflows
+    }
+
+    /// 工作流列表（不包含节点与连线）
+    pub fn list(&self) -> Vec<Value> {
+        self.all()
+            .into_iter()
+            .map(|mut workflow| {
+                if let Some(map) = workflow.as_object_mut() {
+                    map.remove("nodes");
+                    map.remove("edges");
+                }
+                workflow
+            })
+            .collect()
+    }
+
+    pub fn get(&self, id: &str) -> Result<Option<Value>, String> {
+        let path = self.path(id)?;
+        let _guard = self.lock.lock().unwrap();
+        if !path.exists() {
+            return Ok(None);
+        }
+        Self::read(&path).map(Some).map_err(|e| format!("读取工作流 {} 失败: {}", id, e))
+    }
+
+    pub fn save(&self, workflow: &Value) -> Result<(), String> {
+        self.save_with_message(workflow, None)
+    }
+
+    /// 保存工作流并记录历史版本，内容与上一个版本相同且没有说明时不重复记录
+    pub fn save_with_message(&self, workflow: &Value, message: Option<String>) -> Result<(), String> {
+        let id = workflow_id(workflow)?;
+        let path = self.path(id)?;
+        let content = serde_json::to_vec_pretty(workflow).map_err(|e| e.to_string())?;
+        let _guard = self.lock.lock().unwrap();
+        write_atomic(&path, &content)?;
+        if let Err(err) = self.record_revision(id, workflow, message) {
+            warn!("记录工作流 {} 历史版本失败: {}", id, err);
+        }
+        Ok(())
+    }
+
+    pub fn delete(&self, id: &str) -> Result<(), String> {
+        let path = self.path(id)?;
+        let _guard = self.lock.lock().unwrap();
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(format!("删除工作流 {} 失败: {}", id, err)),
+            _ => {
+                let _ = fs::remove_dir_all(self.history_dir(id));
+                Ok(())
+            }
+        }
+    }
+
+    // ==================== 历史版本 ====================
+
+    fn history_dir(&self, id: &str) -> PathBuf {
+        self.dir.join(HISTORY_DIR).join(id)
+    }
+
+    /// 历史版本 ID，从旧到新排序
+    fn revision_ids(&self, id: &str) -> Vec<u64> {
+        let mut ids: Vec<u64> = fs::read_dir(self.history_dir(id))
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter_map(|entry| entry.path().file_name()?.to_str()?.strip_suffix(".json")?.parse().ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        ids.sort_unstable();
+        ids
+    }
+
+    fn read_revision(&self, id: &str, revision: u64) -> Result<Revision, String> {
+        let path = self.history_dir(id).join(format!("{}.json", revision));
+        let content = fs::read_to_string(&path).map_err(|_| format!("工作流 {} 的历史版本 {} 不存在", id, revision))?;
+        serde_json::from_str(&content).map_err(|e| format!("历史版本 {} 解析失败: {}", revision, e))
+    }
+
+    fn record_revision(&self, id: &str, workflow: &Value, message: Option<String>) -> Result<(), String> {
+        let dir = self.history_dir(id);
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let ids = self.revision_ids(id);
+
+        if message.is_none() {
+            let without_time = |w: &Value| {
+                let mut w = w.clone();
+                if let Some(map) = w.as_object_mut() {
+                    map.remove("updatedAt");
+                }
+                w
+            };
+            let last = ids.last().and_then(|rev| self.read_revision(id, *rev).ok());
+            if last.and_then(|r| r.workflow).is_some_and(|w| without_time(&w) == without_time(workflow)) {
+                return Ok(());
+            }
+        }
+
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        // 同一毫秒内多次保存时顺延，保证 ID 递增
+        let revision = ids.last().map_or(timestamp as u64, |last| (timestamp as u64).max(last + 1));
+        let record = Revision { id: revision, timestamp, message, workflow: Some(workflow.clone()) };
+        let content = serde_json::to_vec(&record).map_err(|e| e.to_string())?;
+        write_atomic(&dir.join(format!("{}.json", revision)), &content)?;
+
+        for old in ids.iter().take((ids.len() + 1).saturating_sub(self.max_revisions)) {
+            let _ = fs::remove_file(dir.join(format!("{}.json", old)));
+        }
+        Ok(())
+    }
+
+    /// 历史版本列表（从新到旧，不包含工作流内容）
+    pub fn revisions(&self, id: &str) -> Result<Vec<Revision>, String> {
+        self.path(id)?;
+        let _guard = self.lock.lock().unwrap();
+        Ok(self
+            .revision_ids(id)
+            .into_iter()
+            .rev()
+            .filter_map(|rev| self.read_revision(id, rev).ok())
+            .map(|revision| Revision { workflow: None, ..revision })
+            .collect())
+    }
+
+    pub fn get_revision(&self, id: &str, revision: u64) -> Result<Revision, String> {
+        self.path(id)?;
+        let _guard = self.lock.lock().unwrap();
+        self.read_revision(id, revision)
+    }
+
+    /// 比较两个历史版本，`to` 为空时与当前版本比较
+    pub fn diff(&self, id: &str, from: u64, to: Option<u64>) -> Result<RevisionDiff, String> {
+        let from = self.get_revision(id, from)?.workflow.unwrap_or_default();
+        let to = match to {
+            Some(rev) => self.get_revision(id, rev)?.workflow.unwrap_or_default(),
+            None => self.get(id)?.ok_or(format!("工作流 {} 不存在", id))?,
+        };
+        Ok(RevisionDiff {
+            nodes: ChangeSet::between(&from["nodes"], &to["nodes"]),
+            edges: ChangeSet::between(&from["edges"], &to["edges"]),
+        })
+    }
+
+    /// 将历史版本恢复为当前版本，恢复本身也会记录为一个新版本
+    pub fn restore(&self, id: &str, revision: u64) -> Result<Value, String> {
+        let mut workflow = self
+            .get_revision(id, revision)?
+            .workflow
+            .ok_or(format!("历史版本 {} 没有工作流内容", revision))?;
+        workflow["updatedAt"] = chrono::Utc::now().timestamp_millis().into();
+        self.save_with_message(&workflow, Some(format!("恢复到版本 {}", revision)))?;
+        info!("工作流 {} 已恢复到版本 {}", id, revision);
+        Ok(workflow)
+    }
+
+    /// 迁移旧版本保存在单个 Store 键中的工作流，已存在的文件不会被覆盖
+    pub fn migrate_legacy(&self, data: &str) -> Result<usize, String> {
+        let workflows: Vec<Value> = serde_json::from_str(data).map_err(|e| format!("旧工作流数据解析失败: {}", e))?;
+        let mut count = 0;
+        for workflow in workflows {
+            let path = match workflow_id(&workflow).and_then(|id| self.path(id)) {
+                Ok(path) => path,
+                Err(err) => {
+                    warn!("跳过无法迁移的工作流: {}", err);
+                    continue;
+                }
+            };
+            if !path.exists() {
+                self.save(&workflow)?;
+                count += 1;
+            }
+        }
+        info!("已迁移 {} 个工作流到 {}", count, self.dir.display());
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn saves_one_file_per_workflow() {
+        let dir = std::env::temp_dir().join(format!("renflow-store-{}", rand::random::<u32>()));
+        let store = WorkflowStore::new(dir.clone(), DEFAULT_MAX_REVISIONS).unwrap();
+
+        let legacy = json!([
+            { "id": "wf1", "name": "一", "nodes": [], "edges": [], "createdAt": 1 },
+            { "id": "wf2", "name": "二", "nodes": [], "edges": [], "createdAt": 2 }
+        ]);
+        assert_eq!(store.migrate_legacy(&legacy.to_string()).unwrap(), 2);
+        // 损坏的文件只影响自身
+        fs::write(dir.join("broken.json"), "{").unwrap();
+
+        let list = store.list();
+        assert_eq!(list.len(), 2);
+        assert!(list[0].get("nodes").is_none());
+        assert_eq!(store.get("wf2").unwrap().unwrap()["name"], "二");
+
+        store.save(&json!({ "id": "wf2", "name": "改", "createdAt": 2 })).unwrap();
+        assert_eq!(store.get("wf2").unwrap().unwrap()["name"], "改");
+        store.delete("wf1").unwrap();
+        assert!(store.get("wf1").unwrap().is_none());
+        assert!(store.get("../wf").is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn keeps_revisions_and_restores() {
+        let dir = std::env::temp_dir().join(format!("renflow-history-{}", rand::random::<u32>()));
+        let store = WorkflowStore::new(dir.clone(), DEFAULT_MAX_REVISIONS).unwrap();
+        let node = |id: &str, label: &str| json!({ "id": id, "data": { "label": label } });
+
+        store.save(&json!({ "id": "wf", "nodes": [node("a", "1"), node("b", "1")], "edges": [], "updatedAt": 1 })).unwrap();
+        // 只有更新时间不同时不记录新版本
+        store.save(&json!({ "id": "wf", "nodes": [node("a", "1"), node("b", "1")], "edges": [], "updatedAt": 2 })).unwrap();
+        store
+            .save_with_message(
+                &json!({ "id": "wf", "nodes": [node("a", "2"), node("c", "1")], "edges": [{ "id": "e1" }], "updatedAt": 3 }),
+                Some("改".into()),
+            )
+            .unwrap();
+
+        let revisions = store.revisions("wf").unwrap();
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[0].message.as_deref(), Some("改"));
+        assert!(revisions[0].workflow.is_none());
+
+        let (old, new) = (revisions[1].id, revisions[0].id);
+        let diff = store.diff("wf", old, Some(new)).unwrap();
+        assert_eq!(diff.nodes, ChangeSet { added: vec!["c".into()], removed: vec!["b".into()], changed: vec!["a".into()] });
+        assert_eq!(diff.edges.added, vec!["e1"]);
+
+        store.restore("wf", old).unwrap();
+        assert_eq!(store.get("wf").unwrap().unwrap()["nodes"][1]["id"], "b");
+        assert_eq!(store.revisions("wf").unwrap().len(), 3);
+        assert!(store.diff("wf", old, None).unwrap().nodes.changed.is_empty());
+
+        for i in 0..DEFAULT_MAX_REVISIONS {
+            store.save(&json!({ "id": "wf", "nodes": [], "edges": [], "n": i })).unwrap();
+        }
+        assert_eq!(store.revisions("wf").unwrap().len(), DEFAULT_MAX_REVISIONS);
+
+        // 保留数量由参数决定
+        let store = WorkflowStore::new(dir.clone(), 3).unwrap();
+        store.save(&json!({ "id": "wf", "nodes": [], "edges": [], "n": -1 })).unwrap();
+        assert_eq!(store.revisions("wf").unwrap().len(), 3);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,556 @@
+//! 工作区包（.rfw）读取与导入
+//!
+//! 包内包含 `bots.config`（Bot 配置列表）、若干 `<workflowId>.json`（WorkflowExecution，供 renflow-daemon 执行）
+//! 以及 `source/<workflowId>.json`（编辑器中的 WorkflowData，用于导入）。也可以直接读取包含这些文件的目录。
+//!
+//! `manifest.json` 记录包格式版本、导出时的应用版本、文件校验值和使用到的节点类型；
+//! 没有 manifest 的旧包视为格式版本 1，读取时按 [`MIGRATIONS`] 逐步升级到当前版本。
+//! 敏感信息的移除与整包加密见 [`secret`]。
+
+pub mod secret;
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs::{self, File};
+use std::io::{Cursor, Read, Seek, Write};
+use std::path::Path;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::connectors::BotConfig;
+use secret::SecretField;
+use crate::workflow::WorkflowExecution;
+
+pub const BOTS_FILE: &str = "bots.config";
+pub const MANIFEST_FILE: &str = "manifest.json";
+pub const SOURCE_DIR: &str = "source/";
+
+/// 当前的包格式版本
+pub const FORMAT_VERSION: u32 = 2;
+
+type Migration = fn(&mut WorkspacePackage) -> Result<(), String>;
+
+/// 包格式升级步骤，`(from, migration)` 将版本 from 的包升级到 from + 1
+const MIGRATIONS: &[(u32, Migration)] = &[(1, migrate_v1)];
+
+/// 包清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub exported_at: String,
+    /// 文件名 -> SHA-256
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+    #[serde(default)]
+    pub node_types: Vec<String>,
+}
+
+impl Manifest {
+    /// 为即将写入包中的文件生成清单
+    pub fn build(files: &[(&str, &str)]) -> Self {
+        let mut node_types = BTreeSet::new();
+        for (name, content) in files {
+            if let Ok(value) = serde_json::from_str::<Value>(content) {
+                collect_node_types(name, &value, &mut node_types);
+            }
+        }
+        Self {
+            format_version: FORMAT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            exported_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            files: files.iter().map(|(name, content)| (name.to_string(), sha256(content))).collect(),
+            node_types: node_types.into_iter().collect(),
+        }
+    }
+}
+
+fn sha256(content: &str) -> String {
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+/// 收集工作流文件中使用到的节点类型
+fn collect_node_types(name: &str, value: &Value, types: &mut BTreeSet<String>) {
+    if name.starts_with(SOURCE_DIR) {
+        let nodes = value.get("nodes").and_then(Value::as_array).into_iter().flatten();
+        for node in nodes.filter(|n| n.get("type").and_then(Value::as_str) != Some("trigger")) {
+            if let Some(node_type) = node.pointer("/data/nodeType").and_then(Value::as_str) {
+                types.insert(node_type.to_string());
+            }
+        }
+    } else if name.ends_with(".json") && name != MANIFEST_FILE {
+        let nodes = value.get("nodes").and_then(Value::as_object).into_iter().flatten();
+        for (_, node) in nodes {
+            if let Some(node_type) = node.get("type").and_then(Value::as_str) {
+                types.insert(node_type.to_string());
+            }
+        }
+    }
+}
+
+/// 1 -> 2：补全旧版本导出的 WorkflowData 中缺少的字段（与 WorkflowStorage.save 的默认值一致）
+fn migrate_v1(package: &mut WorkspacePackage) -> Result<(), String> {
+    for file in package.sources.iter_mut() {
+        let Some(source) = file.content.as_object_mut() else {
+            continue;
+        };
+        let trigger_type = source.get("triggerType").cloned().unwrap_or(json!(""));
+        let trigger_name = source.get("triggerName").cloned().unwrap_or(json!(""));
+        source.entry("description").or_insert(json!(""));
+        source.entry("triggerTypeLabel").or_insert(trigger_type);
+        source.entry("triggerLabel").or_insert(trigger_name);
+        source.entry("startParams").or_insert(json!({}));
+        // 旧版本只导出已启用的工作流
+        source.entry("enabled").or_insert(json!(true));
+    }
+    Ok(())
+}
+
+/// 包内的工作流文件
+#[derive(Clone)]
+pub struct WorkflowFile {
+    pub filename: String,
+    pub content: Value,
+}
+
+/// 已解析的工作区包
+#[derive(Default)]
+pub struct WorkspacePackage {
+    pub bots: Vec<Value>,
+    pub workflows: Vec<WorkflowFile>,
+    /// 可编辑的工作流数据（WorkflowData）
+    pub sources: Vec<WorkflowFile>,
+    pub manifest: Option<Manifest>,
+    /// 读取时的包格式版本（升级前）
+    pub format_version: u32,
+    pub encrypted: bool,
+}
+
+/// 导入时 ID 冲突的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MergeStrategy {
+    /// 跳过已存在的项目
+    Skip,
+    /// 覆盖已存在的项目
+    Overwrite,
+    /// 以新的 ID 导入一份副本
+    Duplicate,
+}
+
+/// 导入预览中的单个项目
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportItem {
+    pub id: String,
+    pub name: String,
+    /// 本地已存在相同 ID
+    pub conflict: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 应用后的处理结果：added、overwritten、duplicated、skipped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_id: Option<String>,
+}
+
+/// 导入预览 / 结果
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportPreview {
+    pub path: String,
+    pub bots: Vec<ImportItem>,
+    pub workflows: Vec<ImportItem>,
+    pub format_version: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_version: Option<String>,
+    /// 当前版本无法识别的节点类型
+    pub unknown_node_types: Vec<String>,
+    /// 导出时被移除、需要填写的敏感字段
+    pub secrets: Vec<SecretField>,
+    pub encrypted: bool,
+    /// 包已加密且未提供密码，此时不包含其他内容
+    pub locked: bool,
+    pub applied: bool,
+}
+
+/// 读取目录或 zip 中的所有文件
+fn read_entries(path: &Path) -> Result<Vec<(String, Vec<u8>)>, String> {
+    if !path.is_dir() {
+        let file = File::open(path).map_err(|e| format!("无法打开 {}: {}", path.display(), e))?;
+        return unzip(file);
+    }
+    let mut files = Vec::new();
+    let mut dirs = vec![(path.to_path_buf(), String::new())];
+    while let Some((dir, prefix)) = dirs.pop() {
+        let entries = fs::read_dir(&dir).map_err(|e| format!("无法读取目录 {}: {}", dir.display(), e))?;
+        for entry in entries.flatten() {
+            let file = entry.path();
+            let name = format!("{}{}", prefix, entry.file_name().to_string_lossy());
+            if file.is_dir() {
+                dirs.push((file, format!("{}/", name)));
+            } else {
+                let content = fs::read(&file).map_err(|e| format!("无法读取 {}: {}", name, e))?;
+                files.push((name, content));
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn unzip(reader: impl Read + Seek) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let mut zip = zip::ZipArchive::new(reader).map_err(|e| format!("无效的工作区包: {}", e))?;
+    let mut files = Vec::new();
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i).map_err(|e| e.to_string())?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let mut content = Vec::new();
+        entry
+            .read_to_end(&mut content)
+            .map_err(|e| format!("无法读取 {}: {}", name, e))?;
+        files.push((name, content));
+    }
+    Ok(files)
+}
+
+/// 将文件打包为 zip
+pub fn write_package<D: AsRef<[u8]>>(files: &[(&str, D)]) -> Result<Vec<u8>, String> {
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let opts = zip::write::FileOptions::default();
+    for (name, content) in files {
+        zip.start_file(*name, opts).map_err(|e| e.to_string())?;
+        zip.write_all(content.as_ref()).map_err(|e| e.to_string())?;
+    }
+    Ok(zip.finish().map_err(|e| e.to_string())?.into_inner())
+}
+
+fn str_field(value: &Value, key: &str) -> String {
+    value.get(key).and_then(Value::as_str).unwrap_or_default().to_string()
+}
+
+fn random_suffix(len: usize) -> String {
+    const CHARS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    let mut rng = rand::thread_rng();
+    (0..len).map(|_| CHARS[rng.gen_range(0..CHARS.len())] as char).collect()
+}
+
+/// 与前端 WorkflowStorage.generateId 相同格式的工作流 ID
+fn new_workflow_id() -> String {
+    format!("workflow_{}_{}", chrono::Utc::now().timestamp_millis(), random_suffix(9))
+}
+
+/// 与设置页相同格式的 Bot ID
+fn new_bot_id() -> String {
+    format!("bot-{}{}", chrono::Utc::now().timestamp_millis(), random_suffix(4))
+}
+
+/// 检查 Bot 配置
+fn check_bot(bot: &Value) -> Option<String> {
+    serde_json::from_value::<BotConfig>(bot.clone()).err().map(|e| format!("Bot 配置格式错误: {}", e))
+}
+
+/// 检查可编辑的工作流数据
+fn check_source(source: &Value) -> Option<String> {
+    if str_field(source, "id").is_empty() {
+        return Some("工作流缺少 ID".to_string());
+    }
+    if !source.get("nodes").is_some_and(Value::is_array) || !source.get("edges").is_some_and(Value::is_array) {
+        return Some("工作流缺少节点或连线数据".to_string());
+    }
+    None
+}
+
+impl WorkspacePackage {
+    /// 打开 .rfw 文件或工作区目录，加密的包需要提供密码
+    pub fn open(path: &Path, passphrase: Option<&str>) -> Result<Self, String> {
+        let mut entries = read_entries(path)?;
+        let encrypted = secret::is_encrypted(&entries);
+        if encrypted {
+            let passphrase = passphrase.filter(|p| !p.is_empty()).ok_or("工作区包已加密，请提供密码")?;
+            entries = unzip(Cursor::new(secret::decrypt(&entries, passphrase)?))?;
+        }
+        let files = entries
+            .into_iter()
+            .map(|(name, data)| match String::from_utf8(data) {
+                Ok(content) => Ok((name, content)),
+                Err(_) => Err(format!("{} 不是有效的文本文件", name)),
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let mut package = Self::from_files(files)?;
+        package.encrypted = encrypted;
+        Ok(package)
+    }
+
+    /// 是否为加密的工作区包
+    pub fn is_encrypted(path: &Path) -> Result<bool, String> {
+        Ok(secret::is_encrypted(&read_entries(path)?))
+    }
+
+    /// 从文件名与内容解析
+    pub fn from_files(files: Vec<(String, String)>) -> Result<Self, String> {
+        let mut package = Self { format_version: 1, ..Default::default() };
+        if let Some((_, content)) = files.iter().find(|(name, _)| name == MANIFEST_FILE) {
+            let manifest: Manifest =
+                serde_json::from_str(content).map_err(|e| format!("{} 格式错误: {}", MANIFEST_FILE, e))?;
+            if manifest.format_version > FORMAT_VERSION {
+                return Err(format!(
+                    "工作区包的格式版本 {} 高于当前支持的版本 {}，请升级 RenFlow（导出版本 {}）",
+                    manifest.format_version, FORMAT_VERSION, manifest.app_version
+                ));
+            }
+            for (name, hash) in &manifest.files {
+                match files.iter().find(|(file, _)| file == name) {
+                    None => return Err(format!("工作区包缺少文件: {}", name)),
+                    Some((_, content)) if !sha256(content).eq_ignore_ascii_case(hash) => {
+                        return Err(format!("文件 {} 校验失败，工作区包可能已损坏", name))
+                    }
+                    _ => {}
+                }
+            }
+            package.format_version = manifest.format_version;
+            package.manifest = Some(manifest);
+        }
+        for (name, content) in files {
+            if name == MANIFEST_FILE {
+                continue;
+            } else if name == BOTS_FILE {
+                package.bots = serde_json::from_str(&content).map_err(|e| format!("{} 格式错误: {}", BOTS_FILE, e))?;
+            } else if name.ends_with(".json") {
+                let list = match name.strip_prefix(SOURCE_DIR) {
+                    Some(rest) if !rest.contains('/') => &mut package.sources,
+                    None if !name.contains('/') => &mut package.workflows,
+                    _ => continue,
+                };
+                let content = serde_json::from_str(&content).map_err(|e| format!("{} 格式错误: {}", name, e))?;
+                list.push(WorkflowFile { filename: name, content });
+            }
+        }
+        package.workflows.sort_by(|a, b| a.filename.cmp(&b.filename));
+        package.sources.sort_by(|a, b| a.filename.cmp(&b.filename));
+        package.migrate()?;
+        Ok(package)
+    }
+
+    /// 逐步升级到当前的包格式版本
+    fn migrate(&mut self) -> Result<(), String> {
+        let mut version = self.format_version;
+        while version < FORMAT_VERSION {
+            let (_, migration) = MIGRATIONS
+                .iter()
+                .find(|(from, _)| *from == version)
+                .ok_or(format!("不支持的工作区包格式版本: {}", version))?;
+            migration(self).map_err(|e| format!("升级工作区包（{} -> {}）失败: {}", version, version + 1, e))?;
+            version += 1;
+        }
+        Ok(())
+    }
+
+    /// 包中使用到的节点类型
+    pub fn node_types(&self) -> Vec<String> {
+        let mut types = BTreeSet::new();
+        for file in self.workflows.iter().chain(&self.sources) {
+            collect_node_types(&file.filename, &file.content, &mut types);
+        }
+        types.into_iter().collect()
+    }
+
+    /// 包中使用到、但不在 `known` 中的节点类型
+    pub fn unknown_node_types(&self, known: &HashSet<String>) -> Vec<String> {
+        self.node_types().into_iter().filter(|t| !known.contains(t)).collect()
+    }
+
+    /// 生成导入预览，`bots` 与 `workflows` 为本地已有的数据
+    pub fn preview(&self, bots: &[Value], workflows: &[Value]) -> ImportPreview {
+        let bot_ids: HashSet<String> = bots.iter().map(|b| str_field(b, "id")).collect();
+        let workflow_ids: HashSet<String> = workflows.iter().map(|w| str_field(w, "id")).collect();
+
+        let bots = self
+            .bots
+            .iter()
+            .map(|bot| {
+                let id = str_field(bot, "id");
+                ImportItem {
+                    conflict: bot_ids.contains(&id),
+                    name: str_field(bot, "name"),
+                    error: check_bot(bot),
+                    action: None,
+                    new_id: None,
+                    id,
+                }
+            })
+            .collect();
+
+        let mut items: Vec<ImportItem> = self
+            .sources
+            .iter()
+            .map(|file| {
+                let id = str_field(&file.content, "id");
+                ImportItem {
+                    conflict: workflow_ids.contains(&id),
+                    name: str_field(&file.content, "name"),
+                    error: check_source(&file.content),
+                    action: None,
+                    new_id: None,
+                    id,
+                }
+            })
+            .collect();
+        // 只有执行数据的工作流无法还原为可编辑的工作流
+        for file in &self.workflows {
+            let id = str_field(&file.content, "id");
+            if items.iter().any(|item| item.id == id) {
+                continue;
+            }
+            let error = match serde_json::from_value::<WorkflowExecution>(file.content.clone()) {
+                Ok(_) => "包中缺少可编辑的工作流数据，请使用新版本重新导出".to_string(),
+                Err(err) => format!("{} 格式错误: {}", file.filename, err),
+            };
+            items.push(ImportItem {
+                conflict: workflow_ids.contains(&id),
+                name: str_field(&file.content, "name"),
+                error: Some(error),
+                action: None,
+                new_id: None,
+                id,
+            });
+        }
+
+        ImportPreview {
+            bots,
+            workflows: items,
+            format_version: self.format_version,
+            app_version: self.manifest.as_ref().map(|m| m.app_version.clone()),
+            secrets: self.secret_fields(),
+            encrypted: self.encrypted,
+            ..Default::default()
+        }
+    }
+
+    /// 按冲突处理方式合并到本地数据中，返回处理结果
+    pub fn merge(&self, strategy: MergeStrategy, bots: &mut Vec<Value>, workflows: &mut Vec<Value>) -> ImportPreview {
+        let mut preview = self.preview(bots, workflows);
+        for (item, bot) in preview.bots.iter_mut().zip(&self.bots) {
+            if item.error.is_none() {
+                merge_item(item, bot.clone(), strategy, bots, new_bot_id);
+            }
+        }
+        for item in preview.workflows.iter_mut().filter(|item| item.error.is_none()) {
+            let Some(source) = self.sources.iter().find(|s| str_field(&s.content, "id") == item.id) else {
+                continue;
+            };
+            merge_item(item, source.content.clone(), strategy, workflows, new_workflow_id);
+        }
+        preview.applied = true;
+        preview
+    }
+}
+
+fn merge_item(
+    item: &mut ImportItem,
+    mut value: Value,
+    strategy: MergeStrategy,
+    list: &mut Vec<Value>,
+    new_id: fn() -> String,
+) {
+    let index = list.iter().position(|v| str_field(v, "id") == item.id);
+    match (index, strategy) {
+        (None, _) => {
+            list.push(value);
+            item.action = Some("added");
+        }
+        (Some(_), MergeStrategy::Skip) => item.action = Some("skipped"),
+        (Some(index), MergeStrategy::Overwrite) => {
+            list[index] = value;
+            item.action = Some("overwritten");
+        }
+        (Some(_), MergeStrategy::Duplicate) => {
+            let id = new_id();
+            value["id"] = Value::String(id.clone());
+            list.push(value);
+            item.action = Some("duplicated");
+            item.new_id = Some(id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn package() -> WorkspacePackage {
+        let source = json!({ "id": "wf1", "name": "导入", "nodes": [], "edges": [] });
+        WorkspacePackage::from_files(vec![
+            (BOTS_FILE.to_string(), json!([{ "id": "bot1", "name": "Bot", "type": "napcat", "address": "ws://127.0.0.1:3001" }]).to_string()),
+            ("wf1.json".to_string(), json!({ "id": "wf1", "entryNode": null }).to_string()),
+            ("wf2.json".to_string(), json!({ "id": "wf2", "entryNode": null }).to_string()),
+            (format!("{}wf1.json", SOURCE_DIR), source.to_string()),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn checks_manifest_and_migrates() {
+        let package = package();
+        assert_eq!(package.format_version, 1);
+        // 旧版本的工作流数据会补全默认字段
+        assert_eq!(package.sources[0].content["enabled"], true);
+
+        let bots = json!([]).to_string();
+        let workflow = json!({ "id": "wf1", "entryNode": "a", "nodes": { "a": { "id": "a", "type": "console" } } }).to_string();
+        let manifest = Manifest::build(&[(BOTS_FILE, &bots), ("wf1.json", &workflow)]);
+        assert_eq!(manifest.node_types, vec!["console"]);
+        let files = |manifest: &Manifest, workflow: &str| {
+            vec![
+                (MANIFEST_FILE.to_string(), serde_json::to_string(manifest).unwrap()),
+                (BOTS_FILE.to_string(), bots.clone()),
+                ("wf1.json".to_string(), workflow.to_string()),
+            ]
+        };
+
+        let package = WorkspacePackage::from_files(files(&manifest, &workflow)).unwrap();
+        assert_eq!(package.format_version, FORMAT_VERSION);
+        assert_eq!(package.unknown_node_types(&HashSet::new()), vec!["console"]);
+        assert!(WorkspacePackage::from_files(files(&manifest, "{}")).is_err());
+
+        let mut newer = manifest.clone();
+        newer.format_version = FORMAT_VERSION + 1;
+        assert!(WorkspacePackage::from_files(files(&newer, &workflow)).is_err());
+    }
+
+    #[test]
+    fn merges_with_strategy() {
+        let package = package();
+        let local_bots = vec![json!({ "id": "bot1", "name": "本地" })];
+        let local_workflows = vec![json!({ "id": "wf1", "name": "本地" })];
+
+        let preview = package.preview(&local_bots, &local_workflows);
+        assert!(preview.bots[0].conflict);
+        assert!(preview.workflows[0].conflict && preview.workflows[0].error.is_none());
+        // 没有可编辑数据的工作流不能导入
+        assert!(preview.workflows[1].error.is_some());
+
+        let (mut bots, mut workflows) = (local_bots.clone(), local_workflows.clone());
+        let result = package.merge(MergeStrategy::Skip, &mut bots, &mut workflows);
+        assert_eq!(result.workflows[0].action, Some("skipped"));
+        assert_eq!(workflows, local_workflows);
+
+        let result = package.merge(MergeStrategy::Overwrite, &mut bots, &mut workflows);
+        assert_eq!(result.bots[0].action, Some("overwritten"));
+        assert_eq!(workflows.len(), 1);
+        assert_eq!(workflows[0]["name"], "导入");
+
+        let result = package.merge(MergeStrategy::Duplicate, &mut bots, &mut workflows);
+        let new_id = result.workflows[0].new_id.clone().unwrap();
+        assert_eq!(workflows.len(), 2);
+        assert_eq!(workflows[1]["id"], new_id.as_str());
+        assert!(new_id.starts_with("workflow_"));
+    }
+}
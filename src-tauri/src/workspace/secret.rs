@@ -0,0 +1,316 @@
+//! 工作区包中的敏感信息
+//!
+//! 导出时可以把 Bot 的 token / secret 以及 HTTP 节点中的鉴权请求头替换为占位符，导入时再填写；
+//! 也可以使用密码加密整个包（Argon2id 派生密钥，XChaCha20-Poly1305 加密）。
+
+use std::collections::HashMap;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{str_field, WorkspacePackage, SOURCE_DIR};
+
+/// 被移除的敏感信息在包中的占位符
+pub const SECRET_PLACEHOLDER: &str = "$RENFLOW_SECRET";
+/// 加密包中的加密参数
+pub const ENCRYPTION_FILE: &str = "encryption.json";
+/// 加密包中的密文
+pub const PAYLOAD_FILE: &str = "package.bin";
+
+const ENCRYPTION_FORMAT: &str = "renflow-encrypted";
+const BOT_SECRET_FIELDS: &[&str] = &["token", "secret"];
+
+/// 导出方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportMode {
+    /// 原样导出
+    #[default]
+    Plain,
+    /// 移除敏感信息，导入时填写
+    Strip,
+    /// 使用密码加密整个包
+    Encrypt,
+}
+
+/// 需要在导入时填写的敏感字段
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretField {
+    /// 唯一标识，导入时以此提交填写的值
+    pub key: String,
+    /// bot 或 workflow
+    pub target: &'static str,
+    pub id: String,
+    pub name: String,
+    pub field: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EncryptionInfo {
+    format: String,
+    kdf: String,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+    salt: String,
+    cipher: String,
+    nonce: String,
+}
+
+/// 需要移除的请求头（鉴权、Cookie 以及名称中带有 token / secret / key 的头）
+fn is_secret_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    matches!(name.as_str(), "authorization" | "proxy-authorization" | "cookie")
+        || ["token", "secret", "api-key", "apikey", "password"].iter().any(|k| name.contains(k))
+}
+
+/// 遍历节点参数中的敏感请求头，headers 可能是 JSON 字符串或对象
+fn visit_headers(params: &mut Value, mut f: impl FnMut(&str, &mut Value)) {
+    let Some(headers) = params.get_mut("headers") else {
+        return;
+    };
+    let mut parsed = match headers {
+        Value::String(text) => match serde_json::from_str::<Value>(text) {
+            Ok(value) if value.is_object() => value,
+            _ => return,
+        },
+        Value::Object(_) => headers.take(),
+        _ => return,
+    };
+    let before = parsed.clone();
+    for (name, value) in parsed.as_object_mut().into_iter().flatten() {
+        if is_secret_header(name) {
+            f(name, value);
+        }
+    }
+    match headers {
+        Value::String(text) => {
+            if parsed != before {
+                *text = serde_json::to_string(&parsed).unwrap_or_default();
+            }
+        }
+        _ => *headers = parsed,
+    }
+}
+
+/// 遍历 Bot 配置中的敏感字段
+fn visit_bot(bot: &mut Value, f: &mut dyn FnMut(SecretField, &mut Value)) {
+    let id = str_field(bot, "id");
+    let name = str_field(bot, "name");
+    for field in BOT_SECRET_FIELDS {
+        if let Some(value) = bot.get_mut(*field) {
+            let key = format!("bot:{}:{}", id, field);
+            f(
+                SecretField { key, target: "bot", id: id.clone(), name: name.clone(), field: field.to_string() },
+                value,
+            );
+        }
+    }
+}
+
+/// 遍历工作流（执行数据或可编辑数据）中的敏感字段
+fn visit_workflow(filename: &str, workflow: &mut Value, f: &mut dyn FnMut(SecretField, &mut Value)) {
+    let id = str_field(workflow, "id");
+    let name = str_field(workflow, "name");
+    let nodes: Vec<(String, &mut Value)> = if filename.starts_with(SOURCE_DIR) {
+        let nodes = workflow.get_mut("nodes").and_then(Value::as_array_mut).into_iter().flatten();
+        nodes
+            .filter_map(|node| {
+                let node_id = str_field(node, "id");
+                node.pointer_mut("/data/params").map(|params| (node_id, params))
+            })
+            .collect()
+    } else {
+        let nodes = workflow.get_mut("nodes").and_then(Value::as_object_mut).into_iter().flatten();
+        nodes.filter_map(|(node_id, node)| node.get_mut("params").map(|p| (node_id.clone(), p))).collect()
+    };
+    for (node_id, params) in nodes {
+        visit_headers(params, |header, value| {
+            let field = format!("{}:headers.{}", node_id, header);
+            let key = format!("workflow:{}:{}", id, field);
+            f(SecretField { key, target: "workflow", id: id.clone(), name: name.clone(), field }, value);
+        });
+    }
+}
+
+fn strip(_: SecretField, value: &mut Value) {
+    if value.as_str().is_some_and(|v| !v.is_empty()) {
+        *value = Value::String(SECRET_PLACEHOLDER.to_string());
+    }
+}
+
+/// 导出前移除 Bot 配置中的敏感信息
+pub fn strip_bot(bot: &mut Value) {
+    visit_bot(bot, &mut strip);
+}
+
+/// 导出前移除工作流文件中的敏感信息
+pub fn strip_workflow(filename: &str, workflow: &mut Value) {
+    visit_workflow(filename, workflow, &mut strip);
+}
+
+impl WorkspacePackage {
+    fn visit_secrets(&mut self, f: &mut dyn FnMut(SecretField, &mut Value)) {
+        for bot in self.bots.iter_mut() {
+            visit_bot(bot, f);
+        }
+        for file in self.workflows.iter_mut().chain(self.sources.iter_mut()) {
+            visit_workflow(&file.filename, &mut file.content, f);
+        }
+    }
+
+    /// 包中以占位符代替、需要填写的敏感字段
+    pub fn secret_fields(&self) -> Vec<SecretField> {
+        let mut fields: Vec<SecretField> = Vec::new();
+        let mut package = Self {
+            bots: self.bots.clone(),
+            workflows: self.workflows.clone(),
+            sources: self.sources.clone(),
+            ..Default::default()
+        };
+        package.visit_secrets(&mut |field, value| {
+            if value.as_str() == Some(SECRET_PLACEHOLDER) && !fields.iter().any(|f| f.key == field.key) {
+                fields.push(field);
+            }
+        });
+        fields
+    }
+
+    /// 用填写的值替换占位符，未填写的字段置空
+    pub fn fill_secrets(&mut self, values: &HashMap<String, String>) {
+        self.visit_secrets(&mut |field, value| {
+            if value.as_str() == Some(SECRET_PLACEHOLDER) {
+                *value = Value::String(values.get(&field.key).cloned().unwrap_or_default());
+            }
+        });
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], params: Params) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("密钥派生失败: {}", e))?;
+    Ok(key)
+}
+
+/// 是否为加密的工作区包
+pub fn is_encrypted(entries: &[(String, Vec<u8>)]) -> bool {
+    entries.iter().any(|(name, _)| name == ENCRYPTION_FILE)
+}
+
+/// 加密工作区包，返回加密后包中的文件
+pub fn encrypt(package: &[u8], passphrase: &str) -> Result<Vec<(&'static str, Vec<u8>)>, String> {
+    if passphrase.is_empty() {
+        return Err("加密导出需要设置密码".to_string());
+    }
+    let mut rng = rand::thread_rng();
+    let mut salt = [0u8; 16];
+    let mut nonce = [0u8; 24];
+    rng.fill_bytes(&mut salt);
+    rng.fill_bytes(&mut nonce);
+
+    let params = Params::default();
+    let key = derive_key(passphrase, &salt, params.clone())?;
+    let cipher = XChaCha20Poly1305::new(&key.into());
+    let data = cipher
+        .encrypt(XNonce::from_slice(&nonce), package)
+        .map_err(|_| "加密工作区包失败".to_string())?;
+
+    let info = EncryptionInfo {
+        format: ENCRYPTION_FORMAT.to_string(),
+        kdf: "argon2id".to_string(),
+        m_cost: params.m_cost(),
+        t_cost: params.t_cost(),
+        p_cost: params.p_cost(),
+        salt: hex::encode(salt),
+        cipher: "xchacha20poly1305".to_string(),
+        nonce: hex::encode(nonce),
+    };
+    let info = serde_json::to_vec_pretty(&info).map_err(|e| e.to_string())?;
+    Ok(vec![(ENCRYPTION_FILE, info), (PAYLOAD_FILE, data)])
+}
+
+/// 解密工作区包，返回原始的包数据
+pub fn decrypt(entries: &[(String, Vec<u8>)], passphrase: &str) -> Result<Vec<u8>, String> {
+    let find = |name: &str| entries.iter().find(|(n, _)| n == name).map(|(_, data)| data);
+    let info: EncryptionInfo = find(ENCRYPTION_FILE)
+        .map(|data| serde_json::from_slice(data))
+        .transpose()
+        .map_err(|e| format!("{} 格式错误: {}", ENCRYPTION_FILE, e))?
+        .ok_or("工作区包未加密")?;
+    if info.format != ENCRYPTION_FORMAT || info.kdf != "argon2id" || info.cipher != "xchacha20poly1305" {
+        return Err("不支持的加密方式".to_string());
+    }
+    let data = find(PAYLOAD_FILE).ok_or(format!("工作区包缺少文件: {}", PAYLOAD_FILE))?;
+    let salt = hex::decode(&info.salt).map_err(|e| e.to_string())?;
+    let nonce = hex::decode(&info.nonce).map_err(|e| e.to_string())?;
+    if nonce.len() != 24 {
+        return Err("不支持的加密方式".to_string());
+    }
+
+    // 参数来自包内文件，超过导出时使用的默认参数的包可能会占用大量内存或时间
+    let limit = Params::default();
+    if info.m_cost > limit.m_cost() || info.t_cost > limit.t_cost() || info.p_cost > limit.p_cost() {
+        return Err("不支持的加密参数".to_string());
+    }
+    let params = Params::new(info.m_cost, info.t_cost, info.p_cost, Some(32)).map_err(|e| e.to_string())?;
+    let key = derive_key(passphrase, &salt, params)?;
+    let cipher = XChaCha20Poly1305::new(&key.into());
+    cipher
+        .decrypt(XNonce::from_slice(&nonce), data.as_slice())
+        .map_err(|_| "密码错误或工作区包已损坏".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::workspace::{WorkspacePackage, BOTS_FILE};
+    use serde_json::json;
+
+    #[test]
+    fn strips_and_fills_secrets() {
+        let mut bot = json!({ "id": "bot1", "type": "napcat", "address": "ws://127.0.0.1", "token": "abc" });
+        let mut workflow = json!({ "id": "wf1", "nodes": { "http": { "id": "http", "type": "http-request", "params": {
+            "headers": "{\"Authorization\":\"Bearer abc\",\"Accept\":\"*/*\"}"
+        } } } });
+        strip_bot(&mut bot);
+        strip_workflow("wf1.json", &mut workflow);
+        assert_eq!(bot["token"], SECRET_PLACEHOLDER);
+        let headers: Value = serde_json::from_str(workflow["nodes"]["http"]["params"]["headers"].as_str().unwrap()).unwrap();
+        assert_eq!(headers["Authorization"], SECRET_PLACEHOLDER);
+        assert_eq!(headers["Accept"], "*/*");
+
+        let mut package = WorkspacePackage::from_files(vec![
+            (BOTS_FILE.to_string(), json!([bot]).to_string()),
+            ("wf1.json".to_string(), workflow.to_string()),
+        ])
+        .unwrap();
+        let keys: Vec<String> = package.secret_fields().into_iter().map(|f| f.key).collect();
+        assert_eq!(keys, vec!["bot:bot1:token", "workflow:wf1:http:headers.Authorization"]);
+
+        package.fill_secrets(&HashMap::from([("bot:bot1:token".to_string(), "new".to_string())]));
+        assert_eq!(package.bots[0]["token"], "new");
+        assert!(package.secret_fields().is_empty());
+    }
+
+    #[test]
+    fn encrypts_with_passphrase() {
+        let files = encrypt(b"package", "passphrase").unwrap();
+        let entries: Vec<(String, Vec<u8>)> = files.into_iter().map(|(n, d)| (n.to_string(), d)).collect();
+        assert!(is_encrypted(&entries));
+        assert_eq!(decrypt(&entries, "passphrase").unwrap(), b"package");
+        assert!(decrypt(&entries, "wrong").is_err());
+
+        let mut info: Value = serde_json::from_slice(&entries[0].1).unwrap();
+        info["mCost"] = json!(4 * 1024 * 1024);
+        let crafted = vec![(ENCRYPTION_FILE.to_string(), info.to_string().into_bytes()), entries[1].clone()];
+        assert_eq!(decrypt(&crafted, "passphrase"), Err("不支持的加密参数".to_string()));
+    }
+}
@@ -12,11 +12,14 @@ export const backend = reactive({
     release: '',
     arch: '' as string | undefined,
     proxy: undefined as number | undefined,
+    proxyToken: '',
+    // 改写后的页面中使用的只读令牌，页面脚本可以读取，不能使用完整权限的令牌
+    proxyPageToken: '',
 
     function: undefined as {
         invoke: <T>(cmd: string, args?: InvokeArgs, options?: InvokeOptions) => Promise<T>
     },
-    listener: undefined as ((event: string, ...args: any[]) => void) | undefined,
+    listener: undefined as ((event: string, ...args: any[]) => Promise<() => void>) | undefined,
 
     isDesktop() {
         return this.type == 'tauri'
@@ -28,11 +31,19 @@ export const backend = reactive({
     /**
      * 代理 URL 转换
      * @param url 需要转换的 URL
+     * @param options.rewrite 改写页面中的资源引用，用于预览外部页面
+     * @param options.scripts 改写时是否保留页面脚本，默认保留
      * @returns 转换后的 URL
      */
-    proxyUrl(url: string) {
+    proxyUrl(url: string, options?: { rewrite?: boolean, scripts?: boolean }) {
         if (this.proxy && url && url.startsWith('http')) {
-            return `http://localhost:${this.proxy}/proxy?url=${encodeURIComponent(url)}`
+            const token = options?.rewrite ? this.proxyPageToken : this.proxyToken
+            let proxyUrl = `http://localhost:${this.proxy}/proxy?url=${encodeURIComponent(url)}&token=${token}`
+            if (options?.rewrite) {
+                proxyUrl += '&rewrite=1'
+                if (options.scripts === false) proxyUrl += '&scripts=0'
+            }
+            return proxyUrl
         } else {
             return url
         }
@@ -97,7 +108,18 @@ export const backend = reactive({
             }
             this.release = `${os} ${version} (Web)`
         }
-        this.proxy  = await this.call('sys:runProxy')
+        const proxyInfo = await this.call('sys:runProxy')
+        this.proxy = proxyInfo?.port
+        this.proxyToken = proxyInfo?.token || ''
+        this.proxyPageToken = proxyInfo?.pageToken || ''
+        if (this.isDesktop()) {
+            // 代理重启后端口可能变化
+            this.addListener('sys:proxyPort', (event: any) => {
+                this.proxy = event.payload?.port ?? undefined
+                this.proxyToken = event.payload?.token || this.proxyToken
+                this.proxyPageToken = event.payload?.pageToken || this.proxyPageToken
+            })
+        }
         if(this.type == 'tauri' && !this.proxy) {
             logger.error(null, 'Tauri 代理服务似乎没有正常启动，此服务异常将会影响应用内的大部分外部资源的加载。')
             toast.error('Tauri 代理服务似乎没有正常启动')
@@ -151,13 +173,13 @@ export const backend = reactive({
      * @param name 事件名称
      * @param callBack 回调函数
      */
-    addListener(name: string, callBack: (...args: any[]) => void) {
+    addListener(name: string, callBack: (...args: any[]) => void): Promise<() => void> | undefined {
         if(this.listener) {
             if(this.isDesktop()) {
-                this.listener(name, callBack)
-                return
+                return this.listener(name, callBack)
             }
         }
         logger.error(null, `添加后端监听失败：${name}`)
+        return undefined
     },
 })
@@ -0,0 +1,69 @@
+import { RemoteBotAdapter } from 'renflow.runner'
+import { backend } from '@app/functions/backend'
+
+export interface BotConfig {
+    id: string
+    name?: string
+    type: string
+    address: string
+    token?: string
+    listen?: string
+    secret?: string
+}
+
+export interface BackendBot {
+    adapter: RemoteBotAdapter
+    /** 取消事件订阅，后端的连接保持不变 */
+    dispose: () => void
+}
+
+/**
+ * 创建由后端维持连接的 Bot 适配器（仅桌面模式）
+ *
+ * 连接、重连与 API 的 echo 匹配都在后端完成，事件通过 bot:* 推送
+ * @param config Bot 配置
+ * @returns 适配器与取消订阅的方法，不再使用时需要调用 dispose，否则事件会被重复处理
+ */
+export function createBackendBot(config: BotConfig): BackendBot {
+    const adapter = new RemoteBotAdapter(config.id, {
+        async connect() {
+            // 窗口刷新后后端的连接仍然存在，已连接时直接复用
+            const list: { id: string, connected: boolean }[] = await backend.call('bot:list') || []
+            const exist = list.find(item => item.id === config.id)
+            if (exist?.connected) return
+            if (exist) await backend.call('bot:disconnect', config.id)
+            // 后端命令失败时 backend.call 返回 undefined
+            const result = await backend.call('bot:connect', { data: config })
+            if (result === undefined) throw new Error(`适配器连接失败: ${config.id}`)
+        },
+        async disconnect() {
+            await backend.call('bot:disconnect', config.id)
+        },
+        async callApi(action: string, params: any, timeout?: number) {
+            const result = await backend.call('bot:callApi', { data: { id: config.id, action, params, timeout } })
+            if (result === undefined) throw new Error(`调用 Bot API 失败: ${action}`)
+            return result
+        },
+    })
+
+    const listeners: (Promise<() => void> | undefined)[] = []
+    const forward = (name: string, event: string) => {
+        listeners.push(backend.addListener(name, (evt: any) => {
+            const payload = evt?.payload || {}
+            // 反向连接中接入的 Bot 以 <id>:<self_id> 推送事件
+            if (payload.id !== config.id && !String(payload.id).startsWith(`${config.id}:`)) return
+            if (payload.id !== config.id && (event === 'connected' || event === 'disconnected')) return
+            adapter.dispatch(event, event.startsWith('message') ? payload.data : payload)
+        }))
+    }
+    forward('bot:connected', 'connected')
+    forward('bot:disconnected', 'disconnected')
+    forward('bot:error', 'error')
+    forward('bot:message', 'message')
+    forward('bot:messageMine', 'message_mine')
+
+    return {
+        adapter,
+        dispose: () => listeners.forEach(listener => listener?.then(unlisten => unlisten())),
+    }
+}
@@ -7,6 +7,12 @@ export interface ConfirmOptions {
     cancelText?: string
 }
 
+export interface PromptOptions extends ConfirmOptions {
+    placeholder?: string
+    /** 输入框类型，例如 password */
+    type?: string
+}
+
 type Resolver = (value: boolean) => void
 
 export const confirmState = reactive({
@@ -15,10 +21,13 @@ export const confirmState = reactive({
     message: '',
     confirmText: '确认',
     cancelText: '取消',
+    input: null as { placeholder: string, type: string } | null,
+    value: '',
     _resolver: null as Resolver | null,
 })
 
 export function confirm(options: ConfirmOptions): Promise<boolean> {
+    confirmState.input = null
     confirmState.title = options.title || ''
     confirmState.message = options.message
     confirmState.confirmText = options.confirmText || '确认'
@@ -32,6 +41,17 @@ export function confirm(options: ConfirmOptions): Promise<boolean> {
     })
 }
 
+/**
+ * 带输入框的确认弹窗
+ * @returns 输入的内容，取消时返回 null
+ */
+export async function prompt(options: PromptOptions): Promise<string | null> {
+    const ok = confirm(options)
+    confirmState.input = { placeholder: options.placeholder || '', type: options.type || 'text' }
+    confirmState.value = ''
+    return await ok ? confirmState.value : null
+}
+
 export function resolveConfirm(value: boolean) {
     try {
         if (confirmState._resolver) confirmState._resolver(value)
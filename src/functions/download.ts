@@ -0,0 +1,93 @@
+/**
+ * 下载管理
+ * 下载在后端进行，进度与状态通过 sys:downloadProgress / sys:downloadState 事件推送，事件中带有下载 ID
+ */
+
+import { backend } from './backend'
+
+export interface DownloadOptions {
+    url: string
+    fileName?: string               // 未设置时从 URL 中获取
+    dir?: string                    // 未设置时使用默认下载目录
+    /** 文件校验值，如 sha256:<hex> 或 sha1:<hex> */
+    checksum?: string
+    overwrite?: boolean             // 覆盖同名文件，否则自动追加序号
+}
+
+export type DownloadState = 'queued' | 'downloading' | 'paused' | 'completed' | 'failed' | 'cancelled'
+
+export interface DownloadInfo {
+    id: string
+    url: string
+    path: string
+    state: DownloadState
+    loaded: number
+    total: number | null            // 服务器未返回长度时为 null
+    error: string | null
+    createdAt: number
+}
+
+export interface DownloadProgress {
+    id: string
+    lengthComputable: boolean
+    loaded: number
+    total: number | null
+}
+
+/**
+ * 开始下载，仅桌面端可用
+ * @param options 下载选项
+ * @param ask 是否先让用户选择保存目录
+ * @returns 下载信息，用户取消选择目录时返回 null
+ */
+export async function download(options: DownloadOptions, ask = false): Promise<DownloadInfo | null> {
+    if (!backend.isDesktop()) throw new Error('下载仅支持桌面端')
+    const info = await backend.call('sys:download', { data: options, ask })
+    // 后端命令失败时 backend.call 返回 undefined
+    if (info === undefined) throw new Error(`下载失败: ${options.url}`)
+    return info
+}
+
+export async function pauseDownload(id: string): Promise<boolean> {
+    return await backend.call('sys:pauseDownload', id) !== undefined
+}
+
+export async function resumeDownload(id: string): Promise<boolean> {
+    return await backend.call('sys:resumeDownload', id) !== undefined
+}
+
+export async function cancelDownload(id: string): Promise<boolean> {
+    return await backend.call('sys:cancelDownload', id) !== undefined
+}
+
+export async function listDownloads(): Promise<DownloadInfo[]> {
+    if (!backend.isDesktop()) return []
+    return await backend.call('sys:listDownloads') || []
+}
+
+/**
+ * 监听指定下载的进度与状态，下载完成、失败或取消后自动停止监听
+ * @param id 下载 ID
+ * @param onProgress 进度回调
+ * @param onState 状态变化回调
+ * @returns 提前停止监听的函数
+ */
+export function watchDownload(id: string, onProgress: (progress: DownloadProgress) => void, onState?: (info: DownloadInfo) => void): () => void {
+    const listeners = [
+        backend.addListener('sys:downloadProgress', (event: any) => {
+            if (event.payload?.id === id) onProgress(event.payload)
+        }),
+        backend.addListener('sys:downloadState', (event: any) => {
+            if (event.payload?.id !== id) return
+            onState?.(event.payload)
+            if (['completed', 'failed', 'cancelled'].includes(event.payload.state)) unwatch()
+        }),
+    ]
+    let stopped = false
+    const unwatch = () => {
+        if (stopped) return
+        stopped = true
+        listeners.forEach(listener => listener?.then(unlisten => unlisten()))
+    }
+    return unwatch
+}
@@ -55,6 +55,8 @@ export interface NotifyInfo {
     image?: string
     type: string
     is_important: boolean
+    /** 发出通知的 Bot ID，回复消息通知时由它发送 */
+    bot?: string
 }
 
 export interface NoticeBodyV3 {
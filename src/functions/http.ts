@@ -0,0 +1,127 @@
+/**
+ * 通用 HTTP 请求
+ * 桌面端由后端发起请求，不受 CORS 限制，可以设置任意请求头
+ */
+
+import type { HttpTransport } from 'renflow.runner'
+import { backend } from './backend'
+
+export type HttpRequestBody =
+    | { type: 'text', content: string, contentType?: string }
+    | { type: 'json', content: any }
+    | { type: 'form', fields: { [key: string]: any } }
+    | { type: 'multipart', parts: HttpMultipartPart[] }
+    /** base64 编码的二进制数据 */
+    | { type: 'binary', content: string, contentType?: string }
+
+export interface HttpMultipartPart {
+    name: string
+    value?: string                  // 文本字段的值
+    base64?: string                 // 文件字段的 base64 内容
+    fileName?: string
+    contentType?: string
+}
+
+export interface HttpRequestOptions {
+    /** 用于取消请求的 ID */
+    requestId?: string
+    method?: string
+    url: string
+    headers?: { [key: string]: any }
+    /** 查询参数，数组值会生成多个同名参数 */
+    query?: { [key: string]: any }
+    body?: HttpRequestBody
+    /** 单次请求的超时时间（毫秒） */
+    timeout?: number
+    /** 连接失败、超时、429 和 5xx 时的重试次数 */
+    retries?: number
+    /** 首次重试前的等待时间（毫秒），之后每次翻倍 */
+    retryDelay?: number
+    redirect?: 'follow' | 'manual' | 'error'
+    maxRedirects?: number
+    /** auto 会按 Content-Type 返回对象、字符串或 base64 */
+    responseType?: 'auto' | 'text' | 'json' | 'base64'
+    /** 跳过 TLS 证书校验 */
+    insecure?: boolean
+}
+
+export interface HttpResponse {
+    status: number
+    statusText: string
+    url: string                     // 重定向后的最终地址
+    headers: { [key: string]: string }
+    body: any
+    attempts: number                // 实际请求次数（含重试）
+    timing: {
+        headers: number             // 收到响应头的耗时（毫秒）
+        total: number               // 总耗时（毫秒）
+    }
+}
+
+/**
+ * 发送 HTTP 请求，仅桌面端可用
+ * @param options 请求选项
+ * @returns 响应
+ */
+export async function httpRequest(options: HttpRequestOptions): Promise<HttpResponse> {
+    if (!backend.isDesktop()) throw new Error('HTTP 请求仅支持桌面端')
+    const response = await backend.call('sys:httpRequest', { data: options })
+    // 后端命令失败时 backend.call 返回 undefined
+    if (response === undefined) throw new Error(`请求失败: ${options.url}`)
+    return response
+}
+
+/**
+ * 工作流中 HTTP 请求节点使用的传输层，仅桌面端可用
+ */
+export const httpTransport: HttpTransport = {
+    request: (options) => httpRequest(options),
+}
+
+/**
+ * 取消带有 requestId 的请求
+ * @param requestId 请求 ID
+ * @returns 请求是否仍在进行
+ */
+export async function cancelHttpRequest(requestId: string): Promise<boolean> {
+    if (!backend.isDesktop()) return false
+    return await backend.call('sys:cancelHttpRequest', requestId) || false
+}
+
+export interface RedirectOptions {
+    url: string
+    maxHops?: number                // 最多跟随的跳数，默认 20
+    timeout?: number                // 整个解析过程的超时时间（毫秒），默认 10 秒
+    followMetaRefresh?: boolean
+    followCanonical?: boolean
+}
+
+export interface RedirectHop {
+    url: string
+    status: number
+    location: string | null         // 跳转到的下一个地址，最后一跳为 null
+    kind: 'http' | 'metaRefresh' | 'canonical' | null
+    downgrade: boolean              // 从 https 跳转到了 http
+}
+
+export interface RedirectChain {
+    url: string
+    finalUrl: string
+    hops: RedirectHop[]
+    loopDetected: boolean
+    downgraded: boolean
+    maxHopsReached: boolean
+    error: string | null            // 中途请求失败的原因
+}
+
+/**
+ * 解析完整的重定向链，仅桌面端可用
+ * @param options 解析选项
+ * @returns 重定向链
+ */
+export async function resolveRedirects(options: RedirectOptions): Promise<RedirectChain> {
+    if (!backend.isDesktop()) throw new Error('重定向解析仅支持桌面端')
+    const chain = await backend.call('sys:resolveRedirects', { data: options })
+    if (chain === undefined) throw new Error(`解析重定向失败: ${options.url}`)
+    return chain
+}
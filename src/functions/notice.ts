@@ -0,0 +1,31 @@
+/**
+ * 系统通知
+ * 桌面端由后端发送，消息通知带有回复输入框
+ */
+
+import type { NoticeTransport } from 'renflow.runner'
+import type { NotifyInfo } from './elements/system'
+import { backend } from './backend'
+
+/**
+ * 发送系统通知
+ * @param info 通知信息，消息通知需要带上 bot，回复时由它发送
+ * @returns 是否发送成功
+ */
+export async function sendNotice(info: NotifyInfo): Promise<boolean> {
+    if (!backend.isDesktop()) return false
+    // 失败时 backend.call 返回 undefined，成功时为 null
+    return await backend.call('sys:sendNotice', { data: info }) !== undefined
+}
+
+/**
+ * 供 renflow.runner 发送通知节点使用的传输层
+ */
+export const noticeTransport: NoticeTransport = {
+    send: (options) => sendNotice({
+        base_type: 'msg',
+        icon: '',
+        is_important: false,
+        ...options,
+    }),
+}
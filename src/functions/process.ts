@@ -0,0 +1,71 @@
+/**
+ * 外部程序执行
+ * 只能运行用户在设置中加入白名单的程序，仅桌面端可用
+ */
+
+import { backend } from './backend'
+
+export interface ProcessOptions {
+    program: string                 // 程序名或完整路径
+    args?: string[]
+    env?: { [key: string]: string } // 追加的环境变量
+    cwd?: string
+    stdin?: string
+    timeout?: number                // 超时时间（毫秒），默认 60 秒
+    maxOutput?: number              // stdout 与 stderr 各自保留的最大字节数，默认 1 MB
+    /** 设置后按行推送 sys:processOutput 事件 */
+    runId?: string
+}
+
+export interface ProcessOutput {
+    code: number | null             // 被信号结束或超时时为 null
+    success: boolean
+    stdout: string
+    stderr: string
+    timedOut: boolean
+    truncated: boolean
+    duration: number
+}
+
+export interface ProcessOutputLine {
+    runId: string
+    stream: 'stdout' | 'stderr'
+    line: string
+}
+
+/**
+ * 运行程序
+ * @param options 运行选项
+ * @returns 退出码与输出
+ */
+export async function runProcess(options: ProcessOptions): Promise<ProcessOutput> {
+    if (!backend.isDesktop()) throw new Error('运行程序仅支持桌面端')
+    const output = await backend.call('sys:runProcess', { data: options })
+    // 后端命令失败时 backend.call 返回 undefined（如程序不在白名单中）
+    if (output === undefined) throw new Error(`运行程序失败: ${options.program}`)
+    return output
+}
+
+/**
+ * 监听带有 runId 的程序输出
+ * @param runId 运行 ID
+ * @param callBack 每行输出的回调
+ */
+export function watchProcessOutput(runId: string, callBack: (line: ProcessOutputLine) => void) {
+    backend.addListener('sys:processOutput', (event: any) => {
+        if (event.payload?.runId === runId) callBack(event.payload)
+    })
+}
+
+export async function getProcessAllowlist(): Promise<string[]> {
+    if (!backend.isDesktop()) return []
+    return await backend.call('sys:getProcessAllowlist') || []
+}
+
+/**
+ * 设置程序白名单，新增的程序需要用户在系统对话框中确认
+ * @returns 是否保存成功
+ */
+export async function setProcessAllowlist(allowlist: string[]): Promise<boolean> {
+    return await backend.call('sys:setProcessAllowlist', allowlist) !== undefined
+}
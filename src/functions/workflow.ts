@@ -1,6 +1,6 @@
 /**
  * 工作流存储模块
- * 支持 LocalStorage (Web) 和 Tauri Store (Desktop)
+ * 支持 LocalStorage (Web) 和后端文件存储 (Desktop)
  */
 
 import type { Node, Edge } from '@vue-flow/core'
@@ -24,6 +24,8 @@ export interface WorkflowData {
     startParams?: { [key: string]: any }
     /** 是否启用（用于切换执行/启用状态） */
     enabled?: boolean
+    /** 执行超时时间（毫秒），未设置时为 60 秒 */
+    timeout?: number
     nodes: Node[]                   // 节点列表
     edges: Edge[]                   // 连接线列表
     createdAt: number               // 创建时间戳
@@ -47,12 +49,35 @@ export interface WorkflowListItem {
     updatedAt: number
 }
 
+/**
+ * 工作流历史版本（仅桌面端）
+ */
+export interface WorkflowRevision {
+    id: number                      // 版本 ID
+    timestamp: number               // 保存时间戳
+    message?: string                // 修改说明
+    workflow?: WorkflowData         // 版本内容（列表中不包含）
+}
+
+/**
+ * 两个版本之间按 ID 比较的变化
+ */
+export interface WorkflowChangeSet {
+    added: string[]
+    removed: string[]
+    changed: string[]
+}
+
+export interface WorkflowRevisionDiff {
+    nodes: WorkflowChangeSet
+    edges: WorkflowChangeSet
+}
+
 /**
  * 工作流存储管理器
  */
 export class WorkflowStorage {
     private static readonly STORAGE_KEY = 'renflow_workflows'
-    private static readonly STORE_FILE = 'workflows.dat'
 
     /**
      * 生成唯一 ID
@@ -64,9 +89,10 @@ export class WorkflowStorage {
     /**
      * 保存工作流
      * @param workflow 工作流数据
+     * @param message 修改说明（桌面端记录在历史版本中）
      * @returns 保存后的工作流数据（包含 ID）
      */
-    static async save(workflow: Partial<WorkflowData>): Promise<WorkflowData> {
+    static async save(workflow: Partial<WorkflowData>, message?: string): Promise<WorkflowData> {
         const now = Date.now()
 
         // 如果没有 ID，生成新 ID
@@ -96,8 +122,8 @@ export class WorkflowStorage {
 
         try {
             if (backend.isDesktop()) {
-                // Tauri 环境：使用 Tauri Store
-                await this.saveTauriStore(fullWorkflow)
+                // Tauri 环境：由后端保存为单独文件
+                await this.saveTauriStore(fullWorkflow, message)
             } else {
                 // Web 环境：使用 LocalStorage
                 await this.saveLocalStorage(fullWorkflow)
@@ -165,6 +191,43 @@ export class WorkflowStorage {
         }
     }
 
+    /**
+     * 获取工作流的历史版本列表（从新到旧），Web 环境不支持历史版本
+     * @param id 工作流 ID
+     */
+    static async revisions(id: string): Promise<WorkflowRevision[]> {
+        if (!backend.isDesktop()) return []
+        return await backend.call('wf:revisions', id) || []
+    }
+
+    /**
+     * 比较两个历史版本
+     * @param id 工作流 ID
+     * @param from 旧版本 ID
+     * @param to 新版本 ID，不传时与当前版本比较
+     */
+    static async diff(id: string, from: number, to?: number): Promise<WorkflowRevisionDiff | null> {
+        if (!backend.isDesktop()) return null
+        return await backend.call('wf:diff', { id, from, to }) || null
+    }
+
+    /**
+     * 将历史版本恢复为当前版本
+     * @param id 工作流 ID
+     * @param revision 版本 ID
+     * @returns 恢复后的工作流数据
+     */
+    static async restore(id: string, revision: number): Promise<WorkflowData | null> {
+        if (!backend.isDesktop()) return null
+        const workflow = await backend.call('wf:restore', { id, revision })
+        if (workflow) {
+            logger.add(LogType.INFO, `工作流已恢复到版本 ${revision}: ${id}`)
+        } else {
+            logger.add(LogType.ERR, `恢复工作流版本失败: ${id}`)
+        }
+        return workflow || null
+    }
+
     // ==================== LocalStorage 实现 ====================
 
     /**
@@ -235,42 +298,30 @@ export class WorkflowStorage {
         }
     }
 
-    // ==================== Tauri Store 实现 ====================
+    // ==================== 后端存储实现 ====================
+    // 每个工作流由后端保存为单独的文件，见 src-tauri/src/workflow_store.rs
 
     /**
-     * 使用 Tauri Store 保存工作流
+     * 使用后端存储保存工作流
      */
-    private static async saveTauriStore(workflow: WorkflowData): Promise<void> {
-        // 获取现有工作流列表
-        const workflows = await this.getAllFromTauriStore()
-        const index = workflows.findIndex(w => w.id === workflow.id)
-
-        if (index >= 0) {
-            workflows[index] = workflow
-        } else {
-            workflows.push(workflow)
-        }
-
-        // 保存到 Tauri Store
-        await backend.call('sys:setStoreValue', {
-            key: this.STORAGE_KEY,
-            value: JSON.stringify(workflows)
-        })
+    private static async saveTauriStore(workflow: WorkflowData, message?: string): Promise<void> {
+        const result = await backend.call('wf:save', { data: workflow, message })
+        // 后端命令失败时 backend.call 返回 undefined
+        if (result === undefined) throw new Error(`保存工作流失败: ${workflow.id}`)
     }
 
     /**
-     * 使用 Tauri Store 加载工作流
+     * 使用后端存储加载工作流
      */
     private static async loadTauriStore(id: string): Promise<WorkflowData | null> {
-        const workflows = await this.getAllFromTauriStore()
-        return workflows.find(w => w.id === id) || null
+        return (await backend.call('wf:get', id)) || null
     }
 
     /**
-     * 使用 Tauri Store 获取工作流列表
+     * 使用后端存储获取工作流列表
      */
     private static async listTauriStore(): Promise<WorkflowListItem[]> {
-        const workflows = await this.getAllFromTauriStore()
+        const workflows: WorkflowListItem[] = await backend.call('wf:list') || []
         return workflows.map(w => ({
             id: w.id,
             name: w.name,
@@ -287,31 +338,10 @@ export class WorkflowStorage {
     }
 
     /**
-     * 使用 Tauri Store 删除工作流
+     * 使用后端存储删除工作流
      */
     private static async deleteTauriStore(id: string): Promise<void> {
-        const workflows = await this.getAllFromTauriStore()
-        const filtered = workflows.filter(w => w.id !== id)
-
-        await backend.call('sys:setStoreValue', {
-            key: this.STORAGE_KEY,
-            value: JSON.stringify(filtered)
-        })
-    }
-
-    /**
-     * 从 Tauri Store 获取所有工作流
-     */
-    private static async getAllFromTauriStore(): Promise<WorkflowData[]> {
-        const data = await backend.call('sys:getStoreValue', { key: this.STORAGE_KEY })
-
-        if (!data) return []
-
-        try {
-            return JSON.parse(data as string)
-        } catch (error) {
-            logger.add(LogType.ERR, 'Tauri Store 数据解析失败', error)
-            return []
-        }
+        const result = await backend.call('wf:delete', id)
+        if (result === undefined) throw new Error(`删除工作流失败: ${id}`)
     }
 }